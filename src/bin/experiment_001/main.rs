@@ -9,6 +9,10 @@ const CAMERA_ZOOM_SPEED: f32 = 1.1;
 const CAMERA_ZOOM_MINIMUM: f32 = PI / 2.0;
 const CAMERA_ZOOM_MAXIMUM: f32 = PI / 1000.0;
 
+const COMMAND_POD_MESH_PATH: &str = "experiment_001/command_pod.glb#Mesh0/Primitive0";
+const TORUS_MESH_PATH: &str = "experiment_001/torus.glb#Mesh0/Primitive0";
+const EARTH_MESH_PATH: &str = "experiment_001/earth.glb#Mesh0/Primitive0";
+
 use std::f32::consts::PI;
 
 use bevy::{
@@ -16,14 +20,22 @@ use bevy::{
     input::mouse::{MouseMotion, MouseWheel},
     log::Level,
     prelude::*,
-    utils::tracing::span,
+    tasks::Task,
+    utils::{tracing::span, HashMap},
 };
 use bevy_rapier3d::prelude::*;
+use bevy_space_program::{
+    colliders::{generate_collider_async, poll_collider_task, ColliderShapes, PhysicsMaterials},
+    loading::{AssetLoadingPlugin, LoadingAssets, LoadingProgress},
+    reset::{ResetScenePlugin, ResetToInitial, Respawnable},
+    targeting::TargetingTypesPlugin,
+};
 use rand::Rng;
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
 enum AppState {
     Loading,
+    LoadingFailed,
     Generating,
     Spawning,
     Running,
@@ -39,6 +51,11 @@ fn main() {
             brightness: 100.0,
         })
         .insert_resource(Msaa::Sample8)
+        /* The static Earth can use an exact trimesh; the dynamic pod and torus links need convex parts */
+        .insert_resource(
+            ColliderShapes::default().with(EARTH_MESH_PATH, ComputedColliderShape::TriMesh),
+        )
+        .init_resource::<PhysicsMaterials>()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: String::from(NAME),
@@ -48,14 +65,26 @@ fn main() {
             ..default()
         }))
         .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugins(AssetLoadingPlugin {
+            loading: AppState::Loading,
+            ready: AppState::Generating,
+            failed: Some(AppState::LoadingFailed),
+        })
         .add_plugins(RapierDebugRenderPlugin {
             enabled: false,
             style: DebugRenderStyle { ..default() },
             mode: DebugRenderMode::default(),
         })
+        .add_plugins((ResetScenePlugin, TargetingTypesPlugin))
         .add_systems(Startup, initiate_asset_loading)
         .add_systems(Startup, spawn_camera)
-        .add_systems(Update, app_loading.run_if(in_state(AppState::Loading)))
+        .add_systems(Startup, spawn_loading_text)
+        .add_systems(
+            Update,
+            update_loading_text.run_if(in_state(AppState::Loading)),
+        )
+        .add_systems(OnExit(AppState::Loading), despawn_loading_text)
+        .add_systems(OnEnter(AppState::Generating), start_collider_generation)
         .add_systems(
             Update,
             generate_resources.run_if(in_state(AppState::Generating)),
@@ -93,6 +122,13 @@ pub struct ColliderAssets {
     pub earth_collider: Collider,
 }
 
+/// Collider generation in flight during `AppState::Generating`, keyed by mesh asset path.
+#[derive(Resource, Default)]
+pub struct ColliderTasks {
+    tasks: HashMap<&'static str, Task<Option<Collider>>>,
+    colliders: HashMap<&'static str, Collider>,
+}
+
 #[derive(Component)]
 pub struct Torus;
 
@@ -105,114 +141,141 @@ pub struct EarthPod;
 #[derive(Component)]
 pub struct TheCamera;
 
-fn initiate_asset_loading(mut commands: Commands, asset_server: Res<AssetServer>) {
+#[derive(Component)]
+pub struct LoadingText;
+
+fn initiate_asset_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
     let span = span!(Level::INFO, "initiate_asset_loading()");
     let _enter = span.enter();
     debug!("start");
     commands.insert_resource(MeshAssets {
-        command_pod_mesh: asset_server.load("experiment_001/command_pod.glb#Mesh0/Primitive0"),
-        torus_mesh: asset_server.load("experiment_001/torus.glb#Mesh0/Primitive0"),
-        earth_mesh: asset_server.load("experiment_001/earth.glb#Mesh0/Primitive0"),
+        command_pod_mesh: loading_assets.track(asset_server.load(COMMAND_POD_MESH_PATH)),
+        torus_mesh: loading_assets.track(asset_server.load(TORUS_MESH_PATH)),
+        earth_mesh: loading_assets.track(asset_server.load(EARTH_MESH_PATH)),
     });
     commands.insert_resource(SceneAssets {
-        command_pod_scene: asset_server.load("experiment_001/command_pod.glb#Scene0"),
-        torus_scene: asset_server.load("experiment_001/torus.glb#Scene0"),
-        earth_scene: asset_server.load("experiment_001/earth.glb#Scene0"),
+        command_pod_scene: loading_assets
+            .track(asset_server.load("experiment_001/command_pod.glb#Scene0")),
+        torus_scene: loading_assets.track(asset_server.load("experiment_001/torus.glb#Scene0")),
+        earth_scene: loading_assets.track(asset_server.load("experiment_001/earth.glb#Scene0")),
     });
     debug!("stop");
 }
 
-fn app_loading(
+fn start_collider_generation(
+    mut commands: Commands,
     meshes: Res<Assets<Mesh>>,
     mesh_assets: Res<MeshAssets>,
-    scenes: Res<Assets<Scene>>,
-    scene_assets: Res<SceneAssets>,
-    mut state: ResMut<NextState<AppState>>,
+    collider_shapes: Res<ColliderShapes>,
 ) {
-    let span = span!(Level::INFO, "app_loading()");
+    let span = span!(Level::INFO, "start_collider_generation()");
     let _enter = span.enter();
     debug!("start");
-    let command_pod_scene_option = scenes.get(&scene_assets.command_pod_scene);
-    let torus_scene_option = scenes.get(&scene_assets.torus_scene);
-    let earth_scene_option = scenes.get(&scene_assets.earth_scene);
-    let command_pod_mesh_option = meshes.get(&mesh_assets.command_pod_mesh);
-    let torus_mesh_option = meshes.get(&mesh_assets.torus_mesh);
-    let earth_mesh_option = meshes.get(&mesh_assets.earth_mesh);
-    let mut scenes_loaded = false;
-    match (
-        command_pod_scene_option,
-        torus_scene_option,
-        earth_scene_option,
-    ) {
-        (Some(_), Some(_), Some(_)) => {
-            debug!("scenes loaded");
-            scenes_loaded = true;
-        }
-        _ => print!("."),
-    }
-    let mut meshes_loaded = false;
-    match (
-        command_pod_mesh_option,
-        torus_mesh_option,
-        earth_mesh_option,
-    ) {
-        (Some(_), Some(_), Some(_)) => {
-            debug!("meshes loaded");
-            meshes_loaded = true;
-        }
-        _ => print!("."),
-    }
-    if scenes_loaded && meshes_loaded {
-        debug!("loading complete");
-        state.set(AppState::Generating);
+    let mut collider_tasks = ColliderTasks::default();
+    for (path, handle) in [
+        (COMMAND_POD_MESH_PATH, &mesh_assets.command_pod_mesh),
+        (TORUS_MESH_PATH, &mesh_assets.torus_mesh),
+        (EARTH_MESH_PATH, &mesh_assets.earth_mesh),
+    ] {
+        let mesh = meshes.get(handle).expect(path).clone();
+        collider_tasks.tasks.insert(
+            path,
+            generate_collider_async(
+                path.to_string(),
+                mesh,
+                collider_shapes.shape_for(path).clone(),
+            ),
+        );
     }
+    commands.insert_resource(collider_tasks);
     debug!("stop");
 }
 
 fn generate_resources(
     mut commands: Commands,
-    meshes: Res<Assets<Mesh>>,
-    mesh_assets: Res<MeshAssets>,
+    mut collider_tasks: ResMut<ColliderTasks>,
     mut state: ResMut<NextState<AppState>>,
 ) {
     let span = span!(Level::INFO, "generate_resources()");
     let _enter = span.enter();
     debug!("start");
-    let command_pod_mesh = meshes.get(&mesh_assets.command_pod_mesh);
-    debug!("got command pod mesh");
-    let torus_mesh = meshes.get(&mesh_assets.torus_mesh);
-    debug!("got torus mesh");
-    let earth_mesh = meshes.get(&mesh_assets.earth_mesh);
-    debug!("got earth mesh");
-    let command_pod_collider = Collider::from_bevy_mesh(
-        command_pod_mesh.expect("command_pod_mesh"),
-        &ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
-    );
-    debug!("generated command pod collider");
-    let torus_collider = Collider::from_bevy_mesh(
-        torus_mesh.expect("torus_mesh"),
-        &ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
-    );
-    debug!("generated torus collider");
-    let earth_collider = Collider::from_bevy_mesh(
-        earth_mesh.expect("earth_mesh"),
-        &ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
-    );
-    debug!("generated earth collider");
-    match (command_pod_collider, torus_collider, earth_collider) {
-        (Some(cp), Some(t), Some(e)) => {
-            commands.insert_resource(ColliderAssets {
-                command_pod_collider: cp,
-                torus_collider: t,
-                earth_collider: e,
-            });
+    let ColliderTasks { tasks, colliders } = &mut *collider_tasks;
+    tasks.retain(|path, task| match poll_collider_task(task) {
+        Some(Some(collider)) => {
+            debug!("generated {:?} collider", path);
+            colliders.insert(path, collider);
+            false
         }
-        _ => {}
+        Some(None) => {
+            error!("failed to generate {:?} collider", path);
+            colliders.insert(path, Collider::default());
+            false
+        }
+        None => true,
+    });
+    if tasks.is_empty() {
+        let mut take_collider = |path: &str| colliders.remove(path).unwrap_or_default();
+        commands.insert_resource(ColliderAssets {
+            command_pod_collider: take_collider(COMMAND_POD_MESH_PATH),
+            torus_collider: take_collider(TORUS_MESH_PATH),
+            earth_collider: take_collider(EARTH_MESH_PATH),
+        });
+        commands.remove_resource::<ColliderTasks>();
+        state.set(AppState::Spawning);
     }
-    state.set(AppState::Spawning);
     debug!("stop");
 }
 
+fn spawn_loading_text(mut commands: Commands) {
+    commands.spawn((
+        LoadingText,
+        TextBundle::from_section(
+            "Loading",
+            TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(20.0),
+            left: Val::Px(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_loading_text(
+    progress: Res<LoadingProgress>,
+    mut loading_text_query: Query<&mut Text, With<LoadingText>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+    for mut text in loading_text_query.iter_mut() {
+        text.sections[0].value = format!(
+            "Loading {:.0}% ({}/{})",
+            progress.fraction * 100.0,
+            progress.loaded,
+            progress.total
+        );
+    }
+}
+
+fn despawn_loading_text(
+    mut commands: Commands,
+    loading_text_query: Query<Entity, With<LoadingText>>,
+) {
+    for entity in loading_text_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 fn spawn_camera(mut commands: Commands) {
     let span = span!(Level::INFO, "spawn_camera()");
     let _enter = span.enter();
@@ -243,6 +306,7 @@ fn initiate_spawning(
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
     collider_assets: Res<ColliderAssets>,
+    physics_materials: Res<PhysicsMaterials>,
     mut state: ResMut<NextState<AppState>>,
 ) {
     let span = span!(Level::INFO, "initiate_spawning()");
@@ -270,19 +334,22 @@ fn initiate_spawning(
             scene: scene_assets.earth_scene.clone(),
             ..default()
         },))
-        .insert(Restitution::coefficient(0.1))
+        .insert(physics_materials.bundle("rock"))
         .insert(TransformBundle::from(Transform::from_xyz(0.0, 0.0, 0.0)));
 
     /* Create the command pod. */
     commands
         .spawn((
             CommandPod,
+            ResetToInitial,
             SceneBundle {
                 scene: scene_assets.command_pod_scene.clone(),
                 ..default()
             },
             RigidBody::Dynamic,
+            Velocity::zero(),
             collider_assets.command_pod_collider.clone(),
+            physics_materials.bundle("hull"),
         ))
         .insert(TransformBundle::from_transform(
             Transform::from_xyz(0.0, EARTH_RADIUS + 2.0, 0.0), // * Transform::from_scale(Vec3 { x: 100.0, y: 100.0, z: 100.0 })
@@ -297,8 +364,7 @@ fn initiate_spawning(
                 ..default()
             },))
             .insert(collider_assets.torus_collider.clone())
-            .insert(Restitution::coefficient(0.01))
-            .insert(Friction::coefficient(4.0))
+            .insert(physics_materials.bundle("metal"))
             .insert(Velocity {
                 linvel: Vec3 {
                     x: (rand::thread_rng().gen_range(0..100) as f32) / 100.0,
@@ -311,7 +377,7 @@ fn initiate_spawning(
                     z: 0.0,
                 },
             })
-            .insert(Torus)
+            .insert((Torus, ResetToInitial))
             .insert(TransformBundle::from(
                 Transform::from_xyz(0.0, EARTH_RADIUS + 100.0 - ((i as f32) / 1.9), 0.0)
                     * Transform::from_rotation(Quat::from_rotation_y(PI / 2.0 * (i as f32))),
@@ -443,33 +509,12 @@ fn state_controls(
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
     collider_assets: Res<ColliderAssets>,
+    physics_materials: Res<PhysicsMaterials>,
     keyboard_button_input: Res<ButtonInput<KeyCode>>,
-    mut pod_query: Query<(&mut Transform, &mut Velocity), With<CommandPod>>,
 ) {
     let span = span!(Level::DEBUG, "camera_controls()");
     let _enter = span.enter();
     debug!("start");
-    if keyboard_button_input.pressed(KeyCode::KeyR) {
-        for (mut pod_transform, mut pod_velocity) in pod_query.iter_mut() {
-            pod_transform.translation = Vec3 {
-                x: 0.0,
-                y: 40.0,
-                z: 0.0,
-            };
-            pod_transform.rotation = Quat::from_rotation_x(0.0);
-            pod_velocity.linvel = Vec3 {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            };
-            pod_velocity.angvel = Vec3 {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            };
-        }
-    }
-
     if keyboard_button_input.just_pressed(KeyCode::KeyI) {
         commands
             .spawn(RigidBody::Dynamic)
@@ -478,7 +523,7 @@ fn state_controls(
                 ..default()
             })
             .insert(collider_assets.command_pod_collider.clone())
-            .insert(Restitution::coefficient(0.0))
+            .insert(physics_materials.bundle("hull"))
             .insert(TransformBundle::from(Transform::from_xyz(0.0, 40.0, 0.0)))
             .insert(Velocity {
                 linvel: Vec3 {
@@ -492,7 +537,7 @@ fn state_controls(
                     z: 0.0,
                 },
             })
-            .insert(CommandPod);
+            .insert((CommandPod, Respawnable));
     }
     debug!("stop");
 }
@@ -1,24 +1,48 @@
-use std::{f32::consts::PI, time::Duration};
+use std::f32::consts::PI;
 
 use bevy::{
-    app::AppExit,
-    core_pipeline::Skybox,
     log::Level,
     prelude::*,
-    render::{
-        camera::ScalingMode,
-        render_resource::{TextureViewDescriptor, TextureViewDimension},
-        view::RenderLayers,
-    },
+    render::camera::Exposure,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
     transform::TransformSystem,
     utils::tracing::span,
-    window::{CursorGrabMode, PresentMode, PrimaryWindow, WindowMode},
+    window::{PresentMode, PrimaryWindow, WindowMode},
 };
 use bevy_rapier3d::prelude::*;
 use bevy_scene_hook::{HookPlugin, HookedSceneBundle, SceneHook};
-use bevy_space_program::mipmap::{
-    generate_mipmaps, MipmapGeneratorPlugin, MipmapGeneratorSettings,
+use bevy_space_program::{
+    camera::{
+        default_space_camera, default_space_projection, AutopilotPlugin, CameraKeySchemePlugin,
+        InertialDampingPlugin,
+    },
+    capture::CapturePlugin,
+    colliders::{insert_auto_collider, AutoColliderPlugin},
+    gravity::{Attracted, GravityAttractor, GravityPlugin},
+    hud::{ClosestApproachReadoutPlugin, TargetingModeIndicatorPlugin},
+    input::{
+        grab_cursor, release_cursor, Action, CursorLockPlugin, KeyBindings, QuitConfirmPlugin,
+        SelectInputPlugin, SelectTargetPressed,
+    },
+    layers::{BACKGROUND, FOREGROUND, OVERLAY},
+    lighting::{LightingPlugin, LightingSettings},
+    loading::{AssetLoadingPlugin, LoadingAssets},
+    maneuver::{ManeuverNode, ManeuverPlugin},
+    mipmap::{generate_mipmaps, MipmapGeneratorPlugin, MipmapGeneratorSettings},
+    navball::{NavBallAttitude, NavBallTargetUp},
+    orbits::ReferenceAxesPlugin,
+    plugins::SpaceProgramPlugins,
+    projectile::{spawn_projectile, ProjectilePlugin, ProjectileSpec},
+    reset::ResetToInitial,
+    reticle::{ReticlePlugin, ReticleSettings},
+    skybox::{generate_starfield_cubemap, set_skybox, CubemapSkybox, SkyboxPlugin},
+    targeting::{
+        pick_nearest_onscreen, pick_under_crosshair, project_to_overlay, ComponentInfo,
+        CurrentTarget, NearestObject, OverlayProjection, TargetClass, TargetingMode,
+        TargetingSettings, ValidTarget,
+    },
+    time::{PhysicsPause, TimeScale},
+    trajectory::TrajectoryPlugin,
 };
 use big_space::{
     camera::{CameraController, CameraInput},
@@ -27,20 +51,14 @@ use big_space::{
     FloatingOrigin, GridCell, IgnoreFloatingOrigin,
 };
 
-const BACKGROUND: RenderLayers = RenderLayers::layer(1);
-const FOREGROUND: RenderLayers = RenderLayers::layer(2);
-const OVERLAY: RenderLayers = RenderLayers::layer(3);
-
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
 enum AppState {
     Loading,
+    LoadingFailed,
     PreRunning,
     Running,
 }
 
-#[derive(Default, Reflect, GizmoConfigGroup)]
-struct OverlayGizmos {}
-
 fn main() {
     println!("main() start");
     let app = App::new()
@@ -53,10 +71,48 @@ fn main() {
             bevy_framepace::FramepacePlugin,
             // RapierDebugRenderPlugin::default(),  // Causes Rapier to render meshes representing colliders.
         ))
-        .add_plugins((RapierPhysicsPlugin::<NoUserData>::default(),))
+        .add_plugins((
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            SpaceProgramPlugins,
+            GravityPlugin,
+            AutoColliderPlugin,
+            SkyboxPlugin,
+            ManeuverPlugin,
+            CapturePlugin,
+            AssetLoadingPlugin {
+                loading: AppState::Loading,
+                ready: AppState::PreRunning,
+                failed: Some(AppState::LoadingFailed),
+            },
+            ProjectilePlugin,
+        ))
         .add_plugins(HookPlugin)
         .add_plugins(MipmapGeneratorPlugin)
-        .init_gizmo_group::<OverlayGizmos>()
+        .add_plugins((
+            ReticlePlugin,
+            InertialDampingPlugin,
+            AutopilotPlugin,
+            ReferenceAxesPlugin,
+            LightingPlugin,
+            CameraKeySchemePlugin,
+            CursorLockPlugin,
+            QuitConfirmPlugin,
+            SelectInputPlugin,
+            TargetingModeIndicatorPlugin,
+            TrajectoryPlugin,
+            ClosestApproachReadoutPlugin,
+        ))
+        /* Keeps this scene's original look: Bevy's default ambient light and exposure, no bloom */
+        .insert_resource(LightingSettings {
+            ambient: AmbientLight::default(),
+            exposure: Exposure::default(),
+            bloom: None,
+        })
+        .insert_resource(ReticleSettings {
+            occlusion_test: true,
+            ..default()
+        })
+        .init_resource::<KeyBindings>()
         .insert_resource(MipmapGeneratorSettings {
             anisotropic_filtering: 16,
             ..default()
@@ -79,10 +135,6 @@ fn main() {
             Startup,
             (initiate_asset_loading, main_camera_setup).run_if(in_state(AppState::Loading)),
         )
-        .add_systems(
-            Update,
-            (wait_for_asset_loading).run_if(in_state(AppState::Loading)),
-        )
         .add_systems(
             Update,
             (
@@ -94,11 +146,13 @@ fn main() {
         )
         .add_systems(
             PreUpdate,
-            (miscellaneous_input_handling, spawn_pellet).run_if(in_state(AppState::Running)),
-        )
-        .add_systems(
-            Update,
-            (tick_timers, update_hud).run_if(in_state(AppState::Running)),
+            (
+                miscellaneous_input_handling,
+                spawn_pellet,
+                cycle_skybox,
+                toggle_maneuver_node,
+            )
+                .run_if(in_state(AppState::Running)),
         )
         .add_systems(
             PostUpdate,
@@ -113,88 +167,6 @@ fn main() {
     app
 }
 
-fn wait_for_asset_loading(
-    meshes: Res<Assets<Mesh>>,
-    mesh_assets: Res<MeshAssets>,
-    scenes: Res<Assets<Scene>>,
-    scene_assets: Res<SceneAssets>,
-    mut skyboxes: ResMut<Assets<Image>>,
-    skybox_assets: Res<SkyBoxAssets>,
-    mut state: ResMut<NextState<AppState>>,
-    fpopeq: Query<Entity, With<FloatingOriginPlaceholderComponent>>,
-) {
-    let span = span!(Level::INFO, "wait_for_asset_loading()");
-    let _enter = span.enter();
-    debug!("start");
-    let nav_ring_scene_option = scenes.get(&scene_assets.nav_ball_scene);
-    let nav_ball_scene_option = scenes.get(&scene_assets.nav_ball_scene);
-    let nav_ball_orbital_scene_option = scenes.get(&scene_assets.nav_ball_orbital_scene);
-    let inverted_xyz_scene_option = scenes.get(&scene_assets.inverted_xyz_ball_scene);
-    let jupiter_scene_option = scenes.get(&scene_assets.jupiter_scene);
-    let nav_ring_mesh_option = meshes.get(&mesh_assets.nav_ball_mesh);
-    let nav_ball_mesh_option = meshes.get(&mesh_assets.nav_ball_mesh);
-    let nav_ball_orbital_mesh_option = meshes.get(&mesh_assets.nav_ball_orbital_mesh);
-    let inverted_xyz_mesh_option = meshes.get(&mesh_assets.inverted_xyz_ball_mesh);
-    let jupiter_mesh_option = meshes.get(&mesh_assets.jupiter_mesh);
-    let milky_way_skybox_option = skyboxes.get(&skybox_assets.milky_way_skybox);
-    let mut all_assets_loaded = false;
-    match (
-        nav_ring_scene_option,
-        nav_ball_scene_option,
-        nav_ball_orbital_scene_option,
-        inverted_xyz_scene_option,
-        jupiter_scene_option,
-        nav_ring_mesh_option,
-        nav_ball_mesh_option,
-        nav_ball_orbital_mesh_option,
-        inverted_xyz_mesh_option,
-        jupiter_mesh_option,
-        milky_way_skybox_option,
-    ) {
-        (
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-            Some(_),
-        ) => {
-            debug!("all assets loaded");
-            all_assets_loaded = true;
-        }
-        _ => {}
-    }
-    if all_assets_loaded {
-        debug!("loading complete");
-        state.set(AppState::PreRunning);
-    }
-
-    // let mut skybox_ready = false;
-    // while !skybox_ready {
-    match skyboxes.get_mut(skybox_assets.milky_way_skybox.id()) {
-        Some(image) => {
-            image.reinterpret_stacked_2d_as_array(image.height() / image.width());
-            image.texture_view_descriptor = Some(TextureViewDescriptor {
-                dimension: Some(TextureViewDimension::Cube),
-                ..default()
-            });
-            // skybox_ready = true;
-        }
-        None => {}
-    }
-    // }
-
-    for each in fpopeq.iter() {
-        debug!("{:?}", each);
-    }
-    debug!("stop");
-}
-
 #[derive(Resource, Debug, Default)]
 pub struct MeshAssets {
     pub nav_ring_mesh: Handle<Mesh>,
@@ -216,52 +188,15 @@ pub struct SceneAssets {
 #[derive(Resource, Debug, Default)]
 pub struct SkyBoxAssets {
     pub milky_way_skybox: Handle<Image>,
-}
-
-#[derive(Resource, Debug)]
-pub struct TargetResource {
-    target: Option<Entity>,
-}
-
-#[derive(Debug)]
-enum CurrentCommand {
-    NavTargetModeSelect,
-}
-
-#[derive(Component, Deref, DerefMut, Debug)]
-struct CommandEntryTimer(Timer);
-
-#[derive(Resource, Debug)]
-pub struct CommandEntryResource {
-    current_command_entry: Option<CurrentCommand>,
-}
-
-#[derive(Debug)]
-enum NavTargetMode {
-    Nearest,
-    Cursor,
-}
-
-#[derive(Resource, Debug)]
-pub struct OpsModeResource {
-    current_nav_mode: NavTargetMode,
+    pub starfield_skybox: Handle<Image>,
 }
 
 #[derive(Component)]
 pub struct Planet;
 
-#[derive(Component)]
-pub struct ValidTarget;
-
-#[derive(Component)]
-pub struct HUD;
-
 #[derive(Component)]
 pub struct TargetDisplay;
 
-#[derive(Component)]
-pub struct FloatingOriginPlaceholderComponent;
-
 #[derive(Component)]
 pub struct TargetObjectCrosshair;
 
@@ -290,44 +225,51 @@ fn main_camera_setup(mut commands: Commands, space: Res<RootReferenceFrame<i64>>
         BACKGROUND,
         Camera3dBundle {
             transform: cam_transform,
-            projection: Projection::Perspective(PerspectiveProjection {
-                near: 1e-18,
-                ..default()
-            }),
+            projection: default_space_projection(),
             ..default()
         },
         cam_cell,
         FloatingOrigin,
-        CameraController::default()
-            .with_speed_bounds([10e-18, 10e35])
-            .with_smoothness(0.9, 0.8)
-            .with_speed(1.0),
+        default_space_camera(),
     ));
     debug!("stop");
 }
 
-fn initiate_asset_loading(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn initiate_asset_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
     let span = span!(Level::INFO, "initiate_asset_loading()");
     let _enter = span.enter();
     debug!("start");
     commands.insert_resource(MeshAssets {
-        nav_ring_mesh: asset_server.load("experiment_002/nav_ring.glb#Mesh0/Primitive0"),
-        nav_ball_mesh: asset_server.load("experiment_002/nav_ball.glb#Mesh0/Primitive0"),
-        nav_ball_orbital_mesh: asset_server
-            .load("experiment_002/nav_ball_orbital.glb#Mesh0/Primitive0"),
-        inverted_xyz_ball_mesh: asset_server
-            .load("experiment_002/inverted_xyz_ball.glb#Mesh0/Primitive0"),
-        jupiter_mesh: asset_server.load("experiment_002/jupiter.glb#Mesh0/Primitive0"),
+        nav_ring_mesh: loading_assets
+            .track(asset_server.load("experiment_002/nav_ring.glb#Mesh0/Primitive0")),
+        nav_ball_mesh: loading_assets
+            .track(asset_server.load("experiment_002/nav_ball.glb#Mesh0/Primitive0")),
+        nav_ball_orbital_mesh: loading_assets
+            .track(asset_server.load("experiment_002/nav_ball_orbital.glb#Mesh0/Primitive0")),
+        inverted_xyz_ball_mesh: loading_assets
+            .track(asset_server.load("experiment_002/inverted_xyz_ball.glb#Mesh0/Primitive0")),
+        jupiter_mesh: loading_assets
+            .track(asset_server.load("experiment_002/jupiter.glb#Mesh0/Primitive0")),
     });
     commands.insert_resource(SceneAssets {
-        nav_ring_scene: asset_server.load("experiment_002/nav_ring.glb#Scene0"),
-        nav_ball_scene: asset_server.load("experiment_002/nav_ball.glb#Scene0"),
-        nav_ball_orbital_scene: asset_server.load("experiment_002/nav_ball_orbital.glb#Scene0"),
-        inverted_xyz_ball_scene: asset_server.load("experiment_002/inverted_xyz_ball.glb#Scene0"),
-        jupiter_scene: asset_server.load("experiment_002/jupiter.glb#Scene0"),
+        nav_ring_scene: loading_assets
+            .track(asset_server.load("experiment_002/nav_ring.glb#Scene0")),
+        nav_ball_scene: loading_assets
+            .track(asset_server.load("experiment_002/nav_ball.glb#Scene0")),
+        nav_ball_orbital_scene: loading_assets
+            .track(asset_server.load("experiment_002/nav_ball_orbital.glb#Scene0")),
+        inverted_xyz_ball_scene: loading_assets
+            .track(asset_server.load("experiment_002/inverted_xyz_ball.glb#Scene0")),
+        jupiter_scene: loading_assets.track(asset_server.load("experiment_002/jupiter.glb#Scene0")),
     });
     commands.insert_resource(SkyBoxAssets {
-        milky_way_skybox: asset_server.load("experiment_002/milky_way.png"),
+        milky_way_skybox: loading_assets.track(asset_server.load("experiment_002/milky_way.png")),
+        starfield_skybox: images.add(generate_starfield_cubemap(0, 20_000, 1024)),
     });
     debug!("stop");
 }
@@ -350,9 +292,7 @@ fn general_setup(
     };
     window.mode = WindowMode::BorderlessFullscreen;
     window.present_mode = PresentMode::Fifo;
-    window.cursor.grab_mode = CursorGrabMode::None;
-    window.cursor.visible = true;
-    cam.defaults_disabled = true;
+    release_cursor(&mut window, &mut cam);
 
     // for each_perspective_hud_entity in perspective_hud_query.iter_mut() {
     //     commands
@@ -791,103 +731,6 @@ fn general_setup(
             ));
         });
 
-    commands.insert_resource(TargetResource { target: None });
-
-    commands.insert_resource(CommandEntryResource {
-        current_command_entry: None,
-    });
-
-    commands.spawn(CommandEntryTimer(Timer::default()));
-
-    commands.insert_resource(OpsModeResource {
-        current_nav_mode: NavTargetMode::Cursor,
-    });
-
-    let hud_cam_transform = Transform::from_xyz(-7.5, 3.75, 3.0);
-    debug!("hud_cam_transform: {:?}", hud_cam_transform);
-
-    /* Orthographic Camera */
-    commands.spawn((
-        FOREGROUND,
-        Camera3dBundle {
-            transform: hud_cam_transform,
-            projection: Projection::Orthographic(OrthographicProjection {
-                scaling_mode: ScalingMode::FixedVertical(10.0),
-                ..default()
-            }),
-            // camera_3d: Camera3d {
-            //     depth_load_op: Camera3dDepthLoadOp::Load,
-            //     ..default()
-            // },
-            camera: Camera {
-                order: 1,
-                ..default()
-            },
-
-            ..default()
-        },
-        IgnoreFloatingOrigin,
-    ));
-
-    /* Orthographic NavBall */
-    commands.spawn((
-        FOREGROUND,
-        HookedSceneBundle {
-            hook: SceneHook::new(|entity, cmds| {
-                match entity.get::<Name>().map(|t| t.as_str()) {
-                    _ => cmds.insert(FOREGROUND),
-                };
-            }),
-            scene: SceneBundle {
-                scene: scene_assets.nav_ball_orbital_scene.clone(),
-                transform: Transform {
-                    translation: Vec3 {
-                        x: 0.0,
-                        y: 0.0,
-                        z: 0.0,
-                    },
-                    ..default()
-                },
-                ..default()
-            },
-        },
-        HUD,
-    ));
-    /* Orthographic NavRing */
-    commands.spawn((
-        FOREGROUND,
-        HookedSceneBundle {
-            hook: SceneHook::new(|entity, cmds| {
-                match entity.get::<Name>().map(|t| t.as_str()) {
-                    _ => cmds.insert(FOREGROUND),
-                };
-            }),
-            scene: SceneBundle {
-                scene: scene_assets.nav_ring_scene.clone(),
-                transform: Transform {
-                    translation: Vec3 {
-                        x: 0.0,
-                        y: 0.0,
-                        z: 2.0,
-                    },
-                    ..default()
-                },
-                ..default()
-            },
-        },
-    ));
-    /* Orthographic Light */
-    commands.spawn((
-        FOREGROUND,
-        DirectionalLightBundle {
-            directional_light: DirectionalLight {
-                illuminance: 10_000.0,
-                ..default()
-            },
-            transform: Transform::from_xyz(0.0, 0.0, 0.0).looking_at(-Vec3::Z, Vec3::Y),
-            ..default()
-        },
-    ));
     /* Perspective Light */
     commands.spawn((
         BACKGROUND,
@@ -901,10 +744,12 @@ fn general_setup(
     ));
 
     let perspective_hud_entity = perspective_hud_query.single_mut();
-    commands.entity(perspective_hud_entity).insert(Skybox {
-        image: skybox_assets.milky_way_skybox.clone(),
-        brightness: 1000.0,
-    });
+    commands
+        .entity(perspective_hud_entity)
+        .insert(CubemapSkybox {
+            image: skybox_assets.milky_way_skybox.clone(),
+            brightness: 1000.0,
+        });
 
     let (planet_cell, planet_pos): (GridCell<i64>, _) =
         space.imprecise_translation_to_grid(Vec3::ZERO);
@@ -915,9 +760,16 @@ fn general_setup(
         BACKGROUND,
         Planet,
         ValidTarget,
+        ComponentInfo {
+            name: "Jupiter".to_string(),
+            size: 100.0,
+        },
+        TargetClass::Planet,
+        NavBallTargetUp,
         RigidBody::Fixed,
         GravityScale(0.0),
-        Collider::ball(100.0),
+        /* Roughly 1g at the surface */
+        GravityAttractor { mu: 98_100.0 },
         // PbrBundle {
         //     mesh: mesh_handle.clone(),
         //     material: matl_handle.clone(),
@@ -925,10 +777,12 @@ fn general_setup(
         //     ..default()
         // },
         HookedSceneBundle {
+            /* Colliders come from the scene's own meshes rather than a hand sized ball */
             hook: SceneHook::new(|entity, cmds| {
                 match entity.get::<Name>().map(|t| t.as_str()) {
                     _ => cmds.insert(BACKGROUND),
                 };
+                insert_auto_collider(entity, cmds, &ComputedColliderShape::TriMesh);
             }),
             scene: SceneBundle {
                 scene: scene_assets.jupiter_scene.clone(),
@@ -955,10 +809,19 @@ fn general_setup(
     /* CubeSat (moving) */
     commands.spawn((
         BACKGROUND,
+        ResetToInitial,
         ValidTarget,
+        ComponentInfo {
+            name: "CubeSat".to_string(),
+            size: 0.5,
+        },
+        TargetClass::Vessel,
         RigidBody::Dynamic,
         Collider::cuboid(0.5, 0.5, 0.5),
         GravityScale(0.0),
+        Attracted,
+        ExternalForce::default(),
+        ReadMassProperties::default(),
         Velocity {
             linvel: Vec3 {
                 x: 0.0,
@@ -989,6 +852,11 @@ fn general_setup(
     commands.spawn((
         BACKGROUND,
         ValidTarget,
+        ComponentInfo {
+            name: "CubeSat (spinning)".to_string(),
+            size: 0.5,
+        },
+        TargetClass::Vessel,
         RigidBody::KinematicVelocityBased,
         Collider::cuboid(0.5, 0.5, 0.5),
         GravityScale(0.0),
@@ -1068,8 +936,6 @@ fn ui_setup(
 
     let (default_gizmo_config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
     default_gizmo_config.render_layers = BACKGROUND;
-    let (overlay_gizmo_config, _) = config_store.config_mut::<OverlayGizmos>();
-    overlay_gizmo_config.render_layers = OVERLAY;
 
     state.set(AppState::Running);
 }
@@ -1081,6 +947,11 @@ fn update_ui_text(
     origin: Query<GridTransformReadOnly<i64>, With<FloatingOrigin>>,
     camera: Query<&CameraController>,
     reference_frame: Res<RootReferenceFrame<i64>>,
+    physics_pause: Res<PhysicsPause>,
+    time_scale: Res<TimeScale>,
+    attitude: Res<NavBallAttitude>,
+    current_target: Res<CurrentTarget>,
+    maneuver_node: Option<Res<ManeuverNode>>,
 ) {
     let origin = origin.single();
     let translation = origin.transform.translation;
@@ -1113,10 +984,33 @@ fn update_ui_text(
         format!("Speed: {:.2e} m/s", speed)
     };
 
+    let time_scale_text = format!("Time x{}", time_scale.scale());
+
+    let attitude_text = match current_target.target {
+        Some(_) => format!(
+            "HDG {:05.1}  PIT {:+05.1}  ROL {:+06.1}",
+            attitude.heading, attitude.pitch, attitude.roll
+        ),
+        None => String::new(),
+    };
+
+    let maneuver_text = match maneuver_node {
+        Some(maneuver_node) => format!(
+            "Maneuver: {:.1} / {:.1} m/s remaining",
+            maneuver_node.remaining_delta_v, maneuver_node.delta_v
+        ),
+        None => String::new(),
+    };
+
+    let pause_text = match physics_pause.is_paused() {
+        true => "PAUSED (P resume, . step)",
+        false => "",
+    };
+
     let mut debug_text = debug_text.single_mut();
 
     debug_text.0.sections[0].value = format!(
-        "{grid_text}\n{translation_text}\n\n{real_position_f64_text}\n{real_position_f32_text}\n\n{camera_text}"
+        "{grid_text}\n{translation_text}\n\n{real_position_f64_text}\n{real_position_f32_text}\n\n{camera_text}\n{attitude_text}\n\n{maneuver_text}\n\n{time_scale_text}\n{pause_text}"
     );
 }
 
@@ -1125,7 +1019,7 @@ fn update_hud_reticles(
         (&mut Camera, &mut Transform, &GlobalTransform),
         (With<CameraController>, With<Camera3d>, Without<Camera2d>),
     >,
-    cameras: Query<&CameraController>,
+    nearest_object: Res<NearestObject>,
     objects: Query<&GlobalTransform, Without<NearestObjectCrosshair>>,
     valid_targets_query: Query<(&GlobalTransform, Entity), With<ValidTarget>>,
     mut target_display_query: Query<&mut Text, With<TargetDisplay>>,
@@ -1187,9 +1081,14 @@ fn update_hud_reticles(
         (&mut Camera, &mut Transform, &GlobalTransform),
         (With<Camera2d>, Without<Camera3d>),
     >,
-    key: Res<ButtonInput<KeyCode>>,
-    mut target_resource: ResMut<TargetResource>,
-    ops_mode_resource: Res<OpsModeResource>,
+    mut select_pressed: EventReader<SelectTargetPressed>,
+    mut target_resource: ResMut<CurrentTarget>,
+    (targeting_mode, targeting_settings, rapier_context, parent_query): (
+        Res<TargetingMode>,
+        Res<TargetingSettings>,
+        Res<RapierContext>,
+        Query<&Parent>,
+    ),
 ) {
     let span = span!(Level::INFO, "update_hud_reticles()");
     let _enter = span.enter();
@@ -1198,9 +1097,14 @@ fn update_hud_reticles(
 
     let (camera_2d, _camera_2d_transform, camera_2d_global_transform) = camera_2d_query.single();
 
-    let Some(camera_2d_viewport_rect) = camera_2d.logical_viewport_rect() else {
-        debug!("camera_2d.logical_viewport_rect() returned none");
-        return;
+    let project = |world_position| {
+        project_to_overlay(
+            camera_3d,
+            camera_3d_global_transform,
+            camera_2d,
+            camera_2d_global_transform,
+            world_position,
+        )
     };
 
     let mut cursor_target_crosshair_transform =
@@ -1209,43 +1113,24 @@ fn update_hud_reticles(
     let mut cursor_target_crosshair_visibility =
         cursor_target_crosshair_visibility_query.single_mut();
 
-    let mut cursor_target_onscreen = false;
-    let mut cursor_nearest_entity = None;
-    let mut cursor_nearest = Vec2 {
-        x: 10000000.0,
-        y: 10000000.0,
-    };
-    for (index, (each_valid_target_transform, each_valid_target_entity)) in
-        valid_targets_query.iter().enumerate()
-    {
-        trace!("{:?}: {:?}", index, each_valid_target_transform);
-        match camera_3d.world_to_viewport(
-            camera_3d_global_transform,
-            each_valid_target_transform.translation(),
-        ) {
-            Some(each_object_3d_viewport_position) => {
-                match camera_2d.viewport_to_world_2d(
-                    camera_2d_global_transform,
-                    each_object_3d_viewport_position,
-                ) {
-                    Some(each_object_2d_viewport_position) => {
-                        if each_object_2d_viewport_position.length() < cursor_nearest.length() {
-                            cursor_target_onscreen = true;
-                            cursor_nearest = each_object_2d_viewport_position;
-                            cursor_nearest_entity = Some(each_valid_target_entity);
-                        }
-                    }
-                    None => {}
-                }
-            }
-            None => {}
-        }
-    }
-    if cursor_target_onscreen {
+    let cursor_nearest = pick_nearest_onscreen(
+        valid_targets_query.iter().filter_map(
+            |(each_valid_target_transform, each_valid_target_entity)| {
+                project(each_valid_target_transform.translation())
+                    .map(|projection| (each_valid_target_entity, projection))
+            },
+        ),
+        Vec2::ZERO,
+        targeting_settings.selection_radius,
+    );
+    if let Some((_, cursor_nearest_position)) = cursor_nearest {
         *cursor_target_crosshair_visibility = Visibility::Visible;
-        cursor_target_crosshair_transform.translation.x = cursor_nearest.x;
-        cursor_target_crosshair_transform.translation.y = cursor_nearest.y;
+        cursor_target_crosshair_transform.translation.x = cursor_nearest_position.x;
+        cursor_target_crosshair_transform.translation.y = cursor_nearest_position.y;
+    } else {
+        *cursor_target_crosshair_visibility = Visibility::Hidden;
     }
+    let cursor_nearest_entity = cursor_nearest.map(|(entity, _)| entity);
 
     let mut target_object_crosshair_transform =
         target_object_crosshair_transform_query.single_mut();
@@ -1253,52 +1138,29 @@ fn update_hud_reticles(
     let mut target_object_crosshair_visibility =
         target_object_crosshair_visibility_query.single_mut();
 
-    match target_resource.target {
-        Some(target) => match objects.get(target) {
-            Ok(target_object) => {
-                let (_target_object_scale, _target_object_rotation, target_object_translation) =
-                    target_object.to_scale_rotation_translation();
-                match camera_3d
-                    .world_to_viewport(camera_3d_global_transform, target_object_translation)
-                {
-                    Some(target_object_viewport_position) => {
-                        match (
-                            camera_2d_viewport_rect.contains(target_object_viewport_position),
-                            camera_2d.viewport_to_world_2d(
-                                camera_2d_global_transform,
-                                target_object_viewport_position,
-                            ),
-                        ) {
-                            (true, Some(target_object_overlay_position)) => {
-                                *target_object_crosshair_visibility = Visibility::Visible;
-                                target_object_crosshair_transform.translation.x =
-                                    target_object_overlay_position.x;
-                                target_object_crosshair_transform.translation.y =
-                                    target_object_overlay_position.y;
-                            }
-                            (false, Some(_target_object_overlay_position)) => {
-                                *target_object_crosshair_visibility = Visibility::Hidden;
-                            }
-                            (true, None) => {
-                                *target_object_crosshair_visibility = Visibility::Visible;
-                            }
-                            (false, None) => {
-                                *target_object_crosshair_visibility = Visibility::Hidden;
-                            }
-                        }
-                    }
-                    None => {
-                        *target_object_crosshair_visibility = Visibility::Hidden;
-                    }
+    if let Some(target) = target_resource.target {
+        match objects.get(target) {
+            Ok(target_object) => match project(target_object.translation()) {
+                Some(OverlayProjection {
+                    position: target_object_overlay_position,
+                    onscreen: true,
+                }) => {
+                    *target_object_crosshair_visibility = Visibility::Visible;
+                    target_object_crosshair_transform.translation.x =
+                        target_object_overlay_position.x;
+                    target_object_crosshair_transform.translation.y =
+                        target_object_overlay_position.y;
                 }
-            }
+                _ => {
+                    *target_object_crosshair_visibility = Visibility::Hidden;
+                }
+            },
             Err(e) => debug!("{:?}", e),
-        },
-        None => {}
+        }
     }
 
-    let Some((entity, _)) = cameras.single().nearest_object() else {
-        debug!("cameras.single().nearest_object() returned none");
+    let Some(entity) = nearest_object.entity() else {
+        debug!("no nearest object");
         return;
     };
     let Ok(transform) = objects.get(entity) else {
@@ -1322,40 +1184,30 @@ fn update_hud_reticles(
 
     match target_display_query.get_single_mut() {
         Ok(mut target_display) => {
-            match camera_3d.world_to_viewport(camera_3d_global_transform, translation) {
-                Some(viewport_position) => {
-                    match (
-                        camera_2d_viewport_rect.contains(viewport_position),
-                        camera_2d
-                            .viewport_to_world_2d(camera_2d_global_transform, viewport_position),
-                    ) {
-                        (true, Some(nearest_object_overlay_position)) => {
-                            *nearest_object_crosshair_visibility = Visibility::Visible;
-                            nearest_object_crosshair_transform.translation.x =
-                                nearest_object_overlay_position.x;
-                            nearest_object_crosshair_transform.translation.y =
-                                nearest_object_overlay_position.y;
-
-                            target_text = "nearest object onscreen";
-                            overlay_text_x = format!("{:>20}", nearest_object_overlay_position.x);
-                            overlay_text_y = format!("{:>20}", nearest_object_overlay_position.y);
-                        }
-                        (false, Some(overlay_position)) => {
-                            *nearest_object_crosshair_visibility = Visibility::Hidden;
-
-                            target_text = "nearest object offscreen";
-                            overlay_text_x = format!("{:>20}", overlay_position.x);
-                            overlay_text_y = format!("{:>20}", overlay_position.y);
-                        }
-                        (true, None) => {
-                            *nearest_object_crosshair_visibility = Visibility::Visible;
-                            target_text = "nearest object onscreen";
-                        }
-                        (false, None) => {
-                            *nearest_object_crosshair_visibility = Visibility::Hidden;
-                            target_text = "nearest object offscreen";
-                        }
-                    }
+            match project(translation) {
+                Some(OverlayProjection {
+                    position: nearest_object_overlay_position,
+                    onscreen: true,
+                }) => {
+                    *nearest_object_crosshair_visibility = Visibility::Visible;
+                    nearest_object_crosshair_transform.translation.x =
+                        nearest_object_overlay_position.x;
+                    nearest_object_crosshair_transform.translation.y =
+                        nearest_object_overlay_position.y;
+
+                    target_text = "nearest object onscreen";
+                    overlay_text_x = format!("{:>20}", nearest_object_overlay_position.x);
+                    overlay_text_y = format!("{:>20}", nearest_object_overlay_position.y);
+                }
+                Some(OverlayProjection {
+                    position: overlay_position,
+                    onscreen: false,
+                }) => {
+                    *nearest_object_crosshair_visibility = Visibility::Hidden;
+
+                    target_text = "nearest object offscreen";
+                    overlay_text_x = format!("{:>20}", overlay_position.x);
+                    overlay_text_y = format!("{:>20}", overlay_position.y);
                 }
                 None => {
                     *nearest_object_crosshair_visibility = Visibility::Hidden;
@@ -1378,97 +1230,108 @@ fn update_hud_reticles(
         }
     };
 
-    if key.just_pressed(KeyCode::Enter) {
-        match ops_mode_resource.current_nav_mode {
-            NavTargetMode::Nearest => {
-                target_resource.target = Some(entity);
-            }
-            NavTargetMode::Cursor => {
-                target_resource.target = cursor_nearest_entity;
-            }
-        }
+    if select_pressed.read().last().is_some() {
+        let under_crosshair = (*targeting_mode == TargetingMode::RayCast)
+            .then(|| {
+                pick_under_crosshair(
+                    &rapier_context,
+                    camera_3d_global_transform.translation(),
+                    camera_3d_global_transform.forward(),
+                    |entity| valid_targets_query.contains(entity),
+                    &parent_query,
+                )
+            })
+            .flatten();
+        target_resource.target =
+            targeting_mode.pick(Some(entity), cursor_nearest_entity, under_crosshair);
         debug!("{:?}", target_resource);
     }
 }
 
 fn spawn_pellet(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     btn: Res<ButtonInput<MouseButton>>,
     floating_origin_grid_transform_query: Query<GridTransform<i64>, With<FloatingOrigin>>,
     camera_controller_query: Query<&CameraController>,
+    pellet_spec: Res<ProjectileSpec>,
 ) {
-    let torus = Torus::new(0.01, 0.03);
-    let mesh_handle = meshes.add(torus);
-    let matl_handle = materials.add(StandardMaterial {
-        base_color: Color::PURPLE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-
+    if !btn.just_pressed(MouseButton::Right) {
+        return;
+    }
     let floating_origin_grid_transform = floating_origin_grid_transform_query.single();
     let camera_controller = camera_controller_query.single();
-    let spawn_transform = Transform {
-        translation: floating_origin_grid_transform.transform.translation
-            + (floating_origin_grid_transform.transform.forward() * 0.125),
-        rotation: floating_origin_grid_transform.transform.rotation,
-        ..default()
-    };
-    let spawn_velocity = Velocity {
-        linvel: camera_controller.velocity().0.as_vec3()
-            + (floating_origin_grid_transform.transform.forward() * 20.0),
-        angvel: Vec3 {
-            x: 2.1,
-            y: 2.2,
-            z: 2.3,
+    /* Pellet */
+    spawn_projectile(
+        &mut commands,
+        *floating_origin_grid_transform.cell,
+        floating_origin_grid_transform.transform,
+        camera_controller.velocity().0.as_vec3(),
+        &pellet_spec,
+    )
+    .insert((
+        BACKGROUND,
+        ValidTarget,
+        ComponentInfo {
+            name: "Pellet".to_string(),
+            size: 0.1,
         },
-        ..default()
-    };
+        TargetClass::Debris,
+    ));
+}
 
-    if btn.just_pressed(MouseButton::Right) {
-        /* Pellet */
-        commands.spawn((
-            BACKGROUND,
-            ValidTarget,
-            *floating_origin_grid_transform.cell,
-            RigidBody::Dynamic,
-            Collider::capsule(
-                Vec3 {
-                    x: 0.0,
-                    y: 0.1,
-                    z: 0.0,
-                },
-                Vec3 {
-                    x: 0.0,
-                    y: -0.1,
-                    z: 0.0,
-                },
-                0.1,
-            ),
-            GravityScale(0.0),
-            spawn_velocity,
-            PbrBundle {
-                mesh: mesh_handle,
-                material: matl_handle,
-                transform: spawn_transform,
-                ..default()
-            },
-        ));
+/// Places a maneuver node along the camera's current heading, or clears the existing one.
+fn toggle_maneuver_node(
+    mut commands: Commands,
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    maneuver_node: Option<Res<ManeuverNode>>,
+    camera_query: Query<&Transform, (With<Camera3d>, With<CameraController>)>,
+) {
+    if !key_bindings.just_pressed(&key, Action::ToggleManeuverNode) {
+        return;
+    }
+    match maneuver_node {
+        Some(_) => commands.remove_resource::<ManeuverNode>(),
+        None => {
+            let forward = camera_query.single().forward().as_dvec3();
+            commands.insert_resource(ManeuverNode::new(forward, 100.0));
+        }
     }
 }
 
+fn cycle_skybox(
+    mut commands: Commands,
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    skybox_assets: Res<SkyBoxAssets>,
+    perspective_hud_query: Query<Entity, (With<Camera3d>, With<CameraController>)>,
+    mut current_skybox: Local<usize>,
+) {
+    if !key_bindings.just_pressed(&key, Action::CycleSkybox) {
+        return;
+    }
+    let skyboxes = [
+        &skybox_assets.milky_way_skybox,
+        &skybox_assets.starfield_skybox,
+    ];
+    *current_skybox = (*current_skybox + 1) % skyboxes.len();
+    debug!("skybox: {:?}", *current_skybox);
+    set_skybox(
+        &mut commands,
+        perspective_hud_query.single(),
+        skyboxes[*current_skybox].clone(),
+        1000.0,
+    );
+}
+
 fn miscellaneous_input_handling(
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
     mut cam: ResMut<CameraInput>,
     btn: Res<ButtonInput<MouseButton>>,
     key: Res<ButtonInput<KeyCode>>,
-    mut exit: EventWriter<AppExit>,
-    mut rapier_configuration: ResMut<RapierConfiguration>,
-    mut nav_command_resource: ResMut<CommandEntryResource>,
-    mut ops_mode_resource: ResMut<OpsModeResource>,
-    mut command_entry_timer_query: Query<&mut CommandEntryTimer>,
+    key_bindings: Res<KeyBindings>,
+    mut time_scale: ResMut<TimeScale>,
+    mut physics_pause: ResMut<PhysicsPause>,
 ) {
     let span = span!(Level::INFO, "miscellaneous_input_handling()");
     let _enter = span.enter();
@@ -1478,172 +1341,24 @@ fn miscellaneous_input_handling(
     };
 
     if btn.just_pressed(MouseButton::Left) {
-        window.cursor.grab_mode = CursorGrabMode::Locked;
-        window.cursor.visible = false;
-        cam.defaults_disabled = false;
+        grab_cursor(&mut window, &mut cam);
     }
 
-    if key.just_pressed(KeyCode::Escape) {
-        if window.cursor.grab_mode == CursorGrabMode::None {
-            exit.send(AppExit);
-        }
-        window.cursor.grab_mode = CursorGrabMode::None;
-        window.cursor.visible = true;
-        cam.defaults_disabled = true;
-    }
-
-    if key.just_pressed(KeyCode::Period) {
-        match rapier_configuration.timestep_mode {
-            TimestepMode::Interpolated {
-                dt,
-                time_scale,
-                substeps,
-            } => {
-                rapier_configuration.timestep_mode = {
-                    let mut new_time_scale = time_scale * 2.0;
-                    if new_time_scale > 512.0 {
-                        new_time_scale = 512.0
-                    }
-                    debug!("time_scale: {:?}", new_time_scale);
-                    TimestepMode::Interpolated {
-                        dt,
-                        time_scale: new_time_scale,
-                        substeps,
-                    }
-                }
-            }
-            _ => {}
-        };
+    if key_bindings.just_pressed(&key, Action::TogglePause) {
+        physics_pause.toggle();
+        debug!("physics paused: {:?}", physics_pause.is_paused());
     }
-    if key.just_pressed(KeyCode::Comma) {
-        match rapier_configuration.timestep_mode {
-            TimestepMode::Interpolated {
-                dt,
-                time_scale,
-                substeps,
-            } => {
-                rapier_configuration.timestep_mode = {
-                    let mut new_time_scale = time_scale / 2.0;
-                    if new_time_scale < 0.001953125 {
-                        new_time_scale = 0.001953125
-                    }
-                    debug!("time_scale: {:?}", new_time_scale);
-                    TimestepMode::Interpolated {
-                        dt,
-                        time_scale: new_time_scale,
-                        substeps,
-                    }
-                }
-            }
-            _ => {}
-        };
-    }
-    if key.just_pressed(KeyCode::Slash) {
-        match rapier_configuration.timestep_mode {
-            TimestepMode::Interpolated {
-                dt,
-                time_scale: _,
-                substeps,
-            } => {
-                rapier_configuration.timestep_mode = {
-                    let new_time_scale = 1.0;
-                    debug!("time_scale: {:?}", new_time_scale);
-                    TimestepMode::Interpolated {
-                        dt,
-                        time_scale: new_time_scale,
-                        substeps,
-                    }
-                }
-            }
-            _ => {}
-        };
-    }
-
-    match (
-        &nav_command_resource.current_command_entry,
-        command_entry_timer_query.single_mut(),
-    ) {
-        (Some(CurrentCommand::NavTargetModeSelect), mut timer) => {
-            if !timer.finished() {
-                if key.just_pressed(KeyCode::KeyC) {
-                    ops_mode_resource.current_nav_mode = NavTargetMode::Cursor;
-                    debug!("{:?} {:?}", ops_mode_resource.current_nav_mode, timer);
-                }
-                if key.just_pressed(KeyCode::KeyN) {
-                    ops_mode_resource.current_nav_mode = NavTargetMode::Nearest;
-                    debug!("{:?} {:?}", ops_mode_resource.current_nav_mode, timer);
-                }
-            } else {
-                trace!("command entry timer finished");
-                if key.just_pressed(KeyCode::KeyT) {
-                    nav_command_resource.current_command_entry =
-                        Some(CurrentCommand::NavTargetModeSelect);
-                    timer.set_duration(Duration::from_secs(2));
-                    timer.reset();
-                }
-            }
-        }
-        (None, mut timer) => {
-            if key.just_pressed(KeyCode::KeyT) {
-                nav_command_resource.current_command_entry =
-                    Some(CurrentCommand::NavTargetModeSelect);
-                timer.set_duration(Duration::from_secs(2));
-                timer.reset();
-            }
+    /* While paused, Period steps a single frame instead of speeding up time */
+    if key_bindings.just_pressed(&key, Action::TimeScaleUp) {
+        match physics_pause.is_paused() {
+            true => physics_pause.step_once(),
+            false => time_scale.multiply_time_scale(2.0),
         }
     }
-}
-
-fn tick_timers(mut command_entry_timer_query: Query<&mut CommandEntryTimer>, time: Res<Time>) {
-    command_entry_timer_query.single_mut().tick(time.delta());
-}
-
-fn update_hud(
-    mut hud_transform_query: Query<&mut Transform, (With<HUD>, Without<Planet>)>,
-    camera_grid_query: Query<GridTransformReadOnly<i64>, (With<FloatingOrigin>, Without<HUD>)>,
-    planet_transform_entity_query: Query<(&Transform, Entity), With<Planet>>,
-    target_resource: Res<TargetResource>,
-    objects: Query<&GlobalTransform>,
-) {
-    let span = span!(Level::INFO, "update_hud()");
-    let _enter = span.enter();
-
-    match target_resource.target {
-        Some(target_entity) => {
-            /* Alight NavBall to Planet */
-            let camera_grid = camera_grid_query.single();
-            let mut camera_rotation = camera_grid.transform.rotation;
-            let (_planet_transform, planet_entity) = planet_transform_entity_query.single();
-            match objects.get(target_entity) {
-                Ok(target_transform) => {
-                    let (_target_object_scale, _target_object_rotation, target_object_translation) =
-                        target_transform.to_scale_rotation_translation();
-
-                    let mut camera_looking_at_target_rotation = camera_grid
-                        .transform
-                        .looking_at(target_object_translation, {
-                            if target_entity == planet_entity {
-                                target_transform.up().normalize()
-                            } else {
-                                camera_grid.transform.up().normalize()
-                            }
-                        })
-                        .rotation
-                        .inverse();
-                    camera_rotation.z = -camera_rotation.z;
-                    camera_looking_at_target_rotation.z = -camera_looking_at_target_rotation.z;
-                    let camera_rotations_combined =
-                        camera_rotation * camera_looking_at_target_rotation;
-                    for mut each_hud_transform in hud_transform_query.iter_mut() {
-                        let final_rotation = camera_rotations_combined;
-                        each_hud_transform.rotation = final_rotation;
-                    }
-                }
-                Err(e) => {
-                    error!("{:?}", e)
-                }
-            }
-        }
-        None => {}
+    if key_bindings.just_pressed(&key, Action::TimeScaleDown) {
+        time_scale.multiply_time_scale(0.5);
+    }
+    if key_bindings.just_pressed(&key, Action::TimeScaleReset) {
+        time_scale.set_time_scale(1.0);
     }
 }
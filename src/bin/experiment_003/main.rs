@@ -1,15 +1,48 @@
 use std::f32::consts::PI;
 
 use bevy::{
-    app::AppExit,
-    core_pipeline::bloom::BloomSettings,
     math::DVec3,
     prelude::*,
-    render::{camera::Exposure, view::RenderLayers},
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
-    window::{CursorGrabMode, PresentMode, PrimaryWindow, WindowMode},
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use bevy_space_program::{
+    camera::{
+        default_space_camera, default_space_projection, double_click_focus, frame_target,
+        orbit_camera, target_grid_position, AdaptiveSpeedPlugin, AutoFocus, AutopilotPlugin,
+        CameraBookmarksPlugin, CameraFocusPlugin, CameraKeySchemePlugin, DoubleClickFocusPlugin,
+        FlyToTarget, InertialDampingPlugin, LevelHorizon, OrbitCameraMode, OrbitCameraPlugin,
+        TargetPositionQuery,
+    },
+    capture::CapturePlugin,
+    crosshair::{spawn_crosshair, CrosshairId, CrosshairIds, CrosshairType},
+    gravity::{containing_sphere_of_influence, SphereOfInfluence, SphereOfInfluenceGizmoPlugin},
+    grid::{grid_distance, spawn_at},
+    hud::TargetingModeIndicatorPlugin,
+    input::{
+        grab_cursor, release_cursor, Action, CursorLockPlugin, KeyBindings, QuitConfirmPlugin,
+        SelectInputPlugin, SelectTargetPressed,
+    },
+    layers::{BACKGROUND, OVERLAY},
+    lighting::{
+        star_light_from_luminosity, DistantStar, DistantStarPlugin, LightingPlugin, Star,
+        StarLightPlugin, SUN_LUMINOSITY_W,
+    },
+    navball::NavBallPlugin,
+    orbits::{KeplerOrbit, KeplerOrbitPlugin, ReferenceAxesPlugin, ReferenceGridPlugin},
+    planets::{
+        spawn_planetary_rings, spawn_satellite, spawn_solar_system, AxialSpinPlugin, PlanetData,
+        RingData, SECONDS_PER_DAY,
+    },
+    plugins::SpaceProgramPlugins,
+    reticle::{AnimateOnAcquire, ReticlePlugin},
+    targeting::{
+        pick_nearest_onscreen, project_to_overlay, ComponentInfo, NearestObject, TargetClass,
+        TargetSelected, TargetingMode, TargetingSettings, ValidTarget,
+    },
+    time::TimeScalePlugin,
+    util::{is_onscreen, overlay_position},
 };
-use bevy_space_program::crosshair::{spawn_crosshair, CrosshairType};
 use big_space::{
     camera::{CameraController, CameraInput},
     reference_frame::{ReferenceFrame, RootReferenceFrame},
@@ -17,65 +50,64 @@ use big_space::{
     FloatingOrigin, GridCell, IgnoreFloatingOrigin,
 };
 
-#[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
-enum AutomationState {
-    Idle,
-    FocusingOnTarget,
-}
-
-#[derive(Default, Reflect, GizmoConfigGroup)]
-struct OverlayGizmos {}
-
 fn main() {
     App::new()
-        .insert_state(AutomationState::FocusingOnTarget)
         .add_plugins((
             DefaultPlugins.build().disable::<TransformPlugin>(),
             big_space::FloatingOriginPlugin::<i64>::default(),
             big_space::debug::FloatingOriginDebugPlugin::<i64>::default(),
             big_space::camera::CameraControllerPlugin::<i64>::default(),
             bevy_framepace::FramepacePlugin,
+            /* No Rapier or navball in this scene */
+            SpaceProgramPlugins
+                .build()
+                .disable::<TimeScalePlugin>()
+                .disable::<NavBallPlugin>(),
+            KeplerOrbitPlugin,
+            SphereOfInfluenceGizmoPlugin,
+            CameraFocusPlugin,
+            OrbitCameraPlugin,
+            DoubleClickFocusPlugin,
+            AdaptiveSpeedPlugin,
+            CameraBookmarksPlugin,
+            CapturePlugin,
+        ))
+        .add_plugins((
+            ReticlePlugin,
+            InertialDampingPlugin,
+            AutopilotPlugin,
+            ReferenceGridPlugin,
+            ReferenceAxesPlugin,
+            AxialSpinPlugin,
+            LightingPlugin,
+            StarLightPlugin,
+            DistantStarPlugin,
+            CameraKeySchemePlugin,
+            CursorLockPlugin,
+            QuitConfirmPlugin,
+            SelectInputPlugin,
+            TargetingModeIndicatorPlugin,
         ))
-        .init_gizmo_group::<OverlayGizmos>()
+        .init_resource::<KeyBindings>()
+        .init_resource::<PlanetData>()
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(Msaa::Sample8)
-        .insert_resource(AmbientLight {
-            color: Color::WHITE,
-            brightness: 100.0,
-        })
         .add_systems(Startup, (setup, ui_text_setup))
         .add_systems(
             Update,
             (
                 ui_text_update,
-                input_handling,
+                input_handling
+                    .before(orbit_camera)
+                    .after(double_click_focus),
+                select_target,
                 update_targeting_overlay,
                 rotate,
             ),
         )
-        .add_systems(
-            Update,
-            focus_on_target.run_if(in_state(AutomationState::FocusingOnTarget)),
-        )
-        .add_systems(
-            PostUpdate,
-            (update_valid_target_gizmos, update_orbit_gizmos),
-        )
         .run()
 }
 
-const BACKGROUND: RenderLayers = RenderLayers::layer(1);
-const OVERLAY: RenderLayers = RenderLayers::layer(2);
-
-#[derive(Component)]
-pub struct ValidTarget;
-
-#[derive(Component)]
-pub struct Orbit {
-    radius: f32,
-    base_color: Color,
-}
-
 #[derive(Component)]
 pub struct CursorNearestReticle;
 
@@ -88,12 +120,6 @@ pub struct HUD;
 #[derive(Component)]
 pub struct TargetLabel;
 
-#[derive(Component)]
-pub struct ComponentInfo {
-    name: String,
-    size: f32,
-}
-
 #[derive(Component)]
 struct Rotates(Vec3);
 
@@ -110,7 +136,9 @@ fn setup(
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
     mut cam: ResMut<CameraInput>,
     mut color_materials: ResMut<Assets<ColorMaterial>>,
+    mut crosshair_ids: ResMut<CrosshairIds>,
     mut gizmo_config_store: ResMut<GizmoConfigStore>,
+    planet_data: Res<PlanetData>,
 ) {
     /* User Interface Setup */
     let Some(mut window) = windows.get_single_mut().ok() else {
@@ -118,19 +146,13 @@ fn setup(
     };
     window.mode = WindowMode::BorderlessFullscreen;
     window.present_mode = PresentMode::Fifo;
-    window.cursor.grab_mode = CursorGrabMode::None;
-    window.cursor.visible = true;
-    cam.defaults_disabled = true;
+    release_cursor(&mut window, &mut cam);
 
     /* Ensure gizmos will be rendered to the background layer */
     let (default_gizmo_config, _) = gizmo_config_store.config_mut::<DefaultGizmoConfigGroup>();
     default_gizmo_config.render_layers = BACKGROUND;
     default_gizmo_config.line_width = 2.0;
 
-    let (overlay_gizmo_config, _) = gizmo_config_store.config_mut::<OverlayGizmos>();
-    overlay_gizmo_config.render_layers = OVERLAY;
-    overlay_gizmo_config.line_width = 0.25;
-
     /* Overlay Camera */
     commands.spawn((
         OVERLAY,
@@ -148,10 +170,10 @@ fn setup(
 
     spawn_crosshair(
         &mut commands,
+        &mut crosshair_ids,
         CrosshairType::SmallTriangleArrows45s,
         &mut meshes,
         &mut color_materials,
-        OVERLAY,
     );
 
     /* CursorNearestReticle */
@@ -258,6 +280,7 @@ fn setup(
             OVERLAY,
             IgnoreFloatingOrigin,
             TargetObjectReticle,
+            AnimateOnAcquire,
             Transform::default(),
             GlobalTransform::default(),
             Visibility::Visible,
@@ -330,15 +353,18 @@ fn setup(
             ));
         });
 
-    let initial_target_entity: Option<Entity>;
-
     /* Spawn the Sun at (0,0,0) */
+    let sun_radius_m = 695_508_000.0;
+    let sun_light = star_light_from_luminosity(SUN_LUMINOSITY_W, sun_radius_m);
     let sun_mat = materials.add(StandardMaterial {
         base_color: Color::WHITE,
-        emissive: Color::rgb_linear(10000000., 10000000., 10000000.),
+        emissive: Color::rgb_linear(
+            sun_light.luminance,
+            sun_light.luminance,
+            sun_light.luminance,
+        ),
         ..default()
     });
-    let sun_radius_m = 695_508_000.0;
     let sun_mesh = meshes.add(Sphere::new(sun_radius_m).mesh().ico(16).unwrap());
 
     commands
@@ -347,7 +373,7 @@ fn setup(
             GridCell::<i64>::ZERO,
             PointLightBundle {
                 point_light: PointLight {
-                    intensity: 35.73e28,
+                    intensity: sun_light.intensity,
                     range: 1e20,
                     radius: sun_radius_m,
                     shadows_enabled: true,
@@ -364,6 +390,14 @@ fn setup(
                     size: sun_radius_m,
                 },
                 ValidTarget,
+                TargetClass::Star,
+                Star {
+                    luminosity_w: SUN_LUMINOSITY_W,
+                    radius_m: sun_radius_m,
+                },
+                DistantStar {
+                    luminosity_w: SUN_LUMINOSITY_W,
+                },
                 PbrBundle {
                     mesh: sun_mesh,
                     material: sun_mat,
@@ -372,326 +406,69 @@ fn setup(
             ));
         });
 
-    let mercury_mat = materials.add(StandardMaterial {
-        base_color: Color::DARK_GRAY,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let mercury_radius_m = 2.4397e6;
-    let mercury_orbit_radius_m = 57.91e9;
-    let mercury_mesh = meshes.add(Sphere::new(mercury_radius_m).mesh().ico(16).unwrap());
-    let (mercury_cell, mercury_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * mercury_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Mercury".to_string(),
-            size: mercury_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: mercury_mesh,
-            material: mercury_mat,
-            transform: Transform::from_translation(mercury_pos),
-            ..default()
-        },
-        mercury_cell,
-    ));
-
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: mercury_orbit_radius_m,
-            base_color: Color::DARK_GRAY,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
-
-    let venus_mat = materials.add(StandardMaterial {
-        base_color: Color::ORANGE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let venus_radius_m = 6.0518e6;
-    let venus_orbit_radius_m = 108.21e9;
-    let venus_mesh = meshes.add(Sphere::new(venus_radius_m).mesh().ico(16).unwrap());
-    let (venus_cell, venus_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * venus_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Venus".to_string(),
-            size: venus_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: venus_mesh,
-            material: venus_mat,
-            transform: Transform::from_translation(venus_pos),
-            ..default()
-        },
-        venus_cell,
-    ));
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: venus_orbit_radius_m,
-            base_color: Color::ORANGE,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
-
-    let earth_mat = materials.add(StandardMaterial {
-        base_color: Color::BLUE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let earth_radius_m = 6.371e6;
-    let earth_orbit_radius_m = 149.60e9;
-    let earth_mesh = meshes.add(Sphere::new(earth_radius_m).mesh().ico(16).unwrap());
-    let (earth_cell, earth_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * earth_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Earth".to_string(),
-            size: earth_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: earth_mesh,
-            material: earth_mat,
-            transform: Transform::from_translation(earth_pos),
-            ..default()
-        },
-        earth_cell,
-    ));
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: earth_orbit_radius_m,
-            base_color: Color::BLUE,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
-
-    let mars_mat = materials.add(StandardMaterial {
-        base_color: Color::RED,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let mars_radius_m = 3.3962e6;
-    let mars_orbit_radius_m = 228.6e9;
-    let mars_mesh = meshes.add(Sphere::new(mars_radius_m).mesh().ico(16).unwrap());
-    let (mars_cell, mars_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * mars_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Mars".to_string(),
-            size: mars_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: mars_mesh,
-            material: mars_mat,
-            transform: Transform::from_translation(mars_pos),
-            ..default()
-        },
-        mars_cell,
-    ));
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: mars_orbit_radius_m,
-            base_color: Color::RED,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
-
-    let jupiter_mat = materials.add(StandardMaterial {
-        base_color: Color::BEIGE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let jupiter_radius_m = 71.492e6;
-    let jupiter_orbit_radius_m = 778.479e9;
-    let jupiter_mesh = meshes.add(Sphere::new(jupiter_radius_m).mesh().ico(16).unwrap());
-    let (jupiter_cell, jupiter_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * jupiter_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Jupiter".to_string(),
-            size: jupiter_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: jupiter_mesh,
-            material: jupiter_mat,
-            transform: Transform::from_translation(jupiter_pos),
-            ..default()
-        },
-        jupiter_cell,
-    ));
-    commands.spawn((
+    let planet_entities = spawn_solar_system(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &space,
+        &planet_data,
         BACKGROUND,
-        Orbit {
-            radius: jupiter_orbit_radius_m,
-            base_color: Color::BEIGE,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
-
-    let saturn_mat = materials.add(StandardMaterial {
-        base_color: Color::BEIGE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let saturn_radius_m = 58.232e6;
-    let saturn_orbit_radius_m = 1433.525e9;
-    let saturn_mesh = meshes.add(Sphere::new(saturn_radius_m).mesh().ico(16).unwrap());
-    let (saturn_cell, saturn_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * saturn_orbit_radius_m);
-    initial_target_entity = Some(
-        commands
-            .spawn((
-                ComponentInfo {
-                    name: "Saturn".to_string(),
-                    size: saturn_radius_m,
-                },
-                BACKGROUND,
-                ValidTarget,
-                PbrBundle {
-                    mesh: saturn_mesh,
-                    material: saturn_mat,
-                    transform: Transform::from_translation(saturn_pos),
-                    ..default()
-                },
-                saturn_cell,
-            ))
-            .id(),
     );
-    let saturn_rings_mat = materials.add(StandardMaterial {
-        base_color: Color::WHITE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        cull_mode: None,
-        ..default()
-    });
-    let saturn_rings_radius_m = 100e6;
-    let saturn_rings_mesh = meshes.add(Circle::new(saturn_rings_radius_m).mesh().resolution(128));
-    commands.spawn((
-        BACKGROUND,
-        PbrBundle {
-            mesh: saturn_rings_mesh.clone(),
-            material: saturn_rings_mat.clone(),
-            transform: Transform::from_translation(saturn_pos)
-                .with_rotation(Quat::from_rotation_y((PI / 4.0) - PI)),
+    let initial_target_entity = planet_entities.get("Saturn").copied();
+
+    /* The Moon, in a reference frame nested in Earth's, orbiting in Earth's equatorial plane */
+    if let Some(earth_entity) = planet_entities.get("Earth").copied() {
+        let moon_radius_m = 1.7374e6;
+        let moon_mesh = meshes.add(Sphere::new(moon_radius_m).mesh().ico(16).unwrap());
+        let moon_mat = materials.add(StandardMaterial {
+            base_color: Color::GRAY,
+            perceptual_roughness: 0.8,
+            reflectance: 1.0,
             ..default()
-        },
-        saturn_cell,
-    ));
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: saturn_orbit_radius_m,
-            base_color: Color::BEIGE,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
-
-    let uranus_mat = materials.add(StandardMaterial {
-        base_color: Color::CYAN,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let uranus_radius_m = 25.559e6;
-    let uranus_orbit_radius_m = 2870.975e9;
-    let uranus_mesh = meshes.add(Sphere::new(uranus_radius_m).mesh().ico(16).unwrap());
-    let (uranus_cell, uranus_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * uranus_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Uranus".to_string(),
-            size: uranus_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: uranus_mesh,
-            material: uranus_mat,
-            transform: Transform::from_translation(uranus_pos),
-            ..default()
-        },
-        uranus_cell,
-    ));
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: uranus_orbit_radius_m,
-            base_color: Color::CYAN,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
+        });
+        spawn_satellite(
+            &mut commands,
+            earth_entity,
+            KeplerOrbit {
+                semi_major_axis: 384.4e6,
+                eccentricity: 0.0549,
+                inclination: 0.0,
+                raan: 0.0,
+                arg_periapsis: 0.0,
+                mean_anomaly_at_epoch: 0.0,
+                period: 27.321_661 * SECONDS_PER_DAY,
+            },
+        )
+        .insert((
+            BACKGROUND,
+            ComponentInfo {
+                name: "Moon".to_string(),
+                size: moon_radius_m,
+            },
+            ValidTarget,
+            TargetClass::Planet,
+            moon_mesh,
+            moon_mat,
+        ));
+    }
 
-    let neptune_mat = materials.add(StandardMaterial {
-        base_color: Color::BLUE,
-        perceptual_roughness: 0.8,
-        reflectance: 1.0,
-        ..default()
-    });
-    let neptune_radius_m = 24.764e6;
-    let neptune_orbit_radius_m = 4500e9;
-    let neptune_mesh = meshes.add(Sphere::new(neptune_radius_m).mesh().ico(16).unwrap());
-    let (neptune_cell, neptune_pos): (GridCell<i64>, _) =
-        space.imprecise_translation_to_grid(Vec3::Z * neptune_orbit_radius_m);
-    commands.spawn((
-        ComponentInfo {
-            name: "Neptune".to_string(),
-            size: neptune_radius_m,
-        },
-        BACKGROUND,
-        ValidTarget,
-        PbrBundle {
-            mesh: neptune_mesh,
-            material: neptune_mat,
-            transform: Transform::from_translation(neptune_pos),
-            ..default()
-        },
-        neptune_cell,
-    ));
-    commands.spawn((
-        BACKGROUND,
-        Orbit {
-            radius: neptune_orbit_radius_m,
-            base_color: Color::BLUE,
-        },
-        Transform::IDENTITY,
-        GlobalTransform::IDENTITY,
-        GridCell::<i64>::ZERO,
-    ));
+    /* Saturn's main rings, out to the edge of the A ring */
+    if let Some(saturn_entity) = initial_target_entity {
+        spawn_planetary_rings(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            saturn_entity,
+            &RingData {
+                inner_radius_m: 74.5e6,
+                outer_radius_m: 136.8e6,
+                /* Saturn's axial tilt already carries over to its children */
+                tilt: 0.0,
+                base_color: Color::WHITE,
+            },
+            BACKGROUND,
+        );
+    }
 
     /* Spawn the user controlled camera */
     let (cam_cell, cam_pos): (GridCell<i64>, _) = space.translation_to_grid(DVec3 {
@@ -699,26 +476,26 @@ fn setup(
         y: (sun_radius_m as f64 * 20.0) + 1000.0,
         z: (sun_radius_m as f64 * 20.0) + 1000.0,
     });
-    commands.spawn((
-        BACKGROUND,
-        Camera3dBundle {
-            transform: Transform::from_translation(cam_pos),
-            camera: Camera {
-                order: 1,
-                hdr: true,
+    let camera_entity = commands
+        .spawn((
+            BACKGROUND,
+            Camera3dBundle {
+                transform: Transform::from_translation(cam_pos),
+                camera: Camera {
+                    order: 1,
+                    ..default()
+                },
+                projection: default_space_projection(),
                 ..default()
             },
-            exposure: Exposure::SUNLIGHT,
-            ..default()
-        },
-        BloomSettings::default(),
-        cam_cell,
-        FloatingOrigin, // Important: marks the floating origin entity for rendering.
-        CameraController::default() // Built-in camera controller
-            .with_speed_bounds([10e-18, 10e35])
-            .with_smoothness(0.9, 0.8)
-            .with_speed(1.0),
-    ));
+            cam_cell,
+            FloatingOrigin, // Important: marks the floating origin entity for rendering.
+            default_space_camera(),
+        ))
+        .id();
+    if let Some(target) = initial_target_entity {
+        commands.entity(camera_entity).insert(AutoFocus { target });
+    }
 
     let home_object_mat = materials.add(StandardMaterial {
         base_color: Color::PURPLE,
@@ -741,6 +518,7 @@ fn setup(
             size: home_object_size_m,
         },
         ValidTarget,
+        TargetClass::Vessel,
         BACKGROUND,
         PbrBundle {
             mesh: home_object_mesh,
@@ -762,12 +540,19 @@ fn setup(
     });
 
     /* Proxima Centauri 4.017 × 10^16 m */
+    let proxima_centauri_radius_m = sun_radius_m * 0.1542;
+    let proxima_centauri_luminosity_w = SUN_LUMINOSITY_W * 0.00155;
+    let proxima_centauri_light =
+        star_light_from_luminosity(proxima_centauri_luminosity_w, proxima_centauri_radius_m);
     let proxima_centauri_mat = materials.add(StandardMaterial {
         base_color: Color::WHITE,
-        emissive: Color::rgb_linear(10000000., 10000000., 10000000.),
+        emissive: Color::rgb_linear(
+            proxima_centauri_light.luminance,
+            proxima_centauri_light.luminance,
+            proxima_centauri_light.luminance,
+        ),
         ..default()
     });
-    let proxima_centauri_radius_m = sun_radius_m * 0.1542;
     let proxima_centauri_distance_m = 4.017e16;
     let proxima_centauri_mesh = meshes.add(
         Sphere::new(proxima_centauri_radius_m)
@@ -775,38 +560,51 @@ fn setup(
             .ico(16)
             .unwrap(),
     );
-    let (proxima_centauri_grid_cell, proxima_centauri_grid_pos): (GridCell<i64>, _) =
-        space.translation_to_grid(Vec3::Z * proxima_centauri_distance_m);
-    commands.spawn((
-        BACKGROUND,
-        proxima_centauri_grid_cell,
-        PointLightBundle {
-            transform: Transform::from_translation(proxima_centauri_grid_pos),
-            point_light: PointLight {
-                intensity: 35.73e28,
-                range: 1e20,
-                radius: proxima_centauri_radius_m,
-                shadows_enabled: true,
+    let proxima_centauri_position = DVec3::Z * proxima_centauri_distance_m;
+    spawn_at(
+        &mut commands,
+        &space,
+        proxima_centauri_position,
+        (
+            BACKGROUND,
+            PointLightBundle {
+                point_light: PointLight {
+                    intensity: proxima_centauri_light.intensity,
+                    range: 1e20,
+                    radius: proxima_centauri_radius_m,
+                    shadows_enabled: true,
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        },
-    ));
-    commands.spawn((
-        BACKGROUND,
-        proxima_centauri_grid_cell,
-        ComponentInfo {
-            name: "Proxima Centauri".to_string(),
-            size: proxima_centauri_radius_m,
-        },
-        ValidTarget,
-        PbrBundle {
-            transform: Transform::from_translation(proxima_centauri_grid_pos),
-            mesh: proxima_centauri_mesh,
-            material: proxima_centauri_mat,
-            ..default()
-        },
-    ));
+        ),
+    );
+    spawn_at(
+        &mut commands,
+        &space,
+        proxima_centauri_position,
+        (
+            BACKGROUND,
+            ComponentInfo {
+                name: "Proxima Centauri".to_string(),
+                size: proxima_centauri_radius_m,
+            },
+            ValidTarget,
+            TargetClass::Star,
+            Star {
+                luminosity_w: proxima_centauri_luminosity_w,
+                radius_m: proxima_centauri_radius_m,
+            },
+            DistantStar {
+                luminosity_w: proxima_centauri_luminosity_w,
+            },
+            PbrBundle {
+                mesh: proxima_centauri_mesh,
+                material: proxima_centauri_mat,
+                ..default()
+            },
+        ),
+    );
 }
 
 fn ui_text_setup(mut commands: Commands) {
@@ -871,9 +669,29 @@ fn ui_text_update(
     time: Res<Time>,
     target_resource: ResMut<TargetResource>,
     component_info_query: Query<&ComponentInfo>,
+    kepler_orbit_query: Query<&KeplerOrbit>,
+    soi_query: Query<(Entity, &GridCell<i64>, &Transform, &SphereOfInfluence)>,
+    grid_query: Query<(&GridCell<i64>, &Transform), Without<CameraController>>,
+    space: Res<RootReferenceFrame<i64>>,
 ) {
     let (camera_3d_transform, floating_origin_grid_transform) =
         floating_origin_grid_transform_query.single();
+    let camera_position = space.grid_position_double(
+        floating_origin_grid_transform.cell,
+        floating_origin_grid_transform.transform,
+    );
+    let soi_entity_name = match containing_sphere_of_influence(
+        camera_position,
+        soi_query.iter().map(|(entity, cell, transform, soi)| {
+            (entity, space.grid_position_double(cell, transform), soi)
+        }),
+    ) {
+        Some(soi_entity) => match component_info_query.get(soi_entity) {
+            Ok(soi_component_info) => soi_component_info.name.as_str(),
+            Err(_) => "unknown",
+        },
+        None => "Sun",
+    };
     let grid_text = format!(
         "X:{:_>15} Y:{:_>15} Z:{:_>15}",
         floating_origin_grid_transform.cell.x,
@@ -892,6 +710,33 @@ fn ui_text_update(
         None => {}
     }
 
+    let mut target_orbit_text = "".to_string();
+    if let Some(target_entity) = target_resource.target {
+        if let Ok(target_orbit) = kepler_orbit_query.get(target_entity) {
+            target_orbit_text = format!(
+                "\nAp: {:.3e} m Pe: {:.3e} m",
+                target_orbit.apoapsis(),
+                target_orbit.periapsis()
+            );
+        }
+    }
+
+    let mut target_range_text = "".to_string();
+    if let Some(target_entity) = target_resource.target {
+        if let Ok((target_cell, target_transform)) = grid_query.get(target_entity) {
+            target_range_text = format!(
+                " Range: {:.3e} m",
+                grid_distance(
+                    &space,
+                    floating_origin_grid_transform.cell,
+                    floating_origin_grid_transform.transform,
+                    target_cell,
+                    target_transform,
+                )
+            );
+        }
+    }
+
     let camera_coordinates = camera_3d_transform.translation;
     let camera_controller = camera_controller_query.single();
     let (velocity, _) = camera_controller.velocity();
@@ -903,141 +748,20 @@ fn ui_text_update(
     };
     let mut hud_text = hud_text_query.single_mut();
     let hud_text_string = format!(
-        "Speed: {}\nGrid Coordinates: {}\nCell Coordinates: X:{:_>15} Y:{:_>15} Z:{:_>15}\nTracking: {}",
+        "Speed: {}\nGrid Coordinates: {}\nCell Coordinates: X:{:_>15} Y:{:_>15} Z:{:_>15}\nTracking: {}{}{}\nSOI: {}",
         speed_text,
         grid_text,
         camera_coordinates.x,
         camera_coordinates.y,
         camera_coordinates.z,
-        target_entity_name
+        target_entity_name,
+        target_range_text,
+        target_orbit_text,
+        soi_entity_name
     );
     hud_text.sections[0].value = hud_text_string.clone();
 }
 
-fn update_valid_target_gizmos(
-    global_transform_query: Query<&GlobalTransform>,
-    valid_target_entity_query: Query<Entity, With<ValidTarget>>,
-    mut overlay_gizmos: Gizmos<OverlayGizmos>,
-    camera_3d_query: Query<(&mut Camera, &GlobalTransform), (With<Camera3d>, Without<Camera2d>)>,
-    camera_2d_query: Query<(&mut Camera, &GlobalTransform), (With<Camera2d>, Without<Camera3d>)>,
-) {
-    for each_valid_target_entity in valid_target_entity_query.iter() {
-        let Ok(transform) = global_transform_query.get(each_valid_target_entity) else {
-            return;
-        };
-        let (_scale, _rotationn, translation) = transform.to_scale_rotation_translation();
-
-        let (camera_3d, camera_3d_global_transform) = camera_3d_query.single();
-        let (camera_2d, camera_2d_global_transform) = camera_2d_query.single();
-        match camera_3d.world_to_viewport(camera_3d_global_transform, translation) {
-            Some(each_valid_target_viewport_position) => {
-                match camera_2d.viewport_to_world_2d(
-                    camera_2d_global_transform,
-                    each_valid_target_viewport_position,
-                ) {
-                    Some(each_valid_target_world_2d_position) => {
-                        let color = match Color::hex("FE9F00") {
-                            Ok(c) => c,
-                            Err(_) => Color::rgb(1.0, 1.0, 1.0),
-                        };
-                        overlay_gizmos.linestrip_2d(
-                            vec![
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x + 25.0,
-                                    y: each_valid_target_world_2d_position.y + 30.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x + 30.0,
-                                    y: each_valid_target_world_2d_position.y + 30.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x + 30.0,
-                                    y: each_valid_target_world_2d_position.y + 25.0,
-                                },
-                            ],
-                            color,
-                        );
-                        overlay_gizmos.linestrip_2d(
-                            vec![
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x + 30.0,
-                                    y: each_valid_target_world_2d_position.y - 25.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x + 30.0,
-                                    y: each_valid_target_world_2d_position.y - 30.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x + 25.0,
-                                    y: each_valid_target_world_2d_position.y - 30.0,
-                                },
-                            ],
-                            color,
-                        );
-                        overlay_gizmos.linestrip_2d(
-                            vec![
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x - 25.0,
-                                    y: each_valid_target_world_2d_position.y + 30.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x - 30.0,
-                                    y: each_valid_target_world_2d_position.y + 30.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x - 30.0,
-                                    y: each_valid_target_world_2d_position.y + 25.0,
-                                },
-                            ],
-                            color,
-                        );
-                        overlay_gizmos.linestrip_2d(
-                            vec![
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x - 30.0,
-                                    y: each_valid_target_world_2d_position.y - 25.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x - 30.0,
-                                    y: each_valid_target_world_2d_position.y - 30.0,
-                                },
-                                Vec2 {
-                                    x: each_valid_target_world_2d_position.x - 25.0,
-                                    y: each_valid_target_world_2d_position.y - 30.0,
-                                },
-                            ],
-                            color,
-                        );
-                    }
-                    None => {}
-                }
-            }
-            None => {}
-        }
-    }
-}
-
-fn update_orbit_gizmos(
-    global_transform_query: Query<&GlobalTransform>,
-    orbit_entity_query: Query<(Entity, &Orbit)>,
-    mut default_gizmos: Gizmos,
-) {
-    for (each_entity, each_orbit) in orbit_entity_query.iter() {
-        let Ok(transform) = global_transform_query.get(each_entity) else {
-            return;
-        };
-        let (_scale, _rotationn, translation) = transform.to_scale_rotation_translation();
-        match Direction3d::from_xyz(transform.up().x, transform.up().y, transform.up().z) {
-            Ok(d) => {
-                default_gizmos
-                    .circle(translation, d, each_orbit.radius, each_orbit.base_color)
-                    .segments(64);
-            }
-            Err(e) => error!("{:?}", e),
-        }
-    }
-}
-
 fn update_targeting_overlay(
     camera_3d_query: Query<(&mut Camera, &GlobalTransform), (With<Camera3d>, Without<Camera2d>)>,
     camera_2d_query: Query<(&mut Camera, &GlobalTransform), (With<Camera2d>, Without<Camera3d>)>,
@@ -1050,7 +774,7 @@ fn update_targeting_overlay(
             Without<Camera3d>,
             Without<Camera2d>,
             Without<TargetLabel>,
-            Without<CrosshairType>,
+            Without<CrosshairId>,
         ),
     >,
     mut target_object_reticle_transform_query: Query<
@@ -1061,7 +785,7 @@ fn update_targeting_overlay(
             Without<Camera3d>,
             Without<Camera2d>,
             Without<TargetLabel>,
-            Without<CrosshairType>,
+            Without<CrosshairId>,
         ),
     >,
     mut target_label_style_query: Query<(&mut Style, &mut Text), With<TargetLabel>>,
@@ -1070,7 +794,13 @@ fn update_targeting_overlay(
     target_label_entity_query: Query<Entity, With<TargetLabel>>,
     global_transform_query: Query<&GlobalTransform>,
     mut visibility_query: Query<&mut Visibility>,
-    key: Res<ButtonInput<KeyCode>>,
+    mut select_pressed: EventReader<SelectTargetPressed>,
+    mut target_selected: EventWriter<TargetSelected>,
+    (targeting_mode, nearest_object, targeting_settings): (
+        Res<TargetingMode>,
+        Res<NearestObject>,
+        Res<TargetingSettings>,
+    ),
 ) {
     let cursor_nearest_entity = cursor_nearest_entity_query.single();
     let target_object_reticle_entity = target_object_reticle_entity_query.single();
@@ -1095,134 +825,87 @@ fn update_targeting_overlay(
             let target_object_reticle_visibility = second_visibility_entities_split.0;
             let target_label_visibility = second_visibility_entities_split.1;
 
-            let mut cursor_nearest_entity = None;
-            let mut cursor_target_onscreen = false;
-            let mut cursor_nearest_size = 0.0;
-            let mut cursor_nearest = Vec2 {
-                x: 10000000.0,
-                y: 10000000.0,
-            };
-            for (
-                _index,
-                (each_valid_target_transform, each_valid_target_entity, each_valid_target_info),
-            ) in valid_targets_query.iter().enumerate()
-            {
-                match camera_3d.world_to_viewport(
-                    camera_3d_global_transform,
-                    each_valid_target_transform.translation(),
-                ) {
-                    Some(each_object_3d_viewport_position) => {
-                        match camera_2d.viewport_to_world_2d(
+            let cursor_nearest = pick_nearest_onscreen(
+                valid_targets_query.iter().filter_map(
+                    |(each_valid_target_transform, each_valid_target_entity, _)| {
+                        project_to_overlay(
+                            camera_3d,
+                            camera_3d_global_transform,
+                            camera_2d,
                             camera_2d_global_transform,
-                            each_object_3d_viewport_position,
-                        ) {
-                            Some(each_object_2d_viewport_position) => {
-                                trace!(
-                                    "{:?} {:?}",
-                                    each_valid_target_info.name,
-                                    each_object_2d_viewport_position
-                                );
-
-                                let length_difference = each_object_2d_viewport_position.length()
-                                    - cursor_nearest.length();
-                                if length_difference < 0.0 {
-                                    if length_difference > -3.0 {
-                                        if each_valid_target_info.size > cursor_nearest_size {
-                                            cursor_target_onscreen = true;
-                                            cursor_nearest = each_object_2d_viewport_position;
-                                            cursor_nearest_entity = Some(each_valid_target_entity);
-                                            cursor_nearest_size = each_valid_target_info.size;
-                                        }
-                                    } else {
-                                        cursor_target_onscreen = true;
-                                        cursor_nearest = each_object_2d_viewport_position;
-                                        cursor_nearest_entity = Some(each_valid_target_entity);
-                                        cursor_nearest_size = each_valid_target_info.size;
-                                    }
-                                }
-                            }
-                            None => {}
-                        }
-                    }
-                    None => {}
-                }
-            }
-            if cursor_target_onscreen {
+                            each_valid_target_transform.translation(),
+                        )
+                        .map(|projection| (each_valid_target_entity, projection))
+                    },
+                ),
+                Vec2::ZERO,
+                targeting_settings.selection_radius,
+            );
+            let cursor_nearest_entity = cursor_nearest.map(|(entity, _)| entity);
+            if let Some((_, cursor_nearest_position)) = cursor_nearest {
                 *cursor_nearest_reticle_visibility[0] = Visibility::Visible;
-                cursor_nearest_reticle_transform.translation.x = cursor_nearest.x;
-                cursor_nearest_reticle_transform.translation.y = cursor_nearest.y;
+                cursor_nearest_reticle_transform.translation.x = cursor_nearest_position.x;
+                cursor_nearest_reticle_transform.translation.y = cursor_nearest_position.y;
+            } else {
+                *cursor_nearest_reticle_visibility[0] = Visibility::Hidden;
             }
 
             let mut target_object_reticle_transform =
                 target_object_reticle_transform_query.single_mut();
 
-            let Some(camera_2d_viewport_rect) = camera_2d.logical_viewport_rect() else {
-                return;
-            };
-
             match target_resource.target {
                 Some(target) => match global_transform_query.get(target) {
                     Ok(target_object) => {
-                        let (
-                            _target_object_scale,
-                            _target_object_rotation,
-                            target_object_translation,
-                        ) = target_object.to_scale_rotation_translation();
-                        match camera_3d.world_to_viewport(
-                            camera_3d_global_transform,
-                            target_object_translation,
+                        let target_object_translation = target_object.translation();
+                        match (
+                            is_onscreen(
+                                camera_3d,
+                                camera_3d_global_transform,
+                                target_object_translation,
+                            ),
+                            overlay_position(
+                                camera_3d,
+                                camera_3d_global_transform,
+                                camera_2d,
+                                camera_2d_global_transform,
+                                target_object_translation,
+                            ),
+                            camera_3d.world_to_viewport(
+                                camera_3d_global_transform,
+                                target_object_translation,
+                            ),
                         ) {
-                            Some(target_object_viewport_position) => {
-                                match (
-                                    camera_2d_viewport_rect
-                                        .contains(target_object_viewport_position),
-                                    camera_2d.viewport_to_world_2d(
-                                        camera_2d_global_transform,
-                                        target_object_viewport_position,
-                                    ),
-                                ) {
-                                    (true, Some(target_object_overlay_position)) => {
-                                        *target_object_reticle_visibility[0] = Visibility::Visible;
-                                        target_object_reticle_transform.translation.x =
-                                            target_object_overlay_position.x;
-                                        target_object_reticle_transform.translation.y =
-                                            target_object_overlay_position.y;
-
-                                        *target_label_visibility[0] = Visibility::Visible;
-                                        let (mut target_label_style, mut target_label_text) =
-                                            target_label_style_query.single_mut();
-
-                                        target_label_style.top =
-                                            Val::Px(target_object_viewport_position.y + 30.0);
-                                        target_label_style.left =
-                                            Val::Px(target_object_viewport_position.x + 30.0);
-
-                                        match valid_targets_query.get(target) {
-                                            Ok((_, _, target_component_info)) => {
-                                                target_label_text.sections[0].value =
-                                                    target_component_info.name.to_string();
-                                            }
-                                            Err(e) => error!(
-                                                "match valid_targets_query.get(target) {:?}",
-                                                e
-                                            ),
-                                        }
+                            (
+                                true,
+                                Some(target_object_overlay_position),
+                                Some(target_object_viewport_position),
+                            ) => {
+                                *target_object_reticle_visibility[0] = Visibility::Visible;
+                                target_object_reticle_transform.translation.x =
+                                    target_object_overlay_position.x;
+                                target_object_reticle_transform.translation.y =
+                                    target_object_overlay_position.y;
+
+                                *target_label_visibility[0] = Visibility::Visible;
+                                let (mut target_label_style, mut target_label_text) =
+                                    target_label_style_query.single_mut();
+
+                                target_label_style.top =
+                                    Val::Px(target_object_viewport_position.y + 30.0);
+                                target_label_style.left =
+                                    Val::Px(target_object_viewport_position.x + 30.0);
+
+                                match valid_targets_query.get(target) {
+                                    Ok((_, _, target_component_info)) => {
+                                        target_label_text.sections[0].value =
+                                            target_component_info.name.to_string();
                                     }
-                                    (false, Some(_target_object_overlay_position)) => {
-                                        *target_object_reticle_visibility[0] = Visibility::Hidden;
-                                        *target_label_visibility[0] = Visibility::Hidden;
-                                    }
-                                    (true, None) => {
-                                        *target_object_reticle_visibility[0] = Visibility::Hidden;
-                                        *target_label_visibility[0] = Visibility::Hidden;
-                                    }
-                                    (false, None) => {
-                                        *target_object_reticle_visibility[0] = Visibility::Hidden;
-                                        *target_label_visibility[0] = Visibility::Hidden;
+                                    Err(e) => {
+                                        error!("match valid_targets_query.get(target) {:?}", e)
                                     }
                                 }
                             }
-                            None => {
+                            _ => {
                                 *target_object_reticle_visibility[0] = Visibility::Hidden;
                                 *target_label_visibility[0] = Visibility::Hidden;
                             }
@@ -1234,14 +917,29 @@ fn update_targeting_overlay(
             }
 
             /* Highlight target with crosshair reticle */
-            if key.just_pressed(KeyCode::Enter) {
-                target_resource.target = cursor_nearest_entity;
+            if select_pressed.read().last().is_some() {
+                /* Nothing here has a collider, so a ray cast picks nothing and keeps the current target */
+                if let Some(target) =
+                    targeting_mode.pick(nearest_object.entity(), cursor_nearest_entity, None)
+                {
+                    target_resource.target = Some(target);
+                    target_selected.send(TargetSelected { target });
+                }
             }
         }
         Err(e) => error!("match visibility_entity_results {:?}", e),
     }
 }
 
+fn select_target(
+    mut target_selected: EventReader<TargetSelected>,
+    mut target_resource: ResMut<TargetResource>,
+) {
+    for event in target_selected.read() {
+        target_resource.target = Some(event.target);
+    }
+}
+
 fn rotate(mut rotate_query: Query<(&mut Transform, &Rotates)>) {
     for (mut transform, rotates) in rotate_query.iter_mut() {
         transform.rotate_x(rotates.0.x);
@@ -1254,87 +952,117 @@ fn input_handling(
     mut cam: ResMut<CameraInput>,
     btn: Res<ButtonInput<MouseButton>>,
     key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
-    mut exit: EventWriter<AppExit>,
-    current_state: Res<State<AutomationState>>,
-    mut state: ResMut<NextState<AutomationState>>,
+    mut commands: Commands,
+    camera_query: Query<
+        (
+            Entity,
+            Option<&AutoFocus>,
+            Option<&OrbitCameraMode>,
+            &GridCell<i64>,
+            &Transform,
+            &Projection,
+        ),
+        (With<CameraController>, With<Camera3d>),
+    >,
+    target_resource: Res<TargetResource>,
+    component_info_query: Query<&ComponentInfo>,
+    target_position_query: TargetPositionQuery,
+    space: Res<RootReferenceFrame<i64>>,
 ) {
     let Some(mut window) = windows.get_single_mut().ok() else {
         return;
     };
 
     if btn.just_pressed(MouseButton::Left) {
-        window.cursor.grab_mode = CursorGrabMode::Locked;
-        window.cursor.visible = false;
-        cam.defaults_disabled = false;
+        grab_cursor(&mut window, &mut cam);
     }
 
-    if key.just_pressed(KeyCode::Escape) {
-        if window.cursor.grab_mode == CursorGrabMode::None {
-            exit.send(AppExit);
-        }
-        window.cursor.grab_mode = CursorGrabMode::None;
-        window.cursor.visible = true;
-        cam.defaults_disabled = true;
-    }
-
-    if key.just_pressed(KeyCode::KeyF) {
+    if key_bindings.just_pressed(&key, Action::FocusTarget) {
         debug!("auto focus:");
-        match current_state.get() {
-            AutomationState::Idle => {
+        let (camera_entity, auto_focus, _, _, _, _) = camera_query.single();
+        match (auto_focus, target_resource.target) {
+            (None, Some(target)) => {
                 debug!("enabled");
-                state.set(AutomationState::FocusingOnTarget);
+                commands.entity(camera_entity).insert(AutoFocus { target });
             }
-            AutomationState::FocusingOnTarget => {
+            (Some(_), _) => {
                 debug!("disabled");
-                state.set(AutomationState::Idle);
+                commands.entity(camera_entity).remove::<AutoFocus>();
             }
+            (None, None) => {}
         }
     }
-}
 
-fn focus_on_target(
-    mut camera_3d_query: Query<
-        &mut Transform,
-        (With<CameraController>, With<Camera3d>, Without<Camera2d>),
-    >,
-    target_resource: ResMut<TargetResource>,
-    global_transform_query: Query<&GlobalTransform>,
-    mut state: ResMut<NextState<AutomationState>>,
-) {
-    let mut camera_3d_transform = camera_3d_query.single_mut();
-    match target_resource.target {
-        Some(target) => match global_transform_query.get(target) {
-            Ok(target_object) => {
-                let target_rotation = camera_3d_transform
-                    .looking_at(
-                        target_object.translation(),
-                        camera_3d_transform.up().normalize(),
-                    )
-                    .rotation;
-                let rotation_difference = target_rotation * camera_3d_transform.rotation.inverse();
-                let (rotation_axis, mut rotation_angle) = rotation_difference.to_axis_angle();
-                if rotation_angle > PI {
-                    rotation_angle -= PI * 2.0;
-                };
-                trace!("rotation_axis: {:?}", rotation_axis);
-                trace!("rotation_angle: {:?}", rotation_angle);
-                let mut new_transform = camera_3d_transform.clone();
-                new_transform.rotate_axis(rotation_axis, 0.01 * rotation_angle.signum());
-                let angle_between = target_rotation
-                    .normalize()
-                    .angle_between(camera_3d_transform.rotation.normalize());
-                trace!("angle_between: {:?}", angle_between);
-                if angle_between < 0.01 {
-                    camera_3d_transform.rotation = target_rotation;
-                    debug!("target aligned");
-                    state.set(AutomationState::Idle);
-                } else {
-                    camera_3d_transform.rotation = new_transform.rotation;
+    if key_bindings.just_pressed(&key, Action::FlyToTarget) {
+        if let Some(target) = target_resource.target {
+            let standoff_distance_m = match component_info_query.get(target) {
+                Ok(target_info) => target_info.size as f64 * 3.0,
+                Err(_) => 10.0,
+            };
+            debug!("flying to {:?}, standoff {:?}", target, standoff_distance_m);
+            let (camera_entity, _, _, _, _, _) = camera_query.single();
+            commands.entity(camera_entity).insert((
+                AutoFocus { target },
+                FlyToTarget {
+                    target,
+                    standoff_distance_m,
+                },
+            ));
+        }
+    }
+
+    if key_bindings.just_pressed(&key, Action::LevelHorizon) {
+        debug!("level horizon");
+        let (camera_entity, _, _, _, _, _) = camera_query.single();
+        commands.entity(camera_entity).insert(LevelHorizon);
+    }
+
+    if key_bindings.just_pressed(&key, Action::FrameTarget) {
+        if let Some(target) = target_resource.target {
+            match component_info_query.get(target) {
+                Ok(target_info) => {
+                    let (camera_entity, _, _, _, _, projection) = camera_query.single();
+                    frame_target(
+                        &mut commands,
+                        camera_entity,
+                        projection,
+                        target,
+                        target_info.size as f64,
+                        0.6,
+                    );
                 }
+                Err(e) => error!("match component_info_query.get(target) {:?}", e),
             }
-            Err(e) => error!("match global_transform_query.get(target) {:?}", e),
-        },
-        None => {}
+        }
+    }
+
+    if key_bindings.just_pressed(&key, Action::OrbitCamera) {
+        debug!("orbit camera:");
+        let (camera_entity, _, orbit_mode, camera_cell, camera_transform, _) =
+            camera_query.single();
+        match (orbit_mode, target_resource.target) {
+            (None, Some(target)) => {
+                match target_grid_position(&target_position_query, &space, target) {
+                    Some(target_position) => {
+                        debug!("enabled");
+                        commands
+                            .entity(camera_entity)
+                            .insert(OrbitCameraMode::from_current_view(
+                                target,
+                                space.grid_position_double(camera_cell, camera_transform),
+                                target_position,
+                            ));
+                    }
+                    None => error!("target_grid_position {:?} not found", target),
+                }
+            }
+            (Some(_), _) => {
+                debug!("disabled");
+                commands.entity(camera_entity).remove::<OrbitCameraMode>();
+            }
+            (None, None) => {}
+        }
     }
 }
@@ -0,0 +1,1136 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    ecs::query::QueryFilter,
+    input::mouse::{MouseMotion, MouseWheel},
+    math::DVec3,
+    prelude::*,
+    utils::HashMap,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+use big_space::{
+    camera::{camera_controller, default_camera_inputs, CameraController, CameraInput},
+    reference_frame::{ReferenceFrame, RootReferenceFrame},
+    FloatingOrigin, GridCell,
+};
+
+use crate::{
+    gravity::GravityAttractor,
+    grid::look_toward,
+    input::{Action, KeyBindings},
+    targeting::{ComponentInfo, TargetSelected, ValidTarget},
+};
+
+/// Slowest and fastest the free camera may fly, in m/s.
+pub const SPACE_CAMERA_SPEED_BOUNDS: [f64; 2] = [10e-18, 10e35];
+/// Translational and rotational smoothness of the free camera.
+pub const SPACE_CAMERA_SMOOTHNESS: (f64, f64) = (0.9, 0.8);
+pub const SPACE_CAMERA_SPEED: f64 = 1.0;
+
+/// A `CameraController` with the project's standard speed bounds, smoothness and starting speed.
+pub fn default_space_camera() -> CameraController {
+    CameraController::default()
+        .with_speed_bounds(SPACE_CAMERA_SPEED_BOUNDS)
+        .with_smoothness(SPACE_CAMERA_SMOOTHNESS.0, SPACE_CAMERA_SMOOTHNESS.1)
+        .with_speed(SPACE_CAMERA_SPEED)
+}
+
+/// Near and far planes of the space cameras, in meters. Bevy's perspective projection is already reversed-Z with an
+/// infinite far plane, so depth precision falls off roughly logarithmically and `far` only bounds frustum culling.
+pub const SPACE_CAMERA_NEAR: f32 = 1e-3;
+pub const SPACE_CAMERA_FAR: f32 = 1e30;
+
+/// Sets the near and far planes of a perspective projection; orthographic projections are left alone.
+/// The depth buffer stores `near / distance` as an f32, which underflows to zero once the distance passes
+/// `near / f32::MIN_POSITIVE`, so `near` is raised if needed to keep everything out to `far` from collapsing onto the
+/// same depth.
+pub fn configure_space_projection(projection: &mut Projection, near: f32, far: f32) {
+    let Projection::Perspective(perspective) = projection else {
+        return;
+    };
+    let min_near = far * f32::MIN_POSITIVE;
+    if near < min_near {
+        warn!(
+            "near plane {} is too small for a far plane of {}, using {}",
+            near, far, min_near
+        );
+    }
+    perspective.near = near.max(min_near);
+    perspective.far = far;
+}
+
+/// A perspective projection with `SPACE_CAMERA_NEAR` and `SPACE_CAMERA_FAR`.
+pub fn default_space_projection() -> Projection {
+    let mut projection = Projection::Perspective(PerspectiveProjection::default());
+    configure_space_projection(&mut projection, SPACE_CAMERA_NEAR, SPACE_CAMERA_FAR);
+    projection
+}
+
+/// Keys that drive the big_space `CameraController`, matching experiment_001's rapier camera:
+///
+/// - `thrust_forward` / `thrust_backward` (W / S) thrust along the view axis.
+/// - `strafe_left` / `strafe_right` (A / D) strafe sideways.
+/// - `rise` / `sink` (Left Shift / Left Ctrl) move along the camera's up axis.
+/// - `roll_left` / `roll_right` (Q / E) roll about the view axis.
+/// - `boost` (Space) adds the controller's boost speed while held.
+///
+/// Mouse motion pitches and yaws the camera, scaled by `mouse_sensitivity`.
+#[derive(Resource, Clone)]
+pub struct CameraKeyScheme {
+    pub thrust_forward: KeyCode,
+    pub thrust_backward: KeyCode,
+    pub strafe_left: KeyCode,
+    pub strafe_right: KeyCode,
+    pub rise: KeyCode,
+    pub sink: KeyCode,
+    pub roll_left: KeyCode,
+    pub roll_right: KeyCode,
+    pub boost: KeyCode,
+    pub mouse_sensitivity: f64,
+}
+
+impl Default for CameraKeyScheme {
+    fn default() -> Self {
+        Self {
+            thrust_forward: KeyCode::KeyW,
+            thrust_backward: KeyCode::KeyS,
+            strafe_left: KeyCode::KeyA,
+            strafe_right: KeyCode::KeyD,
+            rise: KeyCode::ShiftLeft,
+            sink: KeyCode::ControlLeft,
+            roll_left: KeyCode::KeyQ,
+            roll_right: KeyCode::KeyE,
+            boost: KeyCode::Space,
+            mouse_sensitivity: 0.1,
+        }
+    }
+}
+
+/// Replaces big_space's default `CameraInput` bindings with `CameraKeyScheme`. Like the defaults, the scheme is only
+/// applied while `CameraInput::defaults_disabled` is false.
+pub struct CameraKeySchemePlugin;
+impl Plugin for CameraKeySchemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraKeyScheme>().add_systems(
+            PostUpdate,
+            apply_camera_key_scheme
+                .after(default_camera_inputs)
+                .before(camera_controller::<i64>)
+                .run_if(|cam: Res<CameraInput>| !cam.defaults_disabled),
+        );
+    }
+}
+
+/// Overwrites whatever `default_camera_inputs` wrote this frame with the inputs from `CameraKeyScheme`.
+pub fn apply_camera_key_scheme(
+    key: Res<ButtonInput<KeyCode>>,
+    scheme: Res<CameraKeyScheme>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut cam: ResMut<CameraInput>,
+) {
+    let axis = |positive: KeyCode, negative: KeyCode| -> f64 {
+        key.pressed(positive) as i8 as f64 - key.pressed(negative) as i8 as f64
+    };
+    /* The camera looks down its local -Z */
+    cam.forward = axis(scheme.thrust_backward, scheme.thrust_forward);
+    cam.right = axis(scheme.strafe_right, scheme.strafe_left);
+    cam.up = axis(scheme.rise, scheme.sink);
+    cam.roll = axis(scheme.roll_left, scheme.roll_right);
+    cam.boost = key.pressed(scheme.boost);
+
+    let motion: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    cam.pitch = -motion.y as f64 * scheme.mouse_sensitivity;
+    cam.yaw = -motion.x as f64 * scheme.mouse_sensitivity;
+}
+
+/// While present on a `CameraController` camera, the camera slews its rotation toward `target` until aligned.
+/// The component is removed and a `FocusCompleted` event is sent once the camera faces the target.
+#[derive(Component, Debug)]
+pub struct AutoFocus {
+    pub target: Entity,
+}
+
+#[derive(Event, Debug)]
+pub struct FocusCompleted {
+    pub camera: Entity,
+    pub target: Entity,
+}
+
+/// While present on a camera, the camera rolls about its view axis until its up vector lines up with the local
+/// vertical: away from the nearest `GravityAttractor`, or world Y when there is none. The component is removed once
+/// the horizon is level.
+#[derive(Component, Debug)]
+pub struct LevelHorizon;
+
+/// While present on the floating origin camera, the camera eases toward a point `standoff_distance_m` short of
+/// `target` on the line from the target to the camera's current position, whichever way the camera is facing; add
+/// `AutoFocus` as well to turn it toward the target on the way. The component is removed and a `FlyToCompleted` event
+/// is sent on arrival.
+#[derive(Component, Debug)]
+pub struct FlyToTarget {
+    pub target: Entity,
+    pub standoff_distance_m: f64,
+}
+
+/// Like `FlyToTarget`, but eases the camera to a fixed pose in the root reference frame, such as a bookmark.
+#[derive(Component, Debug)]
+pub struct FlyToPose {
+    pub position: DVec3,
+    pub rotation: Quat,
+}
+
+#[derive(Event, Debug)]
+pub struct FlyToCompleted {
+    pub camera: Entity,
+}
+
+/// While present on the floating origin camera, the camera orbits `target` at `distance` meters instead of flying
+/// freely. Dragging with the right mouse button changes `yaw` and `pitch`, and scrolling changes `distance`.
+/// The default `CameraController` inputs are disabled while any camera is in this mode.
+#[derive(Component, Debug)]
+pub struct OrbitCameraMode {
+    pub target: Entity,
+    pub distance: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+#[derive(Resource, Clone)]
+pub struct CameraFocusSettings {
+    /// Angle in radians under which the camera snaps onto the target and focusing stops.
+    pub completion_threshold: f32,
+    /// Angle in radians the camera turns per frame while focusing.
+    pub step: f32,
+    /// Fraction of the remaining distance covered per second while flying to a target.
+    pub fly_to_rate: f64,
+    /// Flying stops once within this fraction of the standoff distance (and at least one meter) of the destination.
+    pub fly_to_tolerance: f64,
+}
+
+impl Default for CameraFocusSettings {
+    fn default() -> Self {
+        Self {
+            completion_threshold: 0.01,
+            step: 0.01,
+            fly_to_rate: 2.0,
+            fly_to_tolerance: 0.001,
+        }
+    }
+}
+
+pub struct CameraFocusPlugin;
+impl Plugin for CameraFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraFocusSettings>()
+            .add_event::<FocusCompleted>()
+            .add_event::<FlyToCompleted>()
+            .add_systems(
+                Update,
+                (focus_on_target, fly_to_target, fly_to_pose, level_horizon),
+            );
+    }
+}
+
+pub const CAMERA_BOOKMARK_SLOTS: usize = 10;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CameraBookmark {
+    pub cell: GridCell<i64>,
+    pub transform: Transform,
+}
+
+/// Saved camera poses, recalled with the number keys. Holding Alt while pressing a number saves into that slot; Control
+/// is left free because it sinks the camera under `CameraKeyScheme`.
+#[derive(Resource, Clone, Default)]
+pub struct CameraBookmarks {
+    slots: [Option<CameraBookmark>; CAMERA_BOOKMARK_SLOTS],
+    /// Ease to a recalled bookmark with `FlyToPose` instead of jumping straight to it.
+    pub tween_on_recall: bool,
+}
+
+impl CameraBookmarks {
+    pub fn save(&mut self, slot: usize, cell: GridCell<i64>, transform: Transform) {
+        match self.slots.get_mut(slot) {
+            Some(bookmark) => *bookmark = Some(CameraBookmark { cell, transform }),
+            None => error!("camera bookmark slot {:?} out of range", slot),
+        }
+    }
+
+    pub fn recall(&self, slot: usize) -> Option<CameraBookmark> {
+        self.slots.get(slot).copied().flatten()
+    }
+}
+
+pub struct CameraBookmarksPlugin;
+impl Plugin for CameraBookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraBookmarks {
+            tween_on_recall: true,
+            ..default()
+        })
+        .add_systems(Update, camera_bookmark_input);
+    }
+}
+
+const BOOKMARK_KEYS: [KeyCode; CAMERA_BOOKMARK_SLOTS] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+pub fn camera_bookmark_input(
+    mut commands: Commands,
+    mut camera_query: Query<
+        (Entity, &mut GridCell<i64>, &mut Transform),
+        (With<CameraController>, With<FloatingOrigin>),
+    >,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    key: Res<ButtonInput<KeyCode>>,
+    space: Res<RootReferenceFrame<i64>>,
+) {
+    let Some(slot) = BOOKMARK_KEYS
+        .iter()
+        .position(|slot_key| key.just_pressed(*slot_key))
+    else {
+        return;
+    };
+    let Ok((camera_entity, mut camera_cell, mut camera_transform)) = camera_query.get_single_mut()
+    else {
+        return;
+    };
+
+    if key.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        debug!("saving camera bookmark {:?}", slot);
+        bookmarks.save(slot, *camera_cell, *camera_transform);
+        return;
+    }
+
+    let Some(bookmark) = bookmarks.recall(slot) else {
+        debug!("camera bookmark {:?} is empty", slot);
+        return;
+    };
+    debug!("recalling camera bookmark {:?}", slot);
+    let mut camera_entity_commands = commands.entity(camera_entity);
+    camera_entity_commands.remove::<(AutoFocus, FlyToTarget, OrbitCameraMode)>();
+    match bookmarks.tween_on_recall {
+        true => {
+            camera_entity_commands.insert(FlyToPose {
+                position: space.grid_position_double(&bookmark.cell, &bookmark.transform),
+                rotation: bookmark.transform.rotation,
+            });
+        }
+        false => {
+            camera_entity_commands.remove::<FlyToPose>();
+            *camera_cell = bookmark.cell;
+            *camera_transform = bookmark.transform;
+        }
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct OrbitCameraSettings {
+    /// Radians of yaw and pitch per pixel of mouse drag.
+    pub sensitivity: f32,
+    /// Fraction of the current distance added or removed per scroll line.
+    pub zoom_step: f64,
+    pub min_distance: f64,
+}
+
+impl Default for OrbitCameraSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.005,
+            zoom_step: 0.1,
+            min_distance: 1.0,
+        }
+    }
+}
+
+pub struct OrbitCameraPlugin;
+impl Plugin for OrbitCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OrbitCameraSettings>()
+            .add_systems(Update, orbit_camera);
+    }
+}
+
+impl OrbitCameraMode {
+    /// Enters orbit mode around `target` from the camera's current pose, so the view does not jump.
+    pub fn from_current_view(
+        target: Entity,
+        camera_position: DVec3,
+        target_position: DVec3,
+    ) -> Self {
+        let offset = camera_position - target_position;
+        let direction = offset.normalize_or_zero().as_vec3();
+        Self {
+            target,
+            distance: offset.length(),
+            yaw: direction.x.atan2(direction.z),
+            pitch: -direction.y.clamp(-1.0, 1.0).asin(),
+        }
+    }
+
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0)
+    }
+
+    /// Camera position relative to the target. The camera looks down its local -Z, so it sits on +Z.
+    pub fn offset(&self) -> DVec3 {
+        (self.rotation() * Vec3::Z).as_dvec3() * self.distance
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn orbit_camera(
+    mut commands: Commands,
+    mut camera_query: Query<
+        (
+            Entity,
+            &mut GridCell<i64>,
+            &mut Transform,
+            &mut OrbitCameraMode,
+        ),
+        With<CameraController>,
+    >,
+    target_query: TargetPositionQuery,
+    mut removed_orbit_modes: RemovedComponents<OrbitCameraMode>,
+    mut cam: ResMut<CameraInput>,
+    mut defaults_disabled_before_orbit: Local<Option<bool>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    btn: Res<ButtonInput<MouseButton>>,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<OrbitCameraSettings>,
+) {
+    let drag: Vec2 = match btn.pressed(MouseButton::Right) {
+        true => mouse_motion.read().map(|motion| motion.delta).sum(),
+        false => {
+            mouse_motion.clear();
+            Vec2::ZERO
+        }
+    };
+    let scroll: f32 = mouse_wheel.read().map(|wheel| wheel.y).sum();
+
+    if removed_orbit_modes.read().count() > 0 && camera_query.is_empty() {
+        if let Some(defaults_disabled) = defaults_disabled_before_orbit.take() {
+            cam.defaults_disabled = defaults_disabled;
+        }
+    }
+
+    for (camera_entity, mut camera_cell, mut camera_transform, mut orbit) in camera_query.iter_mut()
+    {
+        let Some(target_position) = target_grid_position(&target_query, &space, orbit.target)
+        else {
+            error!("orbit_camera: target {:?} not found", orbit.target);
+            commands.entity(camera_entity).remove::<OrbitCameraMode>();
+            continue;
+        };
+        if defaults_disabled_before_orbit.is_none() {
+            *defaults_disabled_before_orbit = Some(cam.defaults_disabled);
+        }
+        cam.defaults_disabled = true;
+
+        orbit.yaw -= drag.x * settings.sensitivity;
+        orbit.pitch =
+            (orbit.pitch - drag.y * settings.sensitivity).clamp(-PI / 2.0 + 0.01, PI / 2.0 - 0.01);
+        orbit.distance = (orbit.distance * (1.0 - settings.zoom_step * scroll as f64))
+            .max(settings.min_distance);
+
+        let (new_cell, new_translation) =
+            space.translation_to_grid(target_position + orbit.offset());
+        *camera_cell = new_cell;
+        camera_transform.translation = new_translation;
+        camera_transform.rotation = orbit.rotation();
+    }
+}
+
+/// Scales `CameraController` speed with the distance to the nearest `ValidTarget` surface, so the camera creeps near
+/// small objects and crosses interplanetary space quickly.
+#[derive(Resource, Clone)]
+pub struct AdaptiveSpeedSettings {
+    /// Speed at `reference_distance_m` from the nearest surface.
+    pub base_speed: f64,
+    pub reference_distance_m: f64,
+    pub min_multiplier: f64,
+    pub max_multiplier: f64,
+}
+
+impl Default for AdaptiveSpeedSettings {
+    fn default() -> Self {
+        Self {
+            base_speed: 1.0,
+            reference_distance_m: 1.0,
+            min_multiplier: 0.01,
+            max_multiplier: 1e15,
+        }
+    }
+}
+
+impl AdaptiveSpeedSettings {
+    pub fn speed_at(&self, distance_m: f64) -> f64 {
+        let multiplier = (distance_m / self.reference_distance_m)
+            .clamp(self.min_multiplier, self.max_multiplier);
+        self.base_speed * multiplier
+    }
+}
+
+pub struct AdaptiveSpeedPlugin;
+impl Plugin for AdaptiveSpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AdaptiveSpeedSettings>().add_systems(
+            Update,
+            (disable_near_object_slowing, adapt_camera_speed).chain(),
+        );
+    }
+}
+
+/// big_space's controller multiplies its speed by the nearest object's distance on its own. `adapt_camera_speed`
+/// already scales with distance, so leaving that on would make speed grow with distance squared.
+pub fn disable_near_object_slowing(
+    mut camera_query: Query<&mut CameraController, Added<CameraController>>,
+) {
+    for mut controller in camera_query.iter_mut() {
+        controller.slow_near_objects = false;
+    }
+}
+
+/// Sets each `CameraController` speed from the distance to the nearest `ValidTarget` surface, falling back to the
+/// controller's own nearest object when there are no targets. A speed changed by anything else since the last frame,
+/// such as the player, is kept as a multiplier on the adaptive speed from then on rather than overwritten.
+pub fn adapt_camera_speed(
+    mut camera_query: Query<(Entity, &mut CameraController, &GlobalTransform)>,
+    valid_targets_query: Query<(&GlobalTransform, &ComponentInfo), With<ValidTarget>>,
+    settings: Res<AdaptiveSpeedSettings>,
+    mut adapted: Local<HashMap<Entity, AdaptedSpeed>>,
+) {
+    for (camera_entity, mut controller, camera_global_transform) in camera_query.iter_mut() {
+        let camera_translation = camera_global_transform.translation();
+        let nearest_target_distance = valid_targets_query
+            .iter()
+            .map(|(target_global_transform, target_info)| {
+                (target_global_transform.translation() - camera_translation).length() as f64
+                    - target_info.size as f64
+            })
+            .min_by(|a, b| a.total_cmp(b));
+        let Some(distance_m) = nearest_target_distance
+            .or_else(|| controller.nearest_object().map(|(_, distance)| distance))
+        else {
+            continue;
+        };
+        let adapted_speed = adapted.entry(camera_entity).or_default();
+        if let Some(written) = adapted_speed.written {
+            if controller.speed != written && written > 0.0 {
+                adapted_speed.user_multiplier *= controller.speed / written;
+            }
+        }
+        let speed = settings.speed_at(distance_m.max(0.0)) * adapted_speed.user_multiplier;
+        controller.speed = speed;
+        adapted_speed.written = Some(speed);
+    }
+}
+
+/// What `adapt_camera_speed` last wrote to a controller, and the factor the player has scaled it by since.
+#[derive(Clone, Copy, Debug)]
+pub struct AdaptedSpeed {
+    written: Option<f64>,
+    user_multiplier: f64,
+}
+
+impl Default for AdaptedSpeed {
+    fn default() -> Self {
+        Self {
+            written: None,
+            user_multiplier: 1.0,
+        }
+    }
+}
+
+/// While `Action::DampVelocity` is held the camera's translational input is ignored and its smoothness is lowered to
+/// `damped_smoothness`, so the controller's own smoothing bleeds off any drift quickly. Each camera's original
+/// smoothness returns on release.
+#[derive(Resource, Clone)]
+pub struct InertialDampingSettings {
+    pub damped_smoothness: f64,
+}
+
+impl Default for InertialDampingSettings {
+    fn default() -> Self {
+        Self {
+            damped_smoothness: 0.5,
+        }
+    }
+}
+
+pub struct InertialDampingPlugin;
+impl Plugin for InertialDampingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InertialDampingSettings>()
+            .init_resource::<KeyBindings>()
+            .add_systems(
+                PostUpdate,
+                damp_camera_velocity
+                    .after(default_camera_inputs)
+                    .after(apply_camera_key_scheme)
+                    .before(camera_controller::<i64>),
+            );
+    }
+}
+
+/// Runs after the camera inputs are gathered, so the controller sees zero translation and eases its velocity to rest.
+pub fn damp_camera_velocity(
+    mut camera_query: Query<(Entity, &mut CameraController)>,
+    camera_input: Option<ResMut<CameraInput>>,
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    settings: Res<InertialDampingSettings>,
+    mut smoothness_before_damping: Local<HashMap<Entity, f64>>,
+) {
+    let damping = key_bindings.pressed(&key, Action::DampVelocity);
+    if damping {
+        if let Some(mut camera_input) = camera_input {
+            camera_input.forward = 0.0;
+            camera_input.right = 0.0;
+            camera_input.up = 0.0;
+        }
+    }
+    for (entity, mut controller) in camera_query.iter_mut() {
+        match (damping, smoothness_before_damping.get(&entity).copied()) {
+            (true, None) => {
+                smoothness_before_damping.insert(entity, controller.smoothness);
+                controller.smoothness = settings.damped_smoothness;
+            }
+            (false, Some(smoothness)) => controller.smoothness = smoothness,
+            _ => {}
+        }
+    }
+    if !damping {
+        smoothness_before_damping.clear();
+    }
+}
+
+/// While present on a `CameraController` camera, the camera slews to face along (`Prograde`) or against
+/// (`Retrograde`) its own direction of travel and holds that attitude. The component is removed once the camera
+/// slows below `AutopilotSettings::min_speed`, since the direction of travel is meaningless at rest.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutopilotMode {
+    Prograde,
+    Retrograde,
+}
+
+#[derive(Resource, Clone)]
+pub struct AutopilotSettings {
+    /// Angle in radians the camera turns per frame while holding attitude.
+    pub step: f32,
+    /// Speed in m/s under which the autopilot disengages.
+    pub min_speed: f64,
+}
+
+impl Default for AutopilotSettings {
+    fn default() -> Self {
+        Self {
+            step: 0.01,
+            min_speed: 0.01,
+        }
+    }
+}
+
+/// `Action::HoldPrograde` and `Action::HoldRetrograde` engage the matching `AutopilotMode` on the camera, and
+/// pressing the same one again disengages it.
+pub struct AutopilotPlugin;
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutopilotSettings>()
+            .init_resource::<KeyBindings>()
+            .add_systems(Update, (autopilot_input, hold_autopilot_attitude).chain());
+    }
+}
+
+pub fn autopilot_input(
+    mut commands: Commands,
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    camera_query: Query<(Entity, Option<&AutopilotMode>), With<CameraController>>,
+) {
+    let requested = if key_bindings.just_pressed(&key, Action::HoldPrograde) {
+        AutopilotMode::Prograde
+    } else if key_bindings.just_pressed(&key, Action::HoldRetrograde) {
+        AutopilotMode::Retrograde
+    } else {
+        return;
+    };
+    for (camera_entity, current) in camera_query.iter() {
+        match current {
+            Some(mode) if *mode == requested => {
+                commands.entity(camera_entity).remove::<AutopilotMode>();
+            }
+            _ => {
+                commands.entity(camera_entity).insert(requested);
+            }
+        }
+    }
+}
+
+pub fn hold_autopilot_attitude(
+    mut commands: Commands,
+    mut camera_query: Query<(Entity, &CameraController, &mut Transform, &AutopilotMode)>,
+    time: Res<Time>,
+    settings: Res<AutopilotSettings>,
+) {
+    let delta_seconds = time.delta_seconds_f64();
+    if delta_seconds <= 0.0 {
+        return;
+    }
+    for (camera_entity, controller, mut transform, mode) in camera_query.iter_mut() {
+        let (displacement, _) = controller.velocity();
+        if displacement.length() / delta_seconds < settings.min_speed {
+            debug!("autopilot disengaged, camera is at rest");
+            commands.entity(camera_entity).remove::<AutopilotMode>();
+            continue;
+        }
+        let direction = match mode {
+            AutopilotMode::Prograde => displacement,
+            AutopilotMode::Retrograde => -displacement,
+        };
+        let target_rotation =
+            look_toward(direction, transform.up().normalize(), transform.rotation);
+        /* Snap on within a step so the hold doesn't jitter; it never completes since the heading keeps changing */
+        let (new_rotation, _aligned) = slew_rotation_toward(
+            transform.rotation,
+            target_rotation,
+            settings.step,
+            settings.step,
+        );
+        transform.rotation = new_rotation;
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct DoubleClickFocusSettings {
+    /// Maximum seconds between the two presses of a double click.
+    pub window_seconds: f32,
+    /// Maximum distance in logical pixels between the click and the target picked by it.
+    pub pick_radius_px: f32,
+}
+
+impl Default for DoubleClickFocusSettings {
+    fn default() -> Self {
+        Self {
+            window_seconds: 0.3,
+            pick_radius_px: 30.0,
+        }
+    }
+}
+
+/// Double clicking the left mouse button selects the `ValidTarget` nearest to the click and starts `AutoFocus` on it.
+pub struct DoubleClickFocusPlugin;
+impl Plugin for DoubleClickFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DoubleClickFocusSettings>()
+            .add_event::<TargetSelected>()
+            .add_systems(Update, double_click_focus);
+    }
+}
+
+/// Turns `current` by at most `step` radians toward `target` about their shared axis.
+/// Returns the new rotation and whether it is now within `threshold` radians of `target`, in which case the
+/// returned rotation is exactly `target`.
+pub fn slew_rotation_toward(
+    current: Quat,
+    target: Quat,
+    step: f32,
+    threshold: f32,
+) -> (Quat, bool) {
+    let angle_between = target.normalize().angle_between(current.normalize());
+    trace!("angle_between: {:?}", angle_between);
+    if angle_between < threshold {
+        return (target, true);
+    }
+    let rotation_difference = target * current.inverse();
+    let (rotation_axis, mut rotation_angle) = rotation_difference.to_axis_angle();
+    if rotation_angle > PI {
+        rotation_angle -= PI * 2.0;
+    };
+    trace!("rotation_axis: {:?}", rotation_axis);
+    trace!("rotation_angle: {:?}", rotation_angle);
+    let mut new_transform = Transform::from_rotation(current);
+    new_transform.rotate_axis(rotation_axis, step * rotation_angle.signum());
+    (new_transform.rotation, false)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn focus_on_target(
+    mut commands: Commands,
+    mut camera_3d_query: Query<
+        (Entity, &GridCell<i64>, &mut Transform, &AutoFocus),
+        (With<CameraController>, With<Camera3d>),
+    >,
+    target_query: TargetPositionQuery,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<CameraFocusSettings>,
+    mut focus_completed: EventWriter<FocusCompleted>,
+) {
+    for (camera_entity, camera_cell, mut camera_3d_transform, auto_focus) in
+        camera_3d_query.iter_mut()
+    {
+        match target_grid_position(&target_query, &space, auto_focus.target) {
+            Some(target_position) => {
+                let camera_position = space.grid_position_double(camera_cell, &camera_3d_transform);
+                let target_rotation = look_toward(
+                    target_position - camera_position,
+                    camera_3d_transform.up().normalize(),
+                    camera_3d_transform.rotation,
+                );
+                let (new_rotation, aligned) = slew_rotation_toward(
+                    camera_3d_transform.rotation,
+                    target_rotation,
+                    settings.step,
+                    settings.completion_threshold,
+                );
+                camera_3d_transform.rotation = new_rotation;
+                if aligned {
+                    debug!("target aligned");
+                    commands.entity(camera_entity).remove::<AutoFocus>();
+                    focus_completed.send(FocusCompleted {
+                        camera: camera_entity,
+                        target: auto_focus.target,
+                    });
+                }
+            }
+            None => {
+                error!("focus_on_target: target {:?} not found", auto_focus.target);
+                commands.entity(camera_entity).remove::<AutoFocus>();
+            }
+        }
+    }
+}
+
+/// Selects and focuses the target nearest to a double click.
+/// A single click also grabs the cursor in the experiments, so the pick point is where the cursor was at the first
+/// press while it was still free, or the center of the viewport (the crosshair) when it was already grabbed.
+/// Schedule this before any system that changes the cursor grab mode on click.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn double_click_focus(
+    mut commands: Commands,
+    mut last_press: Local<Option<(f32, Vec2)>>,
+    btn: Res<ButtonInput<MouseButton>>,
+    time: Res<Time<Real>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<
+        (Entity, &Camera, &GlobalTransform),
+        (With<CameraController>, With<Camera3d>),
+    >,
+    valid_targets_query: Query<(Entity, &GlobalTransform), With<ValidTarget>>,
+    settings: Res<DoubleClickFocusSettings>,
+    mut target_selected: EventWriter<TargetSelected>,
+) {
+    if !btn.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok((camera_entity, camera, camera_global_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    let now = time.elapsed_seconds();
+    let pick_point = match (window.cursor.grab_mode, window.cursor_position()) {
+        (CursorGrabMode::None, Some(cursor_position)) => cursor_position,
+        _ => match camera.logical_viewport_size() {
+            Some(viewport_size) => viewport_size / 2.0,
+            None => return,
+        },
+    };
+
+    let first_press = match *last_press {
+        Some((pressed_at, first_pick_point)) if now - pressed_at <= settings.window_seconds => {
+            first_pick_point
+        }
+        _ => {
+            *last_press = Some((now, pick_point));
+            return;
+        }
+    };
+    *last_press = None;
+
+    let nearest = valid_targets_query
+        .iter()
+        .filter_map(|(entity, target_global_transform)| {
+            camera
+                .world_to_viewport(
+                    camera_global_transform,
+                    target_global_transform.translation(),
+                )
+                .map(|viewport_position| (entity, viewport_position.distance(first_press)))
+        })
+        .filter(|(_, distance)| *distance <= settings.pick_radius_px)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((target, _)) = nearest {
+        debug!("double click selected {:?}", target);
+        target_selected.send(TargetSelected { target });
+        commands.entity(camera_entity).insert(AutoFocus { target });
+    }
+}
+
+/// Grid placement of possible camera targets, including targets parented to a grid entity such as the Sun's mesh,
+/// and satellites in a nested `ReferenceFrame`.
+/// Systems that also write a `Transform` can narrow `F` further to keep the queries disjoint.
+pub type TargetPositionQuery<'w, 's, F = Without<CameraController>> = Query<
+    'w,
+    's,
+    (
+        Option<&'static GridCell<i64>>,
+        &'static Transform,
+        Option<&'static Parent>,
+        Option<&'static ReferenceFrame<i64>>,
+    ),
+    F,
+>;
+
+/// Position of `entity` in the root reference frame with double precision.
+/// Entities without a `GridCell` are placed relative to the nearest ancestor that has one, and grid entities whose
+/// parent has a `ReferenceFrame` are placed within that frame.
+pub fn target_grid_position<F: QueryFilter>(
+    target_query: &TargetPositionQuery<'_, '_, F>,
+    space: &RootReferenceFrame<i64>,
+    entity: Entity,
+) -> Option<DVec3> {
+    let (cell, transform, parent, _) = target_query.get(entity).ok()?;
+    let Some(parent) = parent else {
+        return cell.map(|cell| space.grid_position_double(cell, transform));
+    };
+    let (_, parent_transform, _, parent_frame) = target_query.get(parent.get()).ok()?;
+    let offset = match (cell, parent_frame) {
+        (Some(cell), Some(frame)) => frame.grid_position_double(cell, transform),
+        (Some(cell), None) => return Some(space.grid_position_double(cell, transform)),
+        (None, _) => transform.translation.as_dvec3(),
+    };
+    let parent_position = target_grid_position(target_query, space, parent.get())?;
+    Some(parent_position + parent_transform.rotation.as_dquat() * offset)
+}
+
+/// Distance at which a sphere of `radius_m` spans `fov_fraction` of a perspective camera's `vertical_fov` (radians).
+/// Never closer than just outside the sphere, so very large bodies like the Sun are framed from outside them.
+pub fn framing_distance(radius_m: f64, vertical_fov: f32, fov_fraction: f32) -> f64 {
+    let half_angle = (vertical_fov * fov_fraction.clamp(0.01, 1.0) / 2.0) as f64;
+    (radius_m / half_angle.sin()).max(radius_m * 1.1)
+}
+
+/// Flies the camera to where `target` fills `fov_fraction` of the vertical field of view and turns to face it.
+pub fn frame_target(
+    commands: &mut Commands,
+    camera: Entity,
+    projection: &Projection,
+    target: Entity,
+    target_radius_m: f64,
+    fov_fraction: f32,
+) {
+    let Projection::Perspective(perspective) = projection else {
+        error!("frame_target: camera {:?} is not perspective", camera);
+        return;
+    };
+    let standoff_distance_m = framing_distance(target_radius_m, perspective.fov, fov_fraction);
+    debug!(
+        "framing {:?} at {:?}m ({:?} of fov)",
+        target, standoff_distance_m, fov_fraction
+    );
+    commands.entity(camera).insert((
+        AutoFocus { target },
+        FlyToTarget {
+            target,
+            standoff_distance_m,
+        },
+    ));
+}
+
+pub fn level_horizon(
+    mut commands: Commands,
+    mut camera_query: Query<(Entity, &mut Transform, &GlobalTransform), With<LevelHorizon>>,
+    attractor_query: Query<&GlobalTransform, With<GravityAttractor>>,
+    settings: Res<CameraFocusSettings>,
+) {
+    for (camera_entity, mut camera_transform, camera_global_transform) in camera_query.iter_mut() {
+        let camera_translation = camera_global_transform.translation();
+        let reference_up = attractor_query
+            .iter()
+            .map(|attractor_global_transform| {
+                camera_translation - attractor_global_transform.translation()
+            })
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .and_then(|offset| offset.try_normalize())
+            .unwrap_or(Vec3::Y);
+
+        let forward = camera_transform.forward();
+        if forward.cross(reference_up).length_squared() < f32::EPSILON {
+            debug!("looking along the reference up, horizon is undefined");
+            commands.entity(camera_entity).remove::<LevelHorizon>();
+            continue;
+        }
+        let level_rotation = Transform::from_rotation(camera_transform.rotation)
+            .looking_to(*forward, reference_up)
+            .rotation;
+        let (new_rotation, level) = slew_rotation_toward(
+            camera_transform.rotation,
+            level_rotation,
+            settings.step,
+            settings.completion_threshold,
+        );
+        camera_transform.rotation = new_rotation;
+        if level {
+            debug!("horizon level");
+            commands.entity(camera_entity).remove::<LevelHorizon>();
+        }
+    }
+}
+
+/// Moves `current` a `rate * delta_seconds` fraction of the way to `destination`, so the motion slows down as it
+/// arrives. Returns the new position and whether it is now within `tolerance` meters of `destination`.
+pub fn ease_toward(
+    current: DVec3,
+    destination: DVec3,
+    rate: f64,
+    delta_seconds: f64,
+    tolerance: f64,
+) -> (DVec3, bool) {
+    let remaining = destination - current;
+    if remaining.length() <= tolerance {
+        return (destination, true);
+    }
+    let fraction = (rate * delta_seconds).clamp(0.0, 1.0);
+    (current + (remaining * fraction), false)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn fly_to_target(
+    mut commands: Commands,
+    mut camera_query: Query<
+        (Entity, &mut GridCell<i64>, &mut Transform, &FlyToTarget),
+        With<CameraController>,
+    >,
+    target_query: TargetPositionQuery,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<CameraFocusSettings>,
+    time: Res<Time>,
+    mut fly_to_completed: EventWriter<FlyToCompleted>,
+) {
+    for (camera_entity, mut camera_cell, mut camera_transform, fly_to) in camera_query.iter_mut() {
+        let Some(target_position) = target_grid_position(&target_query, &space, fly_to.target)
+        else {
+            error!("fly_to_target: target {:?} not found", fly_to.target);
+            commands.entity(camera_entity).remove::<FlyToTarget>();
+            continue;
+        };
+        let camera_position = space.grid_position_double(&camera_cell, &camera_transform);
+        let approach_direction = (camera_position - target_position).normalize_or_zero();
+        let destination = target_position + (approach_direction * fly_to.standoff_distance_m);
+        let (new_position, arrived) = ease_toward(
+            camera_position,
+            destination,
+            settings.fly_to_rate,
+            time.delta_seconds_f64(),
+            (fly_to.standoff_distance_m * settings.fly_to_tolerance).max(1.0),
+        );
+        let (new_cell, new_translation) = space.translation_to_grid(new_position);
+        *camera_cell = new_cell;
+        camera_transform.translation = new_translation;
+        if arrived {
+            debug!("fly to target arrived");
+            commands.entity(camera_entity).remove::<FlyToTarget>();
+            fly_to_completed.send(FlyToCompleted {
+                camera: camera_entity,
+            });
+        }
+    }
+}
+
+pub fn fly_to_pose(
+    mut commands: Commands,
+    mut camera_query: Query<
+        (Entity, &mut GridCell<i64>, &mut Transform, &FlyToPose),
+        With<CameraController>,
+    >,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<CameraFocusSettings>,
+    time: Res<Time>,
+    mut fly_to_completed: EventWriter<FlyToCompleted>,
+) {
+    for (camera_entity, mut camera_cell, mut camera_transform, fly_to) in camera_query.iter_mut() {
+        let camera_position = space.grid_position_double(&camera_cell, &camera_transform);
+        let (new_position, arrived) = ease_toward(
+            camera_position,
+            fly_to.position,
+            settings.fly_to_rate,
+            time.delta_seconds_f64(),
+            1.0,
+        );
+        let (new_cell, new_translation) = space.translation_to_grid(new_position);
+        *camera_cell = new_cell;
+        camera_transform.translation = new_translation;
+        camera_transform.rotation = match arrived {
+            true => fly_to.rotation,
+            false => camera_transform.rotation.slerp(
+                fly_to.rotation,
+                (settings.fly_to_rate * time.delta_seconds_f64()).clamp(0.0, 1.0) as f32,
+            ),
+        };
+        if arrived {
+            debug!("fly to pose arrived");
+            commands.entity(camera_entity).remove::<FlyToPose>();
+            fly_to_completed.send(FlyToCompleted {
+                camera: camera_entity,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adaptive_speed_is_linear_in_distance() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AdaptiveSpeedPlugin))
+            .insert_resource(AdaptiveSpeedSettings {
+                base_speed: 2.0,
+                reference_distance_m: 10.0,
+                ..default()
+            });
+        let camera = app
+            .world
+            .spawn((default_space_camera(), GlobalTransform::IDENTITY))
+            .id();
+        let target = app
+            .world
+            .spawn((
+                ValidTarget,
+                ComponentInfo {
+                    name: "Target".to_string(),
+                    size: 0.0,
+                },
+                GlobalTransform::from_xyz(100.0, 0.0, 0.0),
+            ))
+            .id();
+
+        app.update();
+        let controller = app.world.get::<CameraController>(camera).unwrap();
+        assert!(!controller.slow_near_objects);
+        assert_eq!(controller.speed, 20.0);
+
+        *app.world.get_mut::<GlobalTransform>(target).unwrap() =
+            GlobalTransform::from_xyz(400.0, 0.0, 0.0);
+        app.update();
+        let controller = app.world.get::<CameraController>(camera).unwrap();
+        assert!(!controller.slow_near_objects);
+        assert_eq!(controller.speed, 80.0);
+    }
+}
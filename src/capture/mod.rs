@@ -0,0 +1,65 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+
+use crate::input::{Action, KeyBindings};
+
+/// Where screenshots are written, relative to the working directory.
+#[derive(Resource, Clone)]
+pub struct CaptureSettings {
+    pub directory: PathBuf,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("screenshots"),
+        }
+    }
+}
+
+/// Saves a PNG of the primary window whenever `Action::Screenshot` is pressed.
+pub struct CapturePlugin;
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CaptureSettings>()
+            .init_resource::<KeyBindings>()
+            .add_systems(Update, capture_screenshot);
+    }
+}
+
+/// File name for a screenshot taken now, e.g. `screenshot-1718000000123.png` (milliseconds since the Unix epoch).
+pub fn screenshot_file_name() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    format!("screenshot-{}.png", millis)
+}
+
+pub fn capture_screenshot(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    settings: Res<CaptureSettings>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshot_manager: ResMut<ScreenshotManager>,
+) {
+    if !key_bindings.just_pressed(&key, Action::Screenshot) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(&settings.directory) {
+        error!("{:?}", e);
+        return;
+    }
+    let path = settings.directory.join(screenshot_file_name());
+    match screenshot_manager.save_screenshot_to_disk(window, &path) {
+        Ok(()) => info!("saving screenshot to {:?}", path),
+        Err(e) => error!("{:?}", e),
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+};
+
+use anyhow::anyhow;
+
+use bevy::{
+    ecs::system::EntityCommands,
+    log::Level,
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+    tasks::{AsyncComputeTaskPool, Task},
+    utils::{tracing::span, HashMap},
+};
+use bevy_rapier3d::prelude::*;
+use futures_lite::future;
+
+/// Replaced by a `Collider` computed from the entity's own mesh once that mesh has loaded.
+/// Typically inserted from a `SceneHook` on every mesh primitive of a loaded glTF scene, see `auto_collider_hook`.
+#[derive(Component, Clone, Debug)]
+pub struct AutoCollider {
+    pub shape: ComputedColliderShape,
+}
+
+pub struct AutoColliderPlugin;
+impl Plugin for AutoColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, generate_auto_colliders);
+    }
+}
+
+/// For use inside a `SceneHook` closure: marks `entity` for an `AutoCollider` if it is a mesh primitive.
+pub fn insert_auto_collider(
+    entity: &EntityRef,
+    cmds: &mut EntityCommands,
+    shape: &ComputedColliderShape,
+) {
+    if entity.contains::<Handle<Mesh>>() {
+        cmds.insert(AutoCollider {
+            shape: shape.clone(),
+        });
+    }
+}
+
+pub fn generate_auto_colliders(
+    mut commands: Commands,
+    auto_collider_query: Query<(Entity, &Handle<Mesh>, &AutoCollider)>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (entity, mesh_handle, auto_collider) in auto_collider_query.iter() {
+        let Some(mesh) = meshes.get(mesh_handle) else {
+            continue;
+        };
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<AutoCollider>();
+        match Collider::from_bevy_mesh(mesh, &auto_collider.shape) {
+            Some(collider) => {
+                debug!("generated auto collider for {:?}", entity);
+                entity_commands.insert(collider);
+            }
+            None => error!("could not generate auto collider for {:?}", entity),
+        }
+    }
+}
+
+/// Which `ComputedColliderShape` to generate for each mesh asset path, e.g. a trimesh for static terrain and a convex
+/// decomposition for dynamic bodies. Paths without an entry get `default`.
+#[derive(Resource, Clone, Debug)]
+pub struct ColliderShapes {
+    pub shapes: HashMap<String, ComputedColliderShape>,
+    pub default: ComputedColliderShape,
+}
+
+impl Default for ColliderShapes {
+    fn default() -> Self {
+        Self {
+            shapes: HashMap::default(),
+            default: ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
+        }
+    }
+}
+
+impl ColliderShapes {
+    pub fn with(mut self, path: impl Into<String>, shape: ComputedColliderShape) -> Self {
+        self.shapes.insert(path.into(), shape);
+        self
+    }
+
+    pub fn shape_for(&self, path: &str) -> &ComputedColliderShape {
+        self.shapes.get(path).unwrap_or(&self.default)
+    }
+}
+
+/// How bouncy and how grippy a surface is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicsMaterial {
+    pub restitution: f32,
+    pub friction: f32,
+}
+
+impl PhysicsMaterial {
+    pub fn bundle(&self) -> (Restitution, Friction) {
+        (
+            Restitution::coefficient(self.restitution),
+            Friction::coefficient(self.friction),
+        )
+    }
+}
+
+/// Named `PhysicsMaterial` presets, so bodies made of the same stuff collide the same way.
+#[derive(Resource, Clone, Debug)]
+pub struct PhysicsMaterials {
+    pub presets: HashMap<String, PhysicsMaterial>,
+}
+
+impl Default for PhysicsMaterials {
+    fn default() -> Self {
+        Self {
+            presets: HashMap::from_iter([
+                (
+                    "rock".to_string(),
+                    PhysicsMaterial {
+                        restitution: 0.1,
+                        friction: 0.5,
+                    },
+                ),
+                (
+                    "metal".to_string(),
+                    PhysicsMaterial {
+                        restitution: 0.01,
+                        friction: 4.0,
+                    },
+                ),
+                (
+                    "hull".to_string(),
+                    PhysicsMaterial {
+                        restitution: 0.0,
+                        friction: 0.5,
+                    },
+                ),
+            ]),
+        }
+    }
+}
+
+impl PhysicsMaterials {
+    pub fn get(&self, name: &str) -> Option<PhysicsMaterial> {
+        self.presets.get(name).copied()
+    }
+
+    /// `Restitution` and `Friction` components for the preset `name`, Rapier's defaults if there is no such preset.
+    pub fn bundle(&self, name: &str) -> (Restitution, Friction) {
+        match self.get(name) {
+            Some(material) => material.bundle(),
+            None => {
+                warn!("no physics material named {:?}", name);
+                (Restitution::default(), Friction::default())
+            }
+        }
+    }
+}
+
+/// Directory, relative to the working directory, where generated colliders are cached between runs.
+pub const COLLIDER_CACHE_DIR: &str = "collider_cache";
+
+/// Returns the cached collider for `path` if its source mesh and shape settings are unchanged, otherwise computes it
+/// from `mesh` with `shape` and refreshes the cache. `path` is the mesh's asset path, e.g.
+/// `"experiment_001/earth.glb#Mesh0/Primitive0"`.
+pub fn load_or_generate_collider(
+    path: &str,
+    mesh: &Mesh,
+    shape: &ComputedColliderShape,
+) -> Option<Collider> {
+    let span = span!(Level::INFO, "load_or_generate_collider()");
+    let _enter = span.enter();
+    debug!("start {:?}", path);
+
+    let source_hash = collider_source_hash(mesh, shape);
+    let cache_path = collider_cache_path(path);
+
+    match read_cached_collider(&cache_path, source_hash) {
+        Ok(collider) => {
+            debug!("loaded cached collider {:?}", cache_path);
+            return Some(collider);
+        }
+        Err(e) => debug!("no usable cached collider {:?}: {:?}", cache_path, e),
+    }
+
+    let collider = Collider::from_bevy_mesh(mesh, shape)?;
+    debug!("generated collider {:?}", path);
+    if let Err(e) = write_cached_collider(&cache_path, source_hash, &collider) {
+        error!("{:?}", e);
+    }
+    Some(collider)
+}
+
+/// Runs `load_or_generate_collider` on the `AsyncComputeTaskPool` so decomposing large meshes doesn't stall frames.
+pub fn generate_collider_async(
+    path: String,
+    mesh: Mesh,
+    shape: ComputedColliderShape,
+) -> Task<Option<Collider>> {
+    AsyncComputeTaskPool::get()
+        .spawn(async move { load_or_generate_collider(&path, &mesh, &shape) })
+}
+
+/// Checks a task from `generate_collider_async` without blocking. Returns `None` while it is still running.
+pub fn poll_collider_task(task: &mut Task<Option<Collider>>) -> Option<Option<Collider>> {
+    future::block_on(future::poll_once(task))
+}
+
+/// Cache file for an asset path, with path separators and labels flattened into the file name.
+pub fn collider_cache_path(path: &str) -> PathBuf {
+    let file_name: String = path
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                true => c,
+                false => '-',
+            },
+        )
+        .collect();
+    PathBuf::from(COLLIDER_CACHE_DIR).join(format!("{file_name}.collider"))
+}
+
+/// Hash of everything the generated collider depends on: vertex positions, indices and the shape settings.
+pub fn collider_source_hash(mesh: &Mesh, shape: &ComputedColliderShape) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", shape).hash(&mut hasher);
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    {
+        for position in positions {
+            position.map(f32::to_bits).hash(&mut hasher);
+        }
+    }
+    match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.hash(&mut hasher),
+        Some(Indices::U32(indices)) => indices.hash(&mut hasher),
+        None => {}
+    }
+    hasher.finish()
+}
+
+fn read_cached_collider(cache_path: &PathBuf, source_hash: u64) -> anyhow::Result<Collider> {
+    let bytes = fs::read(cache_path)?;
+    let (cached_hash, collider): (u64, Collider) = bincode::deserialize(&bytes)?;
+    if cached_hash != source_hash {
+        return Err(anyhow!("source mesh changed"));
+    }
+    Ok(collider)
+}
+
+fn write_cached_collider(
+    cache_path: &PathBuf,
+    source_hash: u64,
+    collider: &Collider,
+) -> anyhow::Result<()> {
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(cache_path, bincode::serialize(&(source_hash, collider))?)?;
+    Ok(())
+}
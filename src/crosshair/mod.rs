@@ -4,10 +4,17 @@ use bevy::{
     prelude::*,
     render::view::RenderLayers,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    transform::TransformSystem,
 };
-use big_space::IgnoreFloatingOrigin;
+use big_space::{camera::CameraController, IgnoreFloatingOrigin};
 
-#[derive(Component)]
+use crate::{
+    layers::LayerConfig,
+    targeting::{project_to_overlay, OverlayProjection},
+};
+
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
 pub enum CrosshairType {
     SmallSquareCorners,
     SmallTriangleArrows45s,
@@ -21,14 +28,170 @@ impl Default for CrosshairType {
     }
 }
 
+/// Identifies one crosshair root, so systems can address a particular crosshair when several are alive. Assigned by
+/// `spawn_crosshair` from the app's `CrosshairIds`, unique within that app.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub struct CrosshairId(pub u32);
+
+/// Hands out `CrosshairId`s in spawn order.
+#[derive(Resource, Debug, Default)]
+pub struct CrosshairIds {
+    next: u32,
+}
+
+impl CrosshairIds {
+    pub fn next(&mut self) -> CrosshairId {
+        let id = CrosshairId(self.next);
+        self.next += 1;
+        id
+    }
+}
+
+/// Put on a crosshair root to move it to wherever `entity` appears on the overlay instead of the screen center.
+/// The crosshair is hidden while `entity` is offscreen, behind the camera, or gone.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CrosshairFollow {
+    pub entity: Entity,
+}
+
+pub struct CrosshairPlugin;
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LayerConfig>()
+            .init_resource::<CrosshairIds>()
+            .register_type::<CrosshairType>()
+            .register_type::<CrosshairId>()
+            .add_systems(
+                PostUpdate,
+                (
+                    apply_crosshair_layers,
+                    follow_tracked_entities.after(TransformSystem::TransformPropagate),
+                ),
+            );
+    }
+}
+
+/// Puts new crosshairs on `LayerConfig::overlay`, or on the `RenderLayers` already on their root to override it.
+pub fn apply_crosshair_layers(
+    mut commands: Commands,
+    layer_config: Res<LayerConfig>,
+    crosshair_query: Query<(Entity, Option<&RenderLayers>), Added<CrosshairId>>,
+    children_query: Query<&Children>,
+) {
+    for (root, render_layers) in crosshair_query.iter() {
+        let render_layers = render_layers.copied().unwrap_or(layer_config.overlay);
+        commands.entity(root).insert(render_layers);
+        for descendant in children_query.iter_descendants(root) {
+            commands.entity(descendant).insert(render_layers);
+        }
+    }
+}
+
+/// Runs after transform propagation so it sees where the tracked entities are this frame, and so writes the
+/// `GlobalTransform` of each crosshair and its descendants itself rather than leaving them a frame behind.
+#[allow(clippy::type_complexity)]
+pub fn follow_tracked_entities(
+    mut crosshair_query: Query<(
+        Entity,
+        &CrosshairFollow,
+        &mut Transform,
+        &mut GlobalTransform,
+        &mut Visibility,
+    )>,
+    mut global_transforms: ParamSet<(
+        Query<&GlobalTransform, Without<CrosshairFollow>>,
+        Query<(&Transform, &mut GlobalTransform), Without<CrosshairFollow>>,
+    )>,
+    children_query: Query<&Children>,
+    camera_3d_query: Query<(Entity, &Camera), (With<Camera3d>, With<CameraController>)>,
+    camera_2d_query: Query<(Entity, &Camera), With<Camera2d>>,
+) {
+    let (Ok((camera_3d_entity, camera_3d)), Ok((camera_2d_entity, camera_2d))) =
+        (camera_3d_query.get_single(), camera_2d_query.get_single())
+    else {
+        return;
+    };
+    let tracked_query = global_transforms.p0();
+    let (Ok(camera_3d_global_transform), Ok(camera_2d_global_transform)) = (
+        tracked_query.get(camera_3d_entity),
+        tracked_query.get(camera_2d_entity),
+    ) else {
+        return;
+    };
+    let projections: Vec<_> = crosshair_query
+        .iter()
+        .map(|(_, follow, ..)| {
+            tracked_query.get(follow.entity).ok().and_then(|tracked| {
+                project_to_overlay(
+                    camera_3d,
+                    camera_3d_global_transform,
+                    camera_2d,
+                    camera_2d_global_transform,
+                    tracked.translation(),
+                )
+            })
+        })
+        .collect();
+
+    let mut descendant_query = global_transforms.p1();
+    for ((root, _, mut transform, mut global_transform, mut visibility), projection) in
+        crosshair_query.iter_mut().zip(projections)
+    {
+        match projection {
+            Some(OverlayProjection {
+                position,
+                onscreen: true,
+            }) => {
+                *visibility = Visibility::Inherited;
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+            }
+            _ => {
+                *visibility = Visibility::Hidden;
+                continue;
+            }
+        }
+        /* Crosshair roots have no parent, so their global transform is their local one */
+        *global_transform = GlobalTransform::from(*transform);
+        let mut pending: Vec<(Entity, GlobalTransform)> = children_query
+            .get(root)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child| (*child, *global_transform))
+                    .collect()
+            })
+            .unwrap_or_default();
+        while let Some((entity, parent_global_transform)) = pending.pop() {
+            let Ok((child_transform, mut child_global_transform)) =
+                descendant_query.get_mut(entity)
+            else {
+                continue;
+            };
+            *child_global_transform = parent_global_transform.mul_transform(*child_transform);
+            if let Ok(children) = children_query.get(entity) {
+                pending.extend(
+                    children
+                        .iter()
+                        .map(|child| (*child, *child_global_transform)),
+                );
+            }
+        }
+    }
+}
+
+/// Spawns a crosshair of the given type, returning its root entity along with the `CrosshairId` tagged on it.
+/// `CrosshairPlugin` puts it on the overlay layer; insert `RenderLayers` on the root right away to pick others.
 pub fn spawn_crosshair(
     commands: &mut Commands,
+    crosshair_ids: &mut ResMut<CrosshairIds>,
     crosshair_type: CrosshairType,
     meshes: &mut ResMut<Assets<Mesh>>,
     color_materials: &mut ResMut<Assets<ColorMaterial>>,
-    render_layers: RenderLayers,
-) -> Entity {
-    match crosshair_type {
+) -> (Entity, CrosshairId) {
+    let crosshair_id = crosshair_ids.next();
+    let entity = match crosshair_type {
         CrosshairType::SmallSquareCorners => {
             let short_horizontal = Mesh2dHandle(meshes.add(Rectangle::new(10.0, 0.25)));
             let short_vertical = Mesh2dHandle(meshes.add(Rectangle::new(0.25, 10.0)));
@@ -39,142 +202,118 @@ pub fn spawn_crosshair(
 
             commands
                 .spawn((
-                    CrosshairType::SmallSquareCorners,
+                    crosshair_type,
+                    crosshair_id,
                     Transform::default(),
                     GlobalTransform::default(),
-                    // Visibility::Hidden,
-                    // InheritedVisibility::HIDDEN,
+                    VisibilityBundle::default(),
                     IgnoreFloatingOrigin,
                 ))
                 .with_children(|parent| {
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_horizontal.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 25.0,
-                                    y: 30.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_horizontal.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 25.0,
+                                y: 30.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_horizontal.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -25.0,
-                                    y: -30.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_horizontal.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -25.0,
+                                y: -30.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_horizontal.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -25.0,
-                                    y: 30.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_horizontal.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -25.0,
+                                y: 30.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_horizontal.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 25.0,
-                                    y: -30.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_horizontal.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 25.0,
+                                y: -30.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_vertical.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 30.0,
-                                    y: 25.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_vertical.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 30.0,
+                                y: 25.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_vertical.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -30.0,
-                                    y: -25.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_vertical.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -30.0,
+                                y: -25.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_vertical.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -30.0,
-                                    y: 25.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_vertical.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -30.0,
+                                y: 25.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: short_vertical.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 30.0,
-                                    y: -25.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: short_vertical.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 30.0,
+                                y: -25.0,
+                                z: 0.0,
                             },
-                            material: crosshair_color.clone(),
                             ..default()
                         },
-                    ));
+                        material: crosshair_color.clone(),
+                        ..default()
+                    });
                 })
                 .id()
         }
@@ -192,78 +331,69 @@ pub fn spawn_crosshair(
 
             commands
                 .spawn((
+                    crosshair_type,
+                    crosshair_id,
                     Transform::default(),
                     GlobalTransform::default(),
+                    VisibilityBundle::default(),
                     IgnoreFloatingOrigin,
                 ))
                 .with_children(|parent| {
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: camera_reticle_color.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 10.0,
-                                    y: 10.0,
-                                    z: 0.0,
-                                },
-                                ..default()
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: camera_reticle_color.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 10.0,
+                                y: 10.0,
+                                z: 0.0,
                             },
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: camera_reticle_color.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -10.0,
-                                    y: 10.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z(PI / 2.0),
-                                ..default()
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: camera_reticle_color.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -10.0,
+                                y: 10.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z(PI / 2.0),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: camera_reticle_color.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -10.0,
-                                    y: -10.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z(PI),
-                                ..default()
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: camera_reticle_color.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -10.0,
+                                y: -10.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z(PI),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: camera_reticle_color.clone(),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 10.0,
-                                    y: -10.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z(-PI / 2.0),
-                                ..default()
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: camera_reticle_color.clone(),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 10.0,
+                                y: -10.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z(-PI / 2.0),
                             ..default()
                         },
-                    ));
+                        ..default()
+                    });
                 })
                 .id()
         }
@@ -280,7 +410,8 @@ pub fn spawn_crosshair(
 
             commands
                 .spawn((
-                    render_layers,
+                    crosshair_type,
+                    crosshair_id,
                     IgnoreFloatingOrigin,
                     // CursorNearestReticle,
                     Transform::default(),
@@ -289,76 +420,65 @@ pub fn spawn_crosshair(
                     InheritedVisibility::HIDDEN,
                 ))
                 .with_children(|parent| {
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: color_materials.add(camera_reticle_color),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 0.0,
-                                    y: 10.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z(PI / 4.0),
-                                ..default()
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: color_materials.add(camera_reticle_color),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 0.0,
+                                y: 10.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z(PI / 4.0),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: color_materials.add(camera_reticle_color),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: -10.0,
-                                    y: 0.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z((PI / 4.0) + (PI / 2.0)),
-                                ..default()
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: color_materials.add(camera_reticle_color),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: -10.0,
+                                y: 0.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z((PI / 4.0) + (PI / 2.0)),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: color_materials.add(camera_reticle_color),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 0.0,
-                                    y: -10.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z((PI / 4.0) + PI),
-                                ..default()
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: color_materials.add(camera_reticle_color),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 0.0,
+                                y: -10.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z((PI / 4.0) + PI),
                             ..default()
                         },
-                    ));
-                    parent.spawn((
-                        render_layers,
-                        MaterialMesh2dBundle {
-                            mesh: small_triangle.clone(),
-                            material: color_materials.add(camera_reticle_color),
-                            transform: Transform {
-                                translation: Vec3 {
-                                    x: 10.0,
-                                    y: 0.0,
-                                    z: 0.0,
-                                },
-                                rotation: Quat::from_rotation_z(-(PI / 4.0)),
-                                ..default()
+                        ..default()
+                    });
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: small_triangle.clone(),
+                        material: color_materials.add(camera_reticle_color),
+                        transform: Transform {
+                            translation: Vec3 {
+                                x: 10.0,
+                                y: 0.0,
+                                z: 0.0,
                             },
+                            rotation: Quat::from_rotation_z(-(PI / 4.0)),
                             ..default()
                         },
-                    ));
+                        ..default()
+                    });
                 })
                 .id()
         }
-    }
+    };
+    (entity, crosshair_id)
 }
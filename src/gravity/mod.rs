@@ -0,0 +1,294 @@
+use bevy::{math::DVec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+use big_space::{reference_frame::RootReferenceFrame, GridCell};
+
+use crate::time::DeterministicSimulation;
+
+/// A point mass that pulls on every `Attracted` rigid body.
+/// `mu` is the standard gravitational parameter (G * M) in m³/s².
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GravityAttractor {
+    pub mu: f64,
+}
+
+impl GravityAttractor {
+    /// Sphere-of-influence radius of this body orbiting `parent` at `semi_major_axis` meters.
+    pub fn sphere_of_influence(&self, semi_major_axis: f64, parent: &GravityAttractor) -> f64 {
+        sphere_of_influence_radius(semi_major_axis, self.mu, parent.mu)
+    }
+}
+
+/// Laplace sphere-of-influence radius `a * (m_body / m_parent)^(2/5)`.
+/// Masses and gravitational parameters give the same ratio, so either can be passed.
+pub fn sphere_of_influence_radius(semi_major_axis: f64, body_mass: f64, parent_mass: f64) -> f64 {
+    semi_major_axis * (body_mass / parent_mass).powf(0.4)
+}
+
+/// Radius in meters of the region around this body where its gravity dominates its parent's.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SphereOfInfluence {
+    pub radius_m: f64,
+}
+
+/// Draws each `SphereOfInfluence` as a faint sphere outline.
+pub struct SphereOfInfluenceGizmoPlugin;
+impl Plugin for SphereOfInfluenceGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, update_sphere_of_influence_gizmos);
+    }
+}
+
+pub fn update_sphere_of_influence_gizmos(
+    soi_query: Query<(&SphereOfInfluence, &GlobalTransform)>,
+    mut default_gizmos: Gizmos,
+) {
+    for (each_soi, each_transform) in soi_query.iter() {
+        default_gizmos.sphere(
+            each_transform.translation(),
+            Quat::IDENTITY,
+            each_soi.radius_m as f32,
+            Color::rgba(0.5, 0.8, 1.0, 0.15),
+        );
+    }
+}
+
+/// Returns the entity with the smallest sphere of influence containing `position`, if any.
+pub fn containing_sphere_of_influence<'a>(
+    position: DVec3,
+    bodies: impl Iterator<Item = (Entity, DVec3, &'a SphereOfInfluence)>,
+) -> Option<Entity> {
+    bodies
+        .filter(|(_, body_position, soi)| body_position.distance(position) < soi.radius_m)
+        .min_by(|(_, _, a), (_, _, b)| a.radius_m.total_cmp(&b.radius_m))
+        .map(|(entity, _, _)| entity)
+}
+
+/// Dynamic rigid bodies with this marker (and an `ExternalForce`) are pulled toward every `GravityAttractor`.
+#[derive(Component)]
+pub struct Attracted;
+
+pub struct GravityPlugin;
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            apply_gravity
+                .before(PhysicsSet::SyncBackend)
+                .run_if(not(resource_exists::<DeterministicSimulation>)),
+        )
+        .add_systems(
+            FixedUpdate,
+            apply_gravity
+                .before(PhysicsSet::SyncBackend)
+                .run_if(resource_exists::<DeterministicSimulation>),
+        );
+    }
+}
+
+/// Gravitational acceleration at `position` due to a point mass with parameter `mu` at `attractor_position`.
+pub fn gravitational_acceleration(position: DVec3, attractor_position: DVec3, mu: f64) -> DVec3 {
+    let offset = attractor_position - position;
+    let distance_squared = offset.length_squared();
+    if distance_squared == 0.0 {
+        return DVec3::ZERO;
+    }
+    offset * (mu / (distance_squared * distance_squared.sqrt()))
+}
+
+/// Sums the pull of every attractor on every attracted body and writes it to the body's `ExternalForce`.
+/// Positions are combined from `GridCell` and `Transform` in f64 so the r² term holds up at planetary distances.
+#[allow(clippy::type_complexity)]
+pub fn apply_gravity(
+    attractor_query: Query<(Entity, &GravityAttractor, &GridCell<i64>, &Transform)>,
+    mut attracted_query: Query<
+        (
+            Entity,
+            &RigidBody,
+            &ReadMassProperties,
+            &GridCell<i64>,
+            &Transform,
+            &mut ExternalForce,
+        ),
+        With<Attracted>,
+    >,
+    space: Res<RootReferenceFrame<i64>>,
+) {
+    for (each_entity, each_rigid_body, each_mass, each_cell, each_transform, mut each_force) in
+        attracted_query.iter_mut()
+    {
+        if *each_rigid_body != RigidBody::Dynamic {
+            continue;
+        }
+        let position = space.grid_position_double(each_cell, each_transform);
+        let mut acceleration = DVec3::ZERO;
+        for (attractor_entity, attractor, attractor_cell, attractor_transform) in
+            attractor_query.iter()
+        {
+            if attractor_entity == each_entity {
+                continue;
+            }
+            let attractor_position =
+                space.grid_position_double(attractor_cell, attractor_transform);
+            acceleration += gravitational_acceleration(position, attractor_position, attractor.mu);
+        }
+        each_force.force = (acceleration * each_mass.get().mass as f64).as_vec3();
+    }
+}
+
+/// Opts a `GravityAttractor` into the n-body simulation. Attractors without this component still pull on the
+/// simulated bodies but stay where they are. Don't combine it with `KeplerOrbit`, which also writes the position.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct NBodyVelocity(pub DVec3);
+
+#[derive(Resource, Clone)]
+pub struct NBodySettings {
+    /// The simulation is O(n²); a warning is logged when more bodies than this take part.
+    pub max_bodies: usize,
+}
+
+impl Default for NBodySettings {
+    fn default() -> Self {
+        Self { max_bodies: 64 }
+    }
+}
+
+/// Integrates every `GravityAttractor` against every other one with a velocity Verlet step in `FixedUpdate`,
+/// independent of Rapier's own timestep.
+pub struct NBodyPlugin;
+impl Plugin for NBodyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NBodySettings>()
+            .add_systems(FixedUpdate, step_n_body);
+    }
+}
+
+/// State of one body in the n-body simulation.
+#[derive(Clone, Copy, Debug)]
+pub struct NBody {
+    pub position: DVec3,
+    pub velocity: DVec3,
+    pub mu: f64,
+    /// Fixed bodies attract the others but are not moved.
+    pub fixed: bool,
+}
+
+fn n_body_accelerations(bodies: &[NBody]) -> Vec<DVec3> {
+    bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| {
+            bodies
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, other)| {
+                    gravitational_acceleration(body.position, other.position, other.mu)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Advances the bodies by `dt` seconds using velocity Verlet (kick-drift-kick), which is symplectic and so keeps
+/// the total energy bounded over long runs.
+pub fn n_body_step(bodies: &mut [NBody], dt: f64) {
+    let accelerations = n_body_accelerations(bodies);
+    for (body, acceleration) in bodies.iter_mut().zip(accelerations) {
+        if !body.fixed {
+            body.velocity += acceleration * (dt / 2.0);
+            body.position += body.velocity * dt;
+        }
+    }
+    let accelerations = n_body_accelerations(bodies);
+    for (body, acceleration) in bodies.iter_mut().zip(accelerations) {
+        if !body.fixed {
+            body.velocity += acceleration * (dt / 2.0);
+        }
+    }
+}
+
+/// Total energy of the system multiplied by G, so it can be computed from gravitational parameters alone.
+pub fn n_body_energy(bodies: &[NBody]) -> f64 {
+    let mut energy = 0.0;
+    for (i, body) in bodies.iter().enumerate() {
+        energy += 0.5 * body.mu * body.velocity.length_squared();
+        for other in bodies.iter().skip(i + 1) {
+            energy -= body.mu * other.mu / body.position.distance(other.position);
+        }
+    }
+    energy
+}
+
+pub fn step_n_body(
+    mut body_query: Query<(
+        &GravityAttractor,
+        Option<&mut NBodyVelocity>,
+        &mut GridCell<i64>,
+        &mut Transform,
+    )>,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<NBodySettings>,
+    time: Res<Time>,
+    mut warned: Local<bool>,
+) {
+    let mut bodies: Vec<NBody> = body_query
+        .iter()
+        .map(|(attractor, velocity, cell, transform)| NBody {
+            position: space.grid_position_double(&cell, &transform),
+            velocity: velocity.as_ref().map_or(DVec3::ZERO, |v| v.0),
+            mu: attractor.mu,
+            fixed: velocity.is_none(),
+        })
+        .collect();
+    /* Once each time the count goes over, rather than every tick */
+    let over_max = bodies.len() > settings.max_bodies;
+    if over_max && !*warned {
+        warn!(
+            "n-body simulation has {} bodies, more than the configured maximum of {}",
+            bodies.len(),
+            settings.max_bodies
+        );
+    }
+    *warned = over_max;
+
+    n_body_step(&mut bodies, time.delta_seconds_f64());
+
+    for ((_, velocity, mut cell, mut transform), body) in body_query.iter_mut().zip(bodies) {
+        if let Some(mut velocity) = velocity {
+            velocity.0 = body.velocity;
+            let (new_cell, translation) = space.translation_to_grid(body.position);
+            *cell = new_cell;
+            transform.translation = translation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_body_energy_is_conserved() {
+        let mut bodies = [
+            NBody {
+                position: DVec3::ZERO,
+                velocity: DVec3::ZERO,
+                mu: 1.0,
+                fixed: false,
+            },
+            NBody {
+                position: DVec3::X,
+                velocity: DVec3::Y * 1.2,
+                mu: 1e-6,
+                fixed: false,
+            },
+        ];
+        let initial_energy = n_body_energy(&bodies);
+        /* About thirteen laps of an e = 0.44 orbit */
+        for _ in 0..20_000 {
+            n_body_step(&mut bodies, 0.01);
+        }
+        let drift = (n_body_energy(&bodies) - initial_energy) / initial_energy;
+        assert!(drift.abs() < 1e-3, "relative energy drift {drift}");
+    }
+}
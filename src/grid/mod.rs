@@ -0,0 +1,154 @@
+use bevy::{
+    ecs::{system::EntityCommands, world::EntityWorldMut},
+    math::DVec3,
+    prelude::*,
+};
+use big_space::{reference_frame::RootReferenceFrame, GridCell};
+
+/// Distance in meters between two grid positions, computed in f64 so it stays exact at interplanetary distances.
+pub fn grid_distance(
+    space: &RootReferenceFrame<i64>,
+    a_cell: &GridCell<i64>,
+    a_transform: &Transform,
+    b_cell: &GridCell<i64>,
+    b_transform: &Transform,
+) -> f64 {
+    grid_offset(space, a_cell, a_transform, b_cell, b_transform).length()
+}
+
+/// Unit vector pointing from `a` toward `b`, or zero when they coincide.
+pub fn grid_direction(
+    space: &RootReferenceFrame<i64>,
+    a_cell: &GridCell<i64>,
+    a_transform: &Transform,
+    b_cell: &GridCell<i64>,
+    b_transform: &Transform,
+) -> DVec3 {
+    grid_offset(space, a_cell, a_transform, b_cell, b_transform).normalize_or_zero()
+}
+
+/// Vector in meters from `a` to `b`.
+pub fn grid_offset(
+    space: &RootReferenceFrame<i64>,
+    a_cell: &GridCell<i64>,
+    a_transform: &Transform,
+    b_cell: &GridCell<i64>,
+    b_transform: &Transform,
+) -> DVec3 {
+    space.grid_position_double(b_cell, b_transform)
+        - space.grid_position_double(a_cell, a_transform)
+}
+
+/// Spawns `bundle` at the absolute grid `position`, inserting the matching `GridCell` and moving the bundle's
+/// `Transform` to the offset within that cell. Rotation and scale from the bundle are kept.
+pub fn spawn_at<'a, B: Bundle>(
+    commands: &'a mut Commands,
+    space: &RootReferenceFrame<i64>,
+    position: DVec3,
+    bundle: B,
+) -> EntityCommands<'a> {
+    let (cell, translation): (GridCell<i64>, _) = space.translation_to_grid(position);
+    let mut entity_commands = commands.spawn((bundle, cell));
+    entity_commands.add(
+        move |mut entity: EntityWorldMut| match entity.get_mut::<Transform>() {
+            Some(mut transform) => transform.translation = translation,
+            None => {
+                entity.insert(Transform::from_translation(translation));
+            }
+        },
+    );
+    entity_commands
+}
+
+/// Rotation that points an entity's forward (-Z) along `direction`, keeping its up as close to `up` as possible.
+/// The direction is normalized in f64 first so distant targets don't lose precision. Returns `fallback` when
+/// `direction` is zero.
+pub fn look_toward(direction: DVec3, up: Vec3, fallback: Quat) -> Quat {
+    match direction.try_normalize() {
+        Some(direction) => {
+            Transform::IDENTITY
+                .looking_to(direction.as_vec3(), up)
+                .rotation
+        }
+        None => fallback,
+    }
+}
+
+/// Rotation for an entity at `from` to face `target`, computed from their f64 relative direction.
+/// Keeps `from_transform`'s rotation when the two positions coincide.
+pub fn look_at_grid(
+    space: &RootReferenceFrame<i64>,
+    from_cell: &GridCell<i64>,
+    from_transform: &Transform,
+    target_cell: &GridCell<i64>,
+    target_transform: &Transform,
+    up: Vec3,
+) -> Quat {
+    look_toward(
+        grid_offset(
+            space,
+            from_cell,
+            from_transform,
+            target_cell,
+            target_transform,
+        ),
+        up,
+        from_transform.rotation,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use big_space::FloatingOriginPlugin;
+
+    use super::*;
+
+    /// A root frame with 1 km cells.
+    fn space() -> RootReferenceFrame<i64> {
+        let mut app = App::new();
+        app.add_plugins(FloatingOriginPlugin::<i64>::new(1_000.0, 100.0));
+        app.world.resource::<RootReferenceFrame<i64>>().clone()
+    }
+
+    #[test]
+    fn grid_distance_within_one_cell() {
+        let space = space();
+        let distance = grid_distance(
+            &space,
+            &GridCell::new(0, 0, 0),
+            &Transform::from_xyz(3.0, 4.0, 0.0),
+            &GridCell::new(0, 0, 0),
+            &Transform::IDENTITY,
+        );
+        assert_eq!(distance, 5.0);
+    }
+
+    #[test]
+    fn grid_distance_across_cells() {
+        let space = space();
+        /* 400 m into cell 0 and 400 m back from the center of cell 1 are 200 m apart */
+        let (a_cell, a_transform) = (GridCell::new(0, 0, 0), Transform::from_xyz(400.0, 0.0, 0.0));
+        let (b_cell, b_transform) = (
+            GridCell::new(1, 0, 0),
+            Transform::from_xyz(-400.0, 0.0, 0.0),
+        );
+        let distance = grid_distance(&space, &a_cell, &a_transform, &b_cell, &b_transform);
+        assert!((distance - 200.0).abs() < 1.0e-9);
+        let direction = grid_direction(&space, &a_cell, &a_transform, &b_cell, &b_transform);
+        assert!(direction.abs_diff_eq(DVec3::X, 1.0e-12));
+    }
+
+    #[test]
+    fn grid_distance_far_from_origin() {
+        let space = space();
+        /* Three cells apart, a billion cells out, where f32 positions would be off by kilometers */
+        let distance = grid_distance(
+            &space,
+            &GridCell::new(1_000_000_000, 0, -1_000_000_000),
+            &Transform::IDENTITY,
+            &GridCell::new(1_000_000_003, 0, -1_000_000_000),
+            &Transform::from_xyz(0.0, 4_000.0, 0.0),
+        );
+        assert!((distance - 5_000.0).abs() < 1.0e-6);
+    }
+}
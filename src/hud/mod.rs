@@ -0,0 +1,244 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use big_space::IgnoreFloatingOrigin;
+
+use crate::{
+    input::{Action, KeyBindings},
+    targeting::{TargetingMode, TargetingModeChanged},
+    trajectory::ClosestApproach,
+};
+
+/// Text showing the measured frame rate and frame time, independent of any `bevy_framepace` limit.
+#[derive(Component)]
+pub struct DiagnosticsOverlay;
+
+#[derive(Resource, Clone)]
+pub struct DiagnosticsOverlaySettings {
+    pub visible: bool,
+    pub font_size: f32,
+    pub color: Color,
+}
+
+impl Default for DiagnosticsOverlaySettings {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            font_size: 16.0,
+            color: Color::YELLOW,
+        }
+    }
+}
+
+/// Adds a bottom right FPS/frame time readout toggled by `Action::ToggleDiagnostics`.
+/// Registers `FrameTimeDiagnosticsPlugin` unless the app already has it.
+pub struct DiagnosticsOverlayPlugin;
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<DiagnosticsOverlaySettings>()
+            .init_resource::<KeyBindings>()
+            .add_systems(Startup, spawn_diagnostics_overlay)
+            .add_systems(
+                Update,
+                (toggle_diagnostics_overlay, update_diagnostics_overlay).chain(),
+            );
+    }
+}
+
+pub fn spawn_diagnostics_overlay(
+    mut commands: Commands,
+    settings: Res<DiagnosticsOverlaySettings>,
+) {
+    commands.spawn((
+        DiagnosticsOverlay,
+        IgnoreFloatingOrigin,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: settings.font_size,
+                color: settings.color,
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+    ));
+}
+
+pub fn toggle_diagnostics_overlay(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut settings: ResMut<DiagnosticsOverlaySettings>,
+) {
+    if key_bindings.just_pressed(&key, Action::ToggleDiagnostics) {
+        settings.visible = !settings.visible;
+    }
+}
+
+pub fn update_diagnostics_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    settings: Res<DiagnosticsOverlaySettings>,
+    mut overlay_query: Query<(&mut Text, &mut Visibility), With<DiagnosticsOverlay>>,
+) {
+    for (mut text, mut visibility) in overlay_query.iter_mut() {
+        *visibility = match settings.visible {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+        if !settings.visible {
+            continue;
+        }
+        let fps = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed());
+        let frame_time = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+            .and_then(|frame_time| frame_time.smoothed());
+        text.sections[0].value = match (fps, frame_time) {
+            (Some(fps), Some(frame_time)) => format!("{:.1} fps\n{:.2} ms", fps, frame_time),
+            _ => "-- fps\n-- ms".to_string(),
+        };
+    }
+}
+
+/// Text showing the current `TargetingMode`.
+#[derive(Component)]
+pub struct TargetingModeIndicator;
+
+#[derive(Resource, Clone)]
+pub struct TargetingModeIndicatorSettings {
+    pub font_size: f32,
+    pub color: Color,
+}
+
+impl Default for TargetingModeIndicatorSettings {
+    fn default() -> Self {
+        Self {
+            font_size: 16.0,
+            color: Color::YELLOW,
+        }
+    }
+}
+
+/// Adds a bottom left readout of the targeting mode, rewritten whenever `TargetingModeChanged` is sent.
+pub struct TargetingModeIndicatorPlugin;
+impl Plugin for TargetingModeIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TargetingModeIndicatorSettings>()
+            .init_resource::<TargetingMode>()
+            .add_event::<TargetingModeChanged>()
+            .add_systems(Startup, spawn_targeting_mode_indicator)
+            .add_systems(Update, update_targeting_mode_indicator);
+    }
+}
+
+pub fn targeting_mode_text(mode: TargetingMode) -> String {
+    format!("targeting: {:?}", mode)
+}
+
+pub fn spawn_targeting_mode_indicator(
+    mut commands: Commands,
+    settings: Res<TargetingModeIndicatorSettings>,
+    targeting_mode: Res<TargetingMode>,
+) {
+    commands.spawn((
+        TargetingModeIndicator,
+        IgnoreFloatingOrigin,
+        TextBundle::from_section(
+            targeting_mode_text(*targeting_mode),
+            TextStyle {
+                font_size: settings.font_size,
+                color: settings.color,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+    ));
+}
+
+pub fn update_targeting_mode_indicator(
+    mut mode_changed: EventReader<TargetingModeChanged>,
+    mut indicator_query: Query<&mut Text, With<TargetingModeIndicator>>,
+) {
+    let Some(event) = mode_changed.read().last() else {
+        return;
+    };
+    for mut text in indicator_query.iter_mut() {
+        text.sections[0].value = targeting_mode_text(event.mode);
+    }
+}
+
+/// Text showing the predicted miss distance to the current target and how far ahead it is.
+#[derive(Component)]
+pub struct ClosestApproachReadout;
+
+/// Adds a bottom left readout of `ClosestApproach`, above the targeting mode, hidden while there is none.
+/// Uses the `TargetingModeIndicatorSettings` text style.
+pub struct ClosestApproachReadoutPlugin;
+impl Plugin for ClosestApproachReadoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TargetingModeIndicatorSettings>()
+            .init_resource::<ClosestApproach>()
+            .add_systems(Startup, spawn_closest_approach_readout)
+            .add_systems(Update, update_closest_approach_readout);
+    }
+}
+
+pub fn spawn_closest_approach_readout(
+    mut commands: Commands,
+    settings: Res<TargetingModeIndicatorSettings>,
+) {
+    commands.spawn((
+        ClosestApproachReadout,
+        IgnoreFloatingOrigin,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: settings.font_size,
+                color: settings.color,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0 + settings.font_size * 1.5),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+    ));
+}
+
+pub fn update_closest_approach_readout(
+    closest: Res<ClosestApproach>,
+    mut readout_query: Query<(&mut Text, &mut Visibility), With<ClosestApproachReadout>>,
+) {
+    if !closest.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in readout_query.iter_mut() {
+        match closest.0 {
+            Some(approach) => {
+                *visibility = Visibility::Inherited;
+                text.sections[0].value = format!(
+                    "closest approach: {:.1} m in {:.1} s",
+                    approach.distance_m, approach.time_s
+                );
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{
+    app::AppExit,
+    input::InputSystem,
+    prelude::*,
+    utils::HashMap,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+use big_space::camera::CameraInput;
+use serde::{Deserialize, Serialize};
+
+/// Everything the experiments let the player do from the keyboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    SelectTarget,
+    NavModeCommand,
+    NavModeCursor,
+    NavModeNearest,
+    NavModeRayCast,
+    TimeScaleUp,
+    TimeScaleDown,
+    TimeScaleReset,
+    TogglePause,
+    CycleTimestepMode,
+    FocusTarget,
+    FlyToTarget,
+    FrameTarget,
+    OrbitCamera,
+    LevelHorizon,
+    CycleSkybox,
+    ToggleManeuverNode,
+    Screenshot,
+    ToggleDiagnostics,
+    DampVelocity,
+    ToggleReferenceAxes,
+    HoldPrograde,
+    HoldRetrograde,
+    ToggleBloom,
+    ResetScene,
+}
+
+/// Which key triggers each `Action`. Serializable so bindings can be stored in a settings file.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct KeyBindings {
+    bindings: HashMap<Action, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from_iter([
+                (Action::SelectTarget, KeyCode::Enter),
+                (Action::NavModeCommand, KeyCode::KeyT),
+                (Action::NavModeCursor, KeyCode::KeyC),
+                (Action::NavModeNearest, KeyCode::KeyN),
+                (Action::NavModeRayCast, KeyCode::KeyY),
+                (Action::TimeScaleUp, KeyCode::Period),
+                (Action::TimeScaleDown, KeyCode::Comma),
+                (Action::TimeScaleReset, KeyCode::Slash),
+                (Action::TogglePause, KeyCode::KeyP),
+                (Action::CycleTimestepMode, KeyCode::KeyI),
+                (Action::FocusTarget, KeyCode::KeyF),
+                (Action::FlyToTarget, KeyCode::KeyG),
+                (Action::FrameTarget, KeyCode::KeyZ),
+                (Action::OrbitCamera, KeyCode::KeyO),
+                (Action::LevelHorizon, KeyCode::KeyL),
+                (Action::CycleSkybox, KeyCode::KeyB),
+                (Action::ToggleManeuverNode, KeyCode::KeyM),
+                (Action::Screenshot, KeyCode::F12),
+                (Action::ToggleDiagnostics, KeyCode::F3),
+                (Action::DampVelocity, KeyCode::KeyX),
+                (Action::ToggleReferenceAxes, KeyCode::KeyH),
+                (Action::HoldPrograde, KeyCode::KeyV),
+                (Action::HoldRetrograde, KeyCode::KeyR),
+                (Action::ToggleBloom, KeyCode::F4),
+                (Action::ResetScene, KeyCode::Backspace),
+            ]),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.bindings.get(&action).copied()
+    }
+
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        self.bindings.insert(action, key);
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        self.key(action).is_some_and(|key| input.just_pressed(key))
+    }
+
+    pub fn pressed(&self, input: &ButtonInput<KeyCode>, action: Action) -> bool {
+        self.key(action).is_some_and(|key| input.pressed(key))
+    }
+}
+
+/// Whether the primary window's cursor is currently grabbed for flying the camera. Kept in step with the window by
+/// `CursorLockPlugin`; change it with `grab_cursor` and `release_cursor`.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CursorLock {
+    pub locked: bool,
+}
+
+pub struct CursorLockPlugin;
+impl Plugin for CursorLockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorLock>()
+            .add_systems(PostUpdate, sync_cursor_lock);
+    }
+}
+
+/// Locks and hides the cursor and hands mouse and keyboard to the camera controller.
+pub fn grab_cursor(window: &mut Window, cam: &mut CameraInput) {
+    window.cursor.grab_mode = CursorGrabMode::Locked;
+    window.cursor.visible = false;
+    cam.defaults_disabled = false;
+}
+
+/// Frees and shows the cursor and stops the camera controller from reading input.
+pub fn release_cursor(window: &mut Window, cam: &mut CameraInput) {
+    window.cursor.grab_mode = CursorGrabMode::None;
+    window.cursor.visible = true;
+    cam.defaults_disabled = true;
+}
+
+pub fn sync_cursor_lock(
+    mut cursor_lock: ResMut<CursorLock>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    cursor_lock.set_if_neq(CursorLock {
+        locked: window.cursor.grab_mode != CursorGrabMode::None,
+    });
+}
+
+/// `click_button` selects a target like `Action::SelectTarget` while the cursor is grabbed; the click that grabs the
+/// cursor selects nothing. `None` leaves selecting to the keyboard.
+#[derive(Resource, Clone, Debug)]
+pub struct SelectInputSettings {
+    pub click_button: Option<MouseButton>,
+}
+
+impl Default for SelectInputSettings {
+    fn default() -> Self {
+        Self {
+            click_button: Some(MouseButton::Left),
+        }
+    }
+}
+
+/// Sent when the player asks to select a target, by key or by click.
+#[derive(Event, Debug)]
+pub struct SelectTargetPressed;
+
+/// Sends `SelectTargetPressed` in `PreUpdate`. Clicks only count with `CursorLockPlugin` tracking the cursor.
+pub struct SelectInputPlugin;
+impl Plugin for SelectInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectInputSettings>()
+            .init_resource::<CursorLock>()
+            .init_resource::<KeyBindings>()
+            .add_event::<SelectTargetPressed>()
+            .add_systems(PreUpdate, send_select_target_pressed.after(InputSystem));
+    }
+}
+
+pub fn send_select_target_pressed(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    settings: Res<SelectInputSettings>,
+    cursor_lock: Res<CursorLock>,
+    mut select_pressed: EventWriter<SelectTargetPressed>,
+) {
+    /* CursorLock is only synced in PostUpdate, so on the click that grabs the cursor it still reads unlocked */
+    let clicked = cursor_lock.locked
+        && settings
+            .click_button
+            .is_some_and(|button| mouse.just_pressed(button));
+    if clicked || key_bindings.just_pressed(&key, Action::SelectTarget) {
+        select_pressed.send(SelectTargetPressed);
+    }
+}
+
+/// Seconds after the first Escape during which a second Escape quits.
+pub const QUIT_CONFIRM_SECONDS: f32 = 2.0;
+
+/// Armed by an Escape press; a second Escape while the timer is running sends `AppExit`. Paused while disarmed.
+#[derive(Resource, Debug)]
+pub struct QuitConfirm {
+    pub timer: Timer,
+}
+
+impl Default for QuitConfirm {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(QUIT_CONFIRM_SECONDS, TimerMode::Once);
+        timer.pause();
+        Self { timer }
+    }
+}
+
+impl QuitConfirm {
+    pub fn arm(&mut self) {
+        self.timer.reset();
+        self.timer.unpause();
+    }
+
+    pub fn disarm(&mut self) {
+        self.timer.pause();
+    }
+
+    pub fn is_armed(&self) -> bool {
+        !self.timer.paused()
+    }
+}
+
+/// Escape releases the cursor and arms `QuitConfirm`; Escape again before the timer runs out quits.
+pub struct QuitConfirmPlugin;
+impl Plugin for QuitConfirmPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuitConfirm>()
+            .add_systems(Update, escape_to_quit);
+    }
+}
+
+pub fn escape_to_quit(
+    key: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut quit_confirm: ResMut<QuitConfirm>,
+    mut cam: ResMut<CameraInput>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if quit_confirm.timer.tick(time.delta()).just_finished() {
+        debug!("quit cancelled");
+        quit_confirm.disarm();
+    }
+    if !key.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if quit_confirm.is_armed() {
+        exit.send(AppExit);
+        return;
+    }
+    if let Ok(mut window) = window_query.get_single_mut() {
+        release_cursor(&mut window, &mut cam);
+    }
+    info!(
+        "press Escape again within {:?} to quit",
+        quit_confirm.timer.duration()
+    );
+    quit_confirm.arm();
+}
+
+/// Seconds allowed between the keys of a command sequence before the partial entry is dropped.
+pub const COMMAND_SEQUENCE_TIMEOUT_SECONDS: f32 = 2.0;
+
+/// Multi-key commands: pressing the keys bound to a registered run of `Action`s, each within the timeout of the
+/// last, sends a `CommandEntered` carrying the registered command. Keys that don't continue any sequence are ignored
+/// while one is being entered.
+#[derive(Resource, Debug)]
+pub struct CommandSequence<C> {
+    sequences: Vec<(Vec<Action>, C)>,
+    entered: Vec<Action>,
+    pub timer: Timer,
+}
+
+impl<C> Default for CommandSequence<C> {
+    fn default() -> Self {
+        Self::new(Duration::from_secs_f32(COMMAND_SEQUENCE_TIMEOUT_SECONDS))
+    }
+}
+
+impl<C> CommandSequence<C> {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            sequences: Vec::new(),
+            entered: Vec::new(),
+            timer: Timer::new(timeout, TimerMode::Once),
+        }
+    }
+
+    pub fn register(&mut self, actions: impl IntoIterator<Item = Action>, command: C) {
+        self.sequences
+            .push((actions.into_iter().collect(), command));
+    }
+
+    pub fn with(mut self, actions: impl IntoIterator<Item = Action>, command: C) -> Self {
+        self.register(actions, command);
+        self
+    }
+
+    /// Actions entered so far toward a sequence.
+    pub fn entered(&self) -> &[Action] {
+        &self.entered
+    }
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct CommandEntered<C>(pub C);
+
+/// Reads `CommandSequence<C>` key sequences and sends `CommandEntered<C>` events.
+pub struct CommandSequencePlugin<C>(PhantomData<C>);
+
+impl<C> Default for CommandSequencePlugin<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Clone + Send + Sync + 'static> Plugin for CommandSequencePlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<CommandSequence<C>>()
+            .add_event::<CommandEntered<C>>()
+            .add_systems(Update, read_command_sequence::<C>);
+    }
+}
+
+pub fn read_command_sequence<C: Clone + Send + Sync + 'static>(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    time: Res<Time>,
+    mut command_sequence: ResMut<CommandSequence<C>>,
+    mut command_events: EventWriter<CommandEntered<C>>,
+) {
+    let command_sequence = &mut *command_sequence;
+    if command_sequence.timer.tick(time.delta()).just_finished()
+        && !command_sequence.entered.is_empty()
+    {
+        trace!(
+            "command entry timed out after {:?}",
+            command_sequence.entered
+        );
+        command_sequence.entered.clear();
+    }
+
+    let position = command_sequence.entered.len();
+    let Some(next) = command_sequence
+        .sequences
+        .iter()
+        .filter(|(actions, _)| actions.starts_with(&command_sequence.entered))
+        .filter_map(|(actions, _)| actions.get(position).copied())
+        .find(|action| key_bindings.just_pressed(&key, *action))
+    else {
+        return;
+    };
+    command_sequence.entered.push(next);
+    command_sequence.timer.reset();
+
+    let completed = command_sequence
+        .sequences
+        .iter()
+        .find(|(actions, _)| *actions == command_sequence.entered);
+    if let Some((actions, command)) = completed {
+        debug!("command entered: {:?}", actions);
+        command_events.send(CommandEntered(command.clone()));
+        command_sequence.entered.clear();
+    }
+}
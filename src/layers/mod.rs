@@ -0,0 +1,28 @@
+use bevy::{prelude::*, render::view::RenderLayers};
+
+/// Layer of the 3D world and the camera looking at it.
+pub const BACKGROUND: RenderLayers = RenderLayers::layer(1);
+/// Layer of instruments drawn by a camera of their own on top of the world, like the navball.
+pub const FOREGROUND: RenderLayers = RenderLayers::layer(2);
+/// Layer of the 2D overlay camera, where crosshairs, reticles and labels go.
+pub const OVERLAY: RenderLayers = RenderLayers::layer(3);
+
+/// Which layers the crate's plugins put their entities on. Each camera must render the matching layer, so change a
+/// layer here and on its camera together. Plugins that draw also take a per settings override, and a crosshair spawned
+/// with `RenderLayers` already on its root keeps them.
+#[derive(Resource, Clone, Debug)]
+pub struct LayerConfig {
+    pub background: RenderLayers,
+    pub foreground: RenderLayers,
+    pub overlay: RenderLayers,
+}
+
+impl Default for LayerConfig {
+    fn default() -> Self {
+        Self {
+            background: BACKGROUND,
+            foreground: FOREGROUND,
+            overlay: OVERLAY,
+        }
+    }
+}
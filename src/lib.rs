@@ -1,2 +1,29 @@
+pub mod camera;
+pub mod capture;
+pub mod colliders;
 pub mod crosshair;
+pub mod gravity;
+pub mod grid;
+pub mod hud;
+pub mod input;
+pub mod layers;
+pub mod lighting;
+pub mod loading;
+pub mod maneuver;
 pub mod mipmap;
+pub mod navball;
+pub mod orbits;
+pub mod planets;
+pub mod plugins;
+/// Re-exports the crate's plugins and the components, resources and events that drive them, for
+/// `use bevy_space_program::prelude::*;`.
+pub mod prelude;
+pub mod projectile;
+pub mod reset;
+pub mod reticle;
+pub mod scene_io;
+pub mod skybox;
+pub mod targeting;
+pub mod time;
+pub mod trajectory;
+pub mod util;
@@ -0,0 +1,392 @@
+use std::f64::consts::PI;
+
+use bevy::{
+    core_pipeline::bloom::BloomSettings,
+    prelude::*,
+    render::{camera::Exposure, view::RenderLayers},
+};
+use big_space::{FloatingOrigin, IgnoreFloatingOrigin};
+
+use crate::{
+    input::{Action, KeyBindings},
+    layers::LayerConfig,
+    reticle::projected_radius,
+    util::{is_onscreen, overlay_position},
+};
+
+/// Radiant luminosity of the Sun in watts.
+pub const SUN_LUMINOSITY_W: f64 = 3.828e26;
+/// Bolometric flux in W/m² of a star of apparent magnitude zero.
+pub const ZERO_MAGNITUDE_FLUX: f64 = 2.518e-8;
+/// Lumens per watt for a sunlike spectrum, turning a star's radiant luminosity into the luminous power Bevy's lights
+/// are specified in.
+pub const STELLAR_LUMINOUS_EFFICACY: f64 = 93.0;
+
+/// Ambient light, exposure and bloom for the floating origin camera. The defaults are tuned for stars lit with
+/// physical intensities: sunlight exposure so a star's point light doesn't blow out every surface, a little ambient
+/// light so night sides aren't pitch black, and bloom so stars glow.
+/// Bloom needs HDR, so HDR is on exactly when `bloom` is set, for the floating origin camera and the 2D overlay
+/// cameras drawn on top of it alike.
+#[derive(Resource, Clone)]
+pub struct LightingSettings {
+    pub ambient: AmbientLight,
+    pub exposure: Exposure,
+    pub bloom: Option<BloomSettings>,
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        Self {
+            ambient: AmbientLight {
+                color: Color::WHITE,
+                brightness: 100.0,
+            },
+            exposure: Exposure::SUNLIGHT,
+            bloom: Some(BloomSettings::default()),
+        }
+    }
+}
+
+/// Applies `LightingSettings` whenever they change and to every camera when it is spawned.
+/// `Action::ToggleBloom` switches bloom, and with it HDR, off and back on.
+pub struct LightingPlugin;
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LightingSettings>()
+            .init_resource::<KeyBindings>()
+            .add_systems(Update, toggle_bloom)
+            .add_systems(PostUpdate, apply_lighting_settings);
+    }
+}
+
+/// Inserts `settings` and adds `LightingPlugin` if it isn't already.
+pub fn configure_realistic_lighting(app: &mut App, settings: LightingSettings) -> &mut App {
+    if !app.is_plugin_added::<LightingPlugin>() {
+        app.add_plugins(LightingPlugin);
+    }
+    app.insert_resource(settings)
+}
+
+pub fn toggle_bloom(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut settings: ResMut<LightingSettings>,
+    mut bloom_while_disabled: Local<Option<BloomSettings>>,
+) {
+    if !key_bindings.just_pressed(&key, Action::ToggleBloom) {
+        return;
+    }
+    settings.bloom = match settings.bloom.take() {
+        Some(bloom) => {
+            *bloom_while_disabled = Some(bloom);
+            None
+        }
+        None => Some(bloom_while_disabled.take().unwrap_or_default()),
+    };
+    debug!("bloom: {:?}", settings.bloom.is_some());
+}
+
+pub fn apply_lighting_settings(
+    mut commands: Commands,
+    settings: Res<LightingSettings>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut camera_query: Query<
+        (Entity, &mut Camera, &mut Exposure, Ref<Camera3d>),
+        With<FloatingOrigin>,
+    >,
+    mut overlay_query: Query<(&mut Camera, Ref<Camera2d>), Without<FloatingOrigin>>,
+) {
+    if settings.is_changed() {
+        *ambient_light = settings.ambient.clone();
+    }
+    let hdr = settings.bloom.is_some();
+    for (camera_entity, mut camera, mut exposure, camera_3d) in camera_query.iter_mut() {
+        if !settings.is_changed() && !camera_3d.is_added() {
+            continue;
+        }
+        *exposure = settings.exposure;
+        camera.hdr = hdr;
+        match &settings.bloom {
+            Some(bloom) => {
+                commands.entity(camera_entity).insert(bloom.clone());
+            }
+            None => {
+                commands.entity(camera_entity).remove::<BloomSettings>();
+            }
+        }
+    }
+    /* Cameras sharing a window only composite onto each other when their HDR modes match */
+    for (mut camera, camera_2d) in overlay_query.iter_mut() {
+        if settings.is_changed() || camera_2d.is_added() {
+            camera.hdr = hdr;
+        }
+    }
+}
+
+/// Photometric output of a star: `intensity` is the luminous power of its point light in lumens and `luminance` the
+/// brightness of its surface in nits, for use as the emissive color of its mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StarLight {
+    pub intensity: f32,
+    pub luminance: f32,
+}
+
+/// Light given off by a star radiating `luminosity_w` watts from a sphere of `radius_m`.
+pub fn star_light_from_luminosity(luminosity_w: f64, radius_m: f32) -> StarLight {
+    let luminous_power = luminosity_w * STELLAR_LUMINOUS_EFFICACY;
+    let surface_area = 4.0 * PI * (radius_m as f64).powi(2);
+    StarLight {
+        intensity: luminous_power as f32,
+        luminance: (luminous_power / surface_area / PI) as f32,
+    }
+}
+
+/// A star's mesh, whose emissive color `scale_star_brightness` keeps in line with its luminosity and distance.
+/// The mesh's material should be its own, since its emissive color is rewritten as the camera moves. Give the star's
+/// `PointLight` the intensity from `star_light_from_luminosity`.
+#[derive(Component, Clone, Debug)]
+pub struct Star {
+    pub luminosity_w: f64,
+    pub radius_m: f32,
+}
+
+/// A star that would cover less than `min_pixels` of radius on screen is scaled up to that size, and its emissive
+/// luminance divided by the added area so the point still gives off the same total light, but never below
+/// `min_luminance` nits, so very distant stars stay faintly visible instead of vanishing.
+#[derive(Resource, Clone)]
+pub struct StarLightSettings {
+    pub min_pixels: f32,
+    pub min_luminance: f32,
+}
+
+impl Default for StarLightSettings {
+    fn default() -> Self {
+        Self {
+            min_pixels: 1.5,
+            min_luminance: 1.0e5,
+        }
+    }
+}
+
+pub struct StarLightPlugin;
+impl Plugin for StarLightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StarLightSettings>()
+            .add_systems(Update, scale_star_brightness);
+    }
+}
+
+pub fn scale_star_brightness(
+    mut star_query: Query<(
+        &Star,
+        &GlobalTransform,
+        &mut Transform,
+        &Handle<StandardMaterial>,
+    )>,
+    camera_query: Query<(&Camera, &GlobalTransform, &Projection), With<FloatingOrigin>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<StarLightSettings>,
+) {
+    let Ok((camera, camera_global_transform, Projection::Perspective(perspective))) =
+        camera_query.get_single()
+    else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+    for (star, global_transform, mut transform, material) in star_query.iter_mut() {
+        let distance_m = global_transform
+            .translation()
+            .distance(camera_global_transform.translation());
+        let radius_px =
+            projected_radius(star.radius_m, distance_m, perspective.fov, viewport_size.y);
+        let scale = (settings.min_pixels / radius_px).max(1.0);
+        if transform.scale.x != scale {
+            transform.scale = Vec3::splat(scale);
+        }
+        let luminance = star_light_from_luminosity(star.luminosity_w, star.radius_m).luminance;
+        let dimmed = (luminance / (scale * scale)).max(settings.min_luminance.min(luminance));
+        let emissive = Color::rgb_linear(dimmed, dimmed, dimmed);
+        /* Only touch the material when it changes, since every change re-uploads it */
+        if materials
+            .get(material)
+            .is_some_and(|material| material.emissive != emissive)
+        {
+            if let Some(material) = materials.get_mut(material) {
+                material.emissive = emissive;
+            }
+        }
+    }
+}
+
+/// Apparent bolometric magnitude of a star radiating `luminosity_w` watts seen from `distance_m` away.
+pub fn apparent_magnitude(luminosity_w: f64, distance_m: f64) -> f64 {
+    let flux = luminosity_w / (4.0 * PI * distance_m * distance_m);
+    -2.5 * (flux / ZERO_MAGNITUDE_FLUX).log10()
+}
+
+/// A star's mesh that is swapped for a point sprite on the overlay once the camera is further than
+/// `DistantStarSettings::switch_distance_m` away, since at that range the mesh is a speck at best.
+#[derive(Component, Clone, Debug)]
+pub struct DistantStar {
+    pub luminosity_w: f64,
+}
+
+/// The overlay sprite standing in for `star`, spawned by `update_distant_stars`.
+#[derive(Component, Debug)]
+pub struct DistantStarSprite {
+    pub star: Entity,
+}
+
+/// `render_layers` overrides `LayerConfig::overlay` when set. Sprites are `max_size_px` wide and fully opaque for
+/// stars at `brightest_magnitude` or brighter, shrinking and fading down to `min_size_px` and `min_alpha` at
+/// `faintest_magnitude`; stars fainter than that aren't drawn at all.
+#[derive(Resource, Clone)]
+pub struct DistantStarSettings {
+    pub render_layers: Option<RenderLayers>,
+    pub switch_distance_m: f32,
+    pub brightest_magnitude: f64,
+    pub faintest_magnitude: f64,
+    pub min_size_px: f32,
+    pub max_size_px: f32,
+    pub min_alpha: f32,
+    pub color: Color,
+}
+
+impl Default for DistantStarSettings {
+    fn default() -> Self {
+        Self {
+            render_layers: None,
+            switch_distance_m: 1.0e13,
+            brightest_magnitude: -2.0,
+            faintest_magnitude: 12.0,
+            min_size_px: 1.0,
+            max_size_px: 6.0,
+            min_alpha: 0.2,
+            color: Color::WHITE,
+        }
+    }
+}
+
+impl DistantStarSettings {
+    pub fn render_layers(&self, layer_config: &LayerConfig) -> RenderLayers {
+        self.render_layers.unwrap_or(layer_config.overlay)
+    }
+
+    /// Sprite size in overlay pixels and opacity for a star of apparent `magnitude`, `None` if it is too faint.
+    pub fn sprite_for(&self, magnitude: f64) -> Option<(f32, f32)> {
+        if magnitude > self.faintest_magnitude {
+            return None;
+        }
+        let range = (self.faintest_magnitude - self.brightest_magnitude).max(f64::EPSILON);
+        let brightness = ((self.faintest_magnitude - magnitude) / range).clamp(0.0, 1.0) as f32;
+        Some((
+            self.min_size_px + (self.max_size_px - self.min_size_px) * brightness,
+            self.min_alpha + (1.0 - self.min_alpha) * brightness,
+        ))
+    }
+}
+
+pub struct DistantStarPlugin;
+impl Plugin for DistantStarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DistantStarSettings>()
+            .init_resource::<LayerConfig>()
+            .add_systems(PostUpdate, update_distant_stars);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_distant_stars(
+    mut commands: Commands,
+    mut star_query: Query<
+        (Entity, &DistantStar, &GlobalTransform, &mut Visibility),
+        Without<DistantStarSprite>,
+    >,
+    mut sprite_query: Query<(
+        Entity,
+        &DistantStarSprite,
+        &mut Transform,
+        &mut Visibility,
+        &mut Sprite,
+    )>,
+    camera_3d_query: Query<(&Camera, &GlobalTransform), (With<Camera3d>, With<FloatingOrigin>)>,
+    camera_2d_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    settings: Res<DistantStarSettings>,
+    layer_config: Res<LayerConfig>,
+) {
+    let (Ok((camera_3d, camera_3d_global_transform)), Ok((camera_2d, camera_2d_global_transform))) =
+        (camera_3d_query.get_single(), camera_2d_query.get_single())
+    else {
+        return;
+    };
+    /* Sprites whose star is gone go with it; the rest are collected so each star finds its own */
+    let mut sprites = Vec::new();
+    for (sprite_entity, sprite, ..) in sprite_query.iter() {
+        match star_query.contains(sprite.star) {
+            true => sprites.push((sprite.star, sprite_entity)),
+            false => commands.entity(sprite_entity).despawn_recursive(),
+        }
+    }
+    for (star_entity, star, star_global_transform, mut star_visibility) in star_query.iter_mut() {
+        let star_position = star_global_transform.translation();
+        let distance_m = star_position.distance(camera_3d_global_transform.translation());
+        let distant = distance_m > settings.switch_distance_m;
+        let mesh_visibility = match distant {
+            true => Visibility::Hidden,
+            false => Visibility::Inherited,
+        };
+        if *star_visibility != mesh_visibility {
+            *star_visibility = mesh_visibility;
+        }
+
+        let sprite = distant
+            .then(|| settings.sprite_for(apparent_magnitude(star.luminosity_w, distance_m as f64)))
+            .flatten()
+            .filter(|_| is_onscreen(camera_3d, camera_3d_global_transform, star_position))
+            .zip(overlay_position(
+                camera_3d,
+                camera_3d_global_transform,
+                camera_2d,
+                camera_2d_global_transform,
+                star_position,
+            ));
+        let existing = sprites
+            .iter()
+            .find(|(star, _)| *star == star_entity)
+            .map(|(_, sprite_entity)| *sprite_entity);
+        match (
+            sprite,
+            existing.and_then(|entity| sprite_query.get_mut(entity).ok()),
+        ) {
+            (
+                Some(((size, alpha), position)),
+                Some((_, _, mut transform, mut visibility, mut sprite)),
+            ) => {
+                transform.translation = position.extend(0.0);
+                *visibility = Visibility::Visible;
+                sprite.color = settings.color.with_a(alpha);
+                sprite.custom_size = Some(Vec2::splat(size));
+            }
+            (Some(((size, alpha), position)), None) => {
+                commands.spawn((
+                    DistantStarSprite { star: star_entity },
+                    IgnoreFloatingOrigin,
+                    settings.render_layers(&layer_config),
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: settings.color.with_a(alpha),
+                            custom_size: Some(Vec2::splat(size)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(position.extend(0.0)),
+                        ..default()
+                    },
+                ));
+            }
+            (None, Some((_, _, _, mut visibility, _))) => *visibility = Visibility::Hidden,
+            (None, None) => {}
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState, UntypedAssetId},
+    log::Level,
+    prelude::*,
+    utils::{tracing::span, HashSet},
+};
+
+/// Asset handles that must finish loading, dependencies included, before the app leaves its loading state.
+/// Register handles with `track` from a startup system.
+#[derive(Resource, Debug, Default)]
+pub struct LoadingAssets {
+    handles: Vec<UntypedHandle>,
+}
+
+impl LoadingAssets {
+    /// Adds `handle` to the tracked set and hands it back, so loads can be registered inline.
+    pub fn track<A: Asset>(&mut self, handle: Handle<A>) -> Handle<A> {
+        self.handles.push(handle.clone().untyped());
+        handle
+    }
+
+    pub fn handles(&self) -> &[UntypedHandle] {
+        &self.handles
+    }
+}
+
+/// How many of the tracked assets have loaded along with their dependencies, updated every frame during the loading
+/// state. Counts the same way `wait_for_tracked_assets` decides readiness, so it reaches `1.0` when loading ends.
+/// `fraction` is in `0.0..=1.0` and is `1.0` when nothing is tracked.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct LoadingProgress {
+    pub loaded: usize,
+    pub total: usize,
+    pub fraction: f32,
+}
+
+impl LoadingProgress {
+    pub fn new(loaded: usize, total: usize) -> Self {
+        let fraction = match total {
+            0 => 1.0,
+            _ => loaded as f32 / total as f32,
+        };
+        Self {
+            loaded,
+            total,
+            fraction,
+        }
+    }
+}
+
+/// Whether `id`, or anything it depends on, failed to load.
+pub fn load_failed(asset_server: &AssetServer, id: UntypedAssetId) -> bool {
+    asset_server.get_load_state(id) == Some(LoadState::Failed)
+        || asset_server.get_recursive_dependency_load_state(id)
+            == Some(RecursiveDependencyLoadState::Failed)
+}
+
+/// The state to move to once every tracked asset has loaded, and the state to move to if any of them fails.
+#[derive(Resource, Debug)]
+pub struct LoadingTransition<S: States> {
+    pub ready: S,
+    pub failed: Option<S>,
+}
+
+/// While the app is in `loading`, polls the handles in `LoadingAssets` and switches to `ready` once all have loaded.
+/// A failed load is logged and switches to `failed`, or with `failed: None` is skipped so the app continues with the
+/// assets that did load.
+pub struct AssetLoadingPlugin<S: States> {
+    pub loading: S,
+    pub ready: S,
+    pub failed: Option<S>,
+}
+
+impl<S: States> Plugin for AssetLoadingPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>()
+            .init_resource::<LoadingProgress>()
+            .insert_resource(LoadingTransition {
+                ready: self.ready.clone(),
+                failed: self.failed.clone(),
+            })
+            .add_systems(
+                Update,
+                (update_loading_progress, wait_for_tracked_assets::<S>)
+                    .chain()
+                    .run_if(in_state(self.loading.clone())),
+            );
+    }
+}
+
+pub fn update_loading_progress(
+    asset_server: Res<AssetServer>,
+    loading_assets: Res<LoadingAssets>,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    let loaded = loading_assets
+        .handles()
+        .iter()
+        .filter(|handle| asset_server.is_loaded_with_dependencies(handle.id()))
+        .count();
+    let new_progress = LoadingProgress::new(loaded, loading_assets.handles().len());
+    if *progress != new_progress {
+        debug!("loaded {} of {}", new_progress.loaded, new_progress.total);
+        *progress = new_progress;
+    }
+}
+
+pub fn wait_for_tracked_assets<S: States>(
+    asset_server: Res<AssetServer>,
+    loading_assets: Res<LoadingAssets>,
+    transition: Res<LoadingTransition<S>>,
+    mut state: ResMut<NextState<S>>,
+    mut reported_failures: Local<HashSet<UntypedAssetId>>,
+) {
+    let span = span!(Level::INFO, "wait_for_tracked_assets()");
+    let _enter = span.enter();
+    let mut any_failed = false;
+    let mut all_settled = true;
+    for handle in loading_assets.handles() {
+        if load_failed(&asset_server, handle.id()) {
+            any_failed = true;
+            if reported_failures.insert(handle.id()) {
+                error!("failed to load {:?}", asset_server.get_path(handle.id()));
+            }
+        } else if !asset_server.is_loaded_with_dependencies(handle.id()) {
+            all_settled = false;
+        }
+    }
+    match (any_failed, &transition.failed) {
+        (true, Some(failed)) => {
+            debug!("loading failed");
+            state.set(failed.clone());
+        }
+        _ if all_settled => {
+            debug!("loading complete");
+            state.set(transition.ready.clone());
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use bevy::{
+        asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+        utils::BoxedFuture,
+    };
+
+    use super::*;
+
+    #[derive(Asset, TypePath)]
+    struct TestText;
+
+    /// A loader for `.txt` files, so a missing path fails inside a real loader rather than for want of one.
+    struct TestTextLoader;
+    impl AssetLoader for TestTextLoader {
+        type Asset = TestText;
+        type Settings = ();
+        type Error = std::io::Error;
+
+        fn load<'a>(
+            &'a self,
+            reader: &'a mut Reader,
+            _settings: &'a (),
+            _load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<TestText, std::io::Error>> {
+            Box::pin(async move {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes).await?;
+                Ok(TestText)
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+    }
+
+    #[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    enum TestState {
+        #[default]
+        Loading,
+        Ready,
+        Failed,
+    }
+
+    fn load_missing_asset(
+        asset_server: Res<AssetServer>,
+        mut loading_assets: ResMut<LoadingAssets>,
+    ) {
+        loading_assets.track(asset_server.load::<TestText>("missing/does_not_exist.txt"));
+    }
+
+    #[test]
+    fn bad_asset_path_reaches_failed_state() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            AssetLoadingPlugin {
+                loading: TestState::Loading,
+                ready: TestState::Ready,
+                failed: Some(TestState::Failed),
+            },
+        ))
+        .init_asset::<TestText>()
+        .register_asset_loader(TestTextLoader)
+        .init_state::<TestState>()
+        .add_systems(Startup, load_missing_asset);
+        /* Loads run on the IO task pool, so give the failure a moment to come back */
+        for _ in 0..500 {
+            app.update();
+            if *app.world.resource::<State<TestState>>().get() != TestState::Loading {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            *app.world.resource::<State<TestState>>().get(),
+            TestState::Failed
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::{math::DVec3, prelude::*};
+use bevy_rapier3d::prelude::Velocity;
+use big_space::{camera::CameraController, FloatingOrigin};
+
+use crate::time::{SimulationTime, SimulationTimePlugin};
+
+/// A planned burn: `delta_v` meters per second along `direction` (world space, unit length).
+/// `remaining_delta_v` counts down as the floating origin camera accelerates along `direction`.
+#[derive(Resource, Clone, Debug)]
+pub struct ManeuverNode {
+    pub direction: DVec3,
+    pub delta_v: f64,
+    pub remaining_delta_v: f64,
+}
+
+impl ManeuverNode {
+    pub fn new(direction: DVec3, delta_v: f64) -> Self {
+        Self {
+            direction: direction.normalize_or_zero(),
+            delta_v,
+            remaining_delta_v: delta_v,
+        }
+    }
+
+    /// Credits a velocity change toward the node. Only the part along `direction` counts.
+    pub fn apply_velocity_change(&mut self, velocity_change: DVec3) {
+        self.remaining_delta_v =
+            (self.remaining_delta_v - velocity_change.dot(self.direction)).clamp(0.0, self.delta_v);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining_delta_v <= 0.0
+    }
+}
+
+/// A burn to make at a future moment: once `SimulationTime` reaches `at` seconds, `delta_v` (m/s) is added to the
+/// entity's `Velocity` and this component is removed. `delta_v` is in the root reference frame's axes, which don't
+/// rotate, so it is applied to the world space linear velocity as is.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ScheduledBurn {
+    pub at: f64,
+    pub delta_v: DVec3,
+}
+
+impl ScheduledBurn {
+    /// Seconds of simulated time until the burn, zero once it is due.
+    pub fn time_until(&self, simulation_time: &SimulationTime) -> f64 {
+        (self.at - simulation_time.elapsed_s).max(0.0)
+    }
+}
+
+/// Sent when a `ScheduledBurn` has been applied.
+#[derive(Event, Debug)]
+pub struct BurnExecuted {
+    pub entity: Entity,
+    pub delta_v: DVec3,
+}
+
+/// Tracks the `ManeuverNode` and executes `ScheduledBurn`s. Burns follow `SimulationTime`, which
+/// `SimulationTimePlugin` advances.
+pub struct ManeuverPlugin;
+impl Plugin for ManeuverPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SimulationTimePlugin>() {
+            app.add_plugins(SimulationTimePlugin);
+        }
+        app.add_event::<BurnExecuted>().add_systems(
+            Update,
+            (
+                burn_maneuver_node.run_if(resource_exists::<ManeuverNode>),
+                execute_scheduled_burns,
+            ),
+        );
+    }
+}
+
+pub fn execute_scheduled_burns(
+    mut commands: Commands,
+    simulation_time: Res<SimulationTime>,
+    mut burn_query: Query<(Entity, &ScheduledBurn, &mut Velocity)>,
+    mut burn_executed: EventWriter<BurnExecuted>,
+) {
+    for (each_entity, each_burn, mut each_velocity) in burn_query.iter_mut() {
+        if simulation_time.elapsed_s < each_burn.at {
+            continue;
+        }
+        each_velocity.linvel += each_burn.delta_v.as_vec3();
+        debug!(
+            "{:?} burned {:?} at {:?}",
+            each_entity, each_burn.delta_v, simulation_time.elapsed_s
+        );
+        commands.entity(each_entity).remove::<ScheduledBurn>();
+        burn_executed.send(BurnExecuted {
+            entity: each_entity,
+            delta_v: each_burn.delta_v,
+        });
+    }
+}
+
+/// Subtracts the camera's change in velocity along the maneuver direction from the node's remaining delta-v.
+pub fn burn_maneuver_node(
+    mut maneuver_node: ResMut<ManeuverNode>,
+    camera_query: Query<&CameraController, With<FloatingOrigin>>,
+    time: Res<Time>,
+    mut previous_velocity: Local<Option<DVec3>>,
+) {
+    let Ok(camera_controller) = camera_query.get_single() else {
+        return;
+    };
+    if time.delta_seconds_f64() <= 0.0 {
+        return;
+    }
+    /* The controller reports the displacement for the frame */
+    let velocity = camera_controller.velocity().0 / time.delta_seconds_f64();
+    if let Some(previous_velocity) = *previous_velocity {
+        maneuver_node.apply_velocity_change(velocity - previous_velocity);
+    }
+    *previous_velocity = Some(velocity);
+}
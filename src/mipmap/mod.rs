@@ -23,6 +23,11 @@ pub struct MipmapGeneratorSettings {
     pub anisotropic_filtering: u16,
     pub filter_type: FilterType,
     pub minimum_mip_resolution: u32,
+    /// Total bytes of mip levels (beyond each texture's full size level) the generator may add, `None` for no limit.
+    /// Once reached, later textures get shorter chains or none at all.
+    pub memory_budget_bytes: Option<usize>,
+    /// Highest mip level to generate, zero being the full size image, `None` to go all the way down.
+    pub max_mip_level: Option<u32>,
 }
 
 ///Mipmaps will not be generated for materials found on entities that also have the `NoMipmapGeneration` component.
@@ -36,23 +41,41 @@ impl Default for MipmapGeneratorSettings {
             anisotropic_filtering: 8,
             filter_type: FilterType::Triangle,
             minimum_mip_resolution: 1,
+            memory_budget_bytes: None,
+            max_mip_level: None,
         }
     }
 }
 
+impl MipmapGeneratorSettings {
+    /// Most mip levels a texture may end up with, counting the full size level.
+    pub fn max_mip_count(&self) -> u32 {
+        self.max_mip_level
+            .map_or(u32::MAX, |max_mip_level| max_mip_level.saturating_add(1))
+    }
+}
+
 pub struct MipmapGeneratorPlugin;
 impl Plugin for MipmapGeneratorPlugin {
     fn build(&self, app: &mut App) {
         if let Some(image_plugin) = app.get_added_plugins::<ImagePlugin>().first() {
             let default_sampler = image_plugin.default_sampler.clone();
             app.insert_resource(DefaultSampler(default_sampler))
-                .init_resource::<MipmapGeneratorSettings>();
+                .init_resource::<MipmapGeneratorSettings>()
+                .init_resource::<MipmapMemoryUsage>();
         } else {
             warn!("No ImagePlugin found. Try adding MipmapGeneratorPlugin after DefaultPlugins");
         }
     }
 }
 
+/// Bytes of mip level data the generator has added so far, counted against
+/// `MipmapGeneratorSettings::memory_budget_bytes`.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct MipmapMemoryUsage {
+    pub generated_bytes: usize,
+}
+
 #[derive(Resource, Default, Deref, DerefMut)]
 #[allow(clippy::type_complexity)]
 pub struct MipmapTasks<M: Material + GetImages>(
@@ -68,6 +91,7 @@ pub fn generate_mipmaps<M: Material + GetImages>(
     mut images: ResMut<Assets<Image>>,
     default_sampler: Res<DefaultSampler>,
     settings: Res<MipmapGeneratorSettings>,
+    mut memory_usage: ResMut<MipmapMemoryUsage>,
     mut tasks_res: Option<ResMut<MipmapTasks<M>>>,
 ) {
     let span = span!(Level::INFO, "generate_mipmaps()");
@@ -109,20 +133,61 @@ pub fn generate_mipmaps<M: Material + GetImages>(
                     };
                     descriptor.anisotropy_clamp = settings.anisotropic_filtering;
                     image.sampler = ImageSampler::Descriptor(descriptor);
-                    if image.texture_descriptor.mip_level_count == 1
-                        && check_image_compatible(image).is_ok()
+                    if image.texture_descriptor.mip_level_count != 1 {
+                        continue;
+                    }
+                    if let Err(e) = check_image_compatible(image)
+                        .and_then(|_| check_format_supported(image.texture_descriptor.format))
                     {
-                        let mut image = image.clone();
-                        let settings = settings.clone();
-                        let task = thread_pool.spawn(async move {
-                            match generate_mips_texture(&mut image, &settings.clone()) {
-                                Ok(_) => (),
-                                Err(e) => warn!("{}", e),
+                        warn!("Skipping mipmap generation for {:?}: {}", image_h, e);
+                        continue;
+                    }
+                    let size = image.texture_descriptor.size;
+                    let block_size = image
+                        .texture_descriptor
+                        .format
+                        .block_copy_size(None)
+                        .unwrap_or(0) as usize;
+                    let full_chain = mip_chain_sizes(
+                        size.width,
+                        size.height,
+                        settings.minimum_mip_resolution,
+                        settings.max_mip_count(),
+                    );
+                    let max_mip_count = match settings.memory_budget_bytes {
+                        Some(budget) => {
+                            let remaining = budget.saturating_sub(memory_usage.generated_bytes);
+                            let mip_count = mip_count_within(&full_chain, block_size, remaining);
+                            if mip_count < full_chain.len() as u32 {
+                                warn!(
+                                    "Mipmap memory budget of {} bytes reached, truncating {:?} to {} of {} mip levels",
+                                    budget,
+                                    image_h,
+                                    mip_count,
+                                    full_chain.len()
+                                );
                             }
-                            image
-                        });
-                        tasks.insert(image_h.clone(), (task, vec![Handle::Weak(*material_h)]));
+                            mip_count
+                        }
+                        None => full_chain.len() as u32,
+                    };
+                    if max_mip_count <= 1 {
+                        continue;
                     }
+                    memory_usage.generated_bytes += mip_chain_bytes(
+                        &full_chain[..(max_mip_count as usize).min(full_chain.len())],
+                        block_size,
+                    );
+                    let mut image = image.clone();
+                    let settings = settings.clone();
+                    let task = thread_pool.spawn(async move {
+                        match generate_mips_texture(&mut image, &settings.clone(), max_mip_count) {
+                            Ok(_) => (),
+                            Err(e) => warn!("{}", e),
+                        }
+                        image
+                    });
+                    tasks.insert(image_h.clone(), (task, vec![Handle::Weak(*material_h)]));
                 }
             }
         }
@@ -151,58 +216,124 @@ pub fn generate_mipmaps<M: Material + GetImages>(
     debug!("stop");
 }
 
+/// Replaces `image` with itself plus up to `max_mip_count` (counting the full size level) mip levels.
 pub fn generate_mips_texture(
     image: &mut Image,
     settings: &MipmapGeneratorSettings,
+    max_mip_count: u32,
 ) -> anyhow::Result<()> {
     let span = span!(Level::INFO, "generate_mips_texture()");
     let _enter = span.enter();
     debug!("start");
 
     check_image_compatible(image)?;
-    match try_into_dynamic(image.clone()) {
-        Ok(mut dyn_image) => {
-            let (mip_level_count, image_data) = generate_mips(
-                &mut dyn_image,
-                settings.minimum_mip_resolution,
-                u32::MAX,
-                settings.filter_type,
-            );
-            image.texture_descriptor.mip_level_count = mip_level_count;
-            image.data = image_data;
-            debug!("stop");
-            Ok(())
-        }
-        Err(e) => Err(e),
-    }
+    let descriptor = &image.texture_descriptor;
+    let (mip_level_count, image_data) = generate_mips(
+        &image.data,
+        descriptor.size.width,
+        descriptor.size.height,
+        descriptor.format,
+        settings.minimum_mip_resolution,
+        max_mip_count,
+        settings.filter_type,
+    )?;
+    image.texture_descriptor.mip_level_count = mip_level_count;
+    image.data = image_data;
+    debug!("stop");
+    Ok(())
 }
 
 /// Returns the number of mip levels, and a vec of bytes containing the image data.
 /// The `max_mip_count` includes the first input mip level. So setting this to 2 will
 /// result in a single additional mip level being generated, for a total of 2 levels.
 pub fn generate_mips(
-    dyn_image: &mut DynamicImage,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    format: TextureFormat,
     minimum_mip_resolution: u32,
     max_mip_count: u32,
     filter_type: FilterType,
-) -> (u32, Vec<u8>) {
-    let mut image_data = dyn_image.as_bytes().to_vec();
-    let mut mip_level_count = 1;
-    let mut width = dyn_image.width();
-    let mut height = dyn_image.height();
-
-    while width / 2 >= minimum_mip_resolution.max(1)
-        && height / 2 >= minimum_mip_resolution.max(1)
-        && mip_level_count < max_mip_count
-    {
-        width /= 2;
-        height /= 2;
-        *dyn_image = dyn_image.resize_exact(width, height, filter_type);
-        image_data.append(&mut dyn_image.as_bytes().to_vec());
-        mip_level_count += 1;
+) -> anyhow::Result<(u32, Vec<u8>)> {
+    let sizes = mip_chain_sizes(width, height, minimum_mip_resolution, max_mip_count);
+    let mut image_data = data.to_vec();
+    let mut level = data.to_vec();
+    for &(width, height) in &sizes[..sizes.len() - 1] {
+        level = downsample_level(&level, width, height, format, filter_type)?;
+        image_data.extend_from_slice(&level);
     }
 
-    (mip_level_count, image_data)
+    Ok((sizes.len() as u32, image_data))
+}
+
+/// Sizes of each mip level of a `width` x `height` image, starting with the full size level. The chain ends at 1x1,
+/// before a level whose smaller side would drop below `minimum_mip_resolution`, or after `max_mip_count` levels.
+pub fn mip_chain_sizes(
+    width: u32,
+    height: u32,
+    minimum_mip_resolution: u32,
+    max_mip_count: u32,
+) -> Vec<(u32, u32)> {
+    let mut sizes = vec![(width, height)];
+    let (mut width, mut height) = (width, height);
+    while (sizes.len() as u32) < max_mip_count {
+        let (next_width, next_height) = next_mip_size(width, height);
+        if (width, height) == (next_width, next_height)
+            || next_width.min(next_height) < minimum_mip_resolution.max(1)
+        {
+            break;
+        }
+        (width, height) = (next_width, next_height);
+        sizes.push((width, height));
+    }
+    sizes
+}
+
+/// Bytes taken by the levels of `sizes` after the first, with `block_size` bytes per pixel.
+pub fn mip_chain_bytes(sizes: &[(u32, u32)], block_size: usize) -> usize {
+    sizes
+        .iter()
+        .skip(1)
+        .map(|(width, height)| *width as usize * *height as usize * block_size)
+        .sum()
+}
+
+/// How many levels of `sizes`, counting the full size level, fit in `budget_bytes` of added mip data.
+pub fn mip_count_within(sizes: &[(u32, u32)], block_size: usize, budget_bytes: usize) -> u32 {
+    (1..=sizes.len())
+        .take_while(|count| mip_chain_bytes(&sizes[..*count], block_size) <= budget_bytes)
+        .last()
+        .unwrap_or(1) as u32
+}
+
+/// Size of the mip level after a `width` x `height` one. Each dimension is halved and rounded down, but never drops
+/// below one, so odd and non-power-of-two sizes still end in a 1x1 level.
+pub fn next_mip_size(width: u32, height: u32) -> (u32, u32) {
+    ((width / 2).max(1), (height / 2).max(1))
+}
+
+/// Size of mip level `mip_level` (zero being the full image) of a `width` x `height` image.
+pub fn mip_level_size(width: u32, height: u32, mip_level: u32) -> (u32, u32) {
+    (0..mip_level).fold((width, height), |(width, height), _| {
+        next_mip_size(width, height)
+    })
+}
+
+/// Produces the next mip level of `src`, a `width` x `height` image in `format`, sized by `next_mip_size`.
+/// Each output pixel is `filter_type` applied over the block of source pixels it covers; for `Triangle` and a 2x2
+/// block that is their average.
+pub fn downsample_level(
+    src: &[u8],
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    filter_type: FilterType,
+) -> anyhow::Result<Vec<u8>> {
+    let dyn_image = dynamic_from_raw(src.to_vec(), width, height, format)?;
+    let (next_width, next_height) = next_mip_size(width, height);
+    Ok(dyn_image
+        .resize_exact(next_width, next_height, filter_type)
+        .into_bytes())
 }
 
 /// Extract a specific individual mip level as a new image.
@@ -218,22 +349,23 @@ pub fn extract_mip_level(image: &Image, mip_level: u32) -> anyhow::Result<Image>
         ));
     }
 
-    let block_size = descriptor.format.block_copy_size(None).unwrap() as usize;
-
-    //let mip_factor = 2u32.pow(mip_level - 1);
-    //let final_width = descriptor.size.width/mip_factor;
-    //let final_height = descriptor.size.height/mip_factor;
-
-    let mut width = descriptor.size.width as usize;
-    let mut height = descriptor.size.height as usize;
+    let block_size = descriptor
+        .format
+        .block_copy_size(None)
+        .ok_or_else(|| anyhow!("Unsupported format {:?}.", descriptor.format))?
+        as usize;
 
     let mut byte_offset = 0usize;
-
-    for _ in 0..mip_level - 1 {
-        byte_offset += width * block_size * height;
-        width /= 2;
-        height /= 2;
+    for level in 0..mip_level.saturating_sub(1) {
+        let (width, height) = mip_level_size(descriptor.size.width, descriptor.size.height, level);
+        byte_offset += width as usize * block_size * height as usize;
     }
+    let (width, height) = mip_level_size(
+        descriptor.size.width,
+        descriptor.size.height,
+        mip_level.saturating_sub(1),
+    );
+    let (width, height) = (width as usize, height as usize);
 
     let mut new_descriptor = descriptor.clone();
 
@@ -276,6 +408,20 @@ pub fn check_image_compatible(image: &Image) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Errors unless `format` is one `dynamic_from_raw` can convert.
+pub fn check_format_supported(format: TextureFormat) -> anyhow::Result<()> {
+    match format {
+        TextureFormat::R8Unorm
+        | TextureFormat::Rg8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Rgba8Unorm => Ok(()),
+        texture_format => Err(anyhow!(
+            "Mipmap generation not supported for {:?}.",
+            texture_format
+        )),
+    }
+}
+
 // Implement the GetImages trait for any materials that need conversion
 pub trait GetImages {
     fn get_images(&self) -> Vec<&Handle<Image>>;
@@ -297,31 +443,34 @@ impl GetImages for StandardMaterial {
 }
 
 pub fn try_into_dynamic(image: Image) -> anyhow::Result<DynamicImage> {
-    match image.texture_descriptor.format {
-        TextureFormat::R8Unorm => ImageBuffer::from_raw(
-            image.texture_descriptor.size.width,
-            image.texture_descriptor.size.height,
-            image.data,
-        )
-        .map(DynamicImage::ImageLuma8),
-        TextureFormat::Rg8Unorm => ImageBuffer::from_raw(
-            image.texture_descriptor.size.width,
-            image.texture_descriptor.size.height,
-            image.data,
-        )
-        .map(DynamicImage::ImageLumaA8),
-        TextureFormat::Rgba8UnormSrgb => ImageBuffer::from_raw(
-            image.texture_descriptor.size.width,
-            image.texture_descriptor.size.height,
-            image.data,
-        )
-        .map(DynamicImage::ImageRgba8),
-        TextureFormat::Rgba8Unorm => ImageBuffer::from_raw(
-            image.texture_descriptor.size.width,
-            image.texture_descriptor.size.height,
-            image.data,
-        )
-        .map(DynamicImage::ImageRgba8),
+    dynamic_from_raw(
+        image.data,
+        image.texture_descriptor.size.width,
+        image.texture_descriptor.size.height,
+        image.texture_descriptor.format,
+    )
+}
+
+/// Wraps raw pixel bytes of an uncompressed 8-bit-per-channel `format` as a `DynamicImage`.
+pub fn dynamic_from_raw(
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> anyhow::Result<DynamicImage> {
+    match format {
+        TextureFormat::R8Unorm => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8)
+        }
+        TextureFormat::Rg8Unorm => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA8)
+        }
+        TextureFormat::Rgba8UnormSrgb => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+        }
+        TextureFormat::Rgba8Unorm => {
+            ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8)
+        }
         // Throw and error if conversion isn't supported
         texture_format => {
             return Err(anyhow!(
@@ -330,10 +479,66 @@ pub fn try_into_dynamic(image: Image) -> anyhow::Result<DynamicImage> {
             ))
         }
     }
-    .ok_or_else(|| {
-        anyhow!(
-            "Failed to convert into {:?}.",
-            image.texture_descriptor.format
-        )
-    })
+    .ok_or_else(|| anyhow!("Failed to convert into {:?}.", format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downsample_solid_color_is_unchanged() {
+        let pixel: [u8; 4] = [12, 34, 56, 255];
+        let src = pixel.repeat(4 * 4);
+        let level =
+            downsample_level(&src, 4, 4, TextureFormat::Rgba8Unorm, FilterType::Triangle).unwrap();
+        assert_eq!(level, pixel.repeat(2 * 2));
+    }
+
+    #[test]
+    fn downsample_checkerboard_is_its_mean() {
+        let src: [u8; 4] = [0, 200, 200, 0];
+        let level =
+            downsample_level(&src, 2, 2, TextureFormat::R8Unorm, FilterType::Triangle).unwrap();
+        assert_eq!(level, vec![100]);
+    }
+
+    #[test]
+    fn downsample_odd_dimensions_round_like_next_mip_size() {
+        for (width, height) in [(5, 3), (7, 1), (1, 9), (3, 3)] {
+            let src = vec![0u8; (width * height * 4) as usize];
+            let level = downsample_level(
+                &src,
+                width,
+                height,
+                TextureFormat::Rgba8Unorm,
+                FilterType::Triangle,
+            )
+            .unwrap();
+            let (next_width, next_height) = next_mip_size(width, height);
+            assert_eq!(level.len(), (next_width * next_height * 4) as usize);
+        }
+    }
+
+    #[test]
+    fn mip_chain_sizes_of_6x10_runs_down_to_1x1() {
+        assert_eq!(
+            mip_chain_sizes(6, 10, 1, u32::MAX),
+            vec![(6, 10), (3, 5), (1, 2), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn max_mip_level_stops_the_chain() {
+        let settings = MipmapGeneratorSettings {
+            max_mip_level: Some(2),
+            ..default()
+        };
+        assert_eq!(settings.max_mip_count(), 3);
+        assert_eq!(
+            mip_chain_sizes(64, 64, 1, settings.max_mip_count()),
+            vec![(64, 64), (32, 32), (16, 16)]
+        );
+        assert_eq!(MipmapGeneratorSettings::default().max_mip_count(), u32::MAX);
+    }
 }
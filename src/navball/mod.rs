@@ -0,0 +1,411 @@
+use bevy::{
+    log::Level,
+    math::DVec3,
+    prelude::*,
+    render::{camera::ScalingMode, view::RenderLayers},
+    utils::tracing::span,
+};
+use bevy_scene_hook::{HookedSceneBundle, SceneHook};
+use big_space::{
+    camera::CameraController, reference_frame::RootReferenceFrame, FloatingOrigin, GridCell,
+    IgnoreFloatingOrigin,
+};
+
+use crate::{
+    camera::{target_grid_position, TargetPositionQuery},
+    grid::look_toward,
+    layers::LayerConfig,
+    maneuver::{ManeuverNode, ScheduledBurn},
+    targeting::CurrentTarget,
+};
+
+/// The navball sphere, rotated every frame to show the camera's attitude relative to the current target.
+#[derive(Component)]
+pub struct NavBall;
+
+/// Targets with this marker align the navball's up with their own up (e.g. planets). For other targets the navball
+/// uses the camera's up.
+#[derive(Component)]
+pub struct NavBallTargetUp;
+
+/// The camera's attitude in degrees, relative to a reference frame whose forward points at the current target and
+/// whose up is the target's up for `NavBallTargetUp` targets, or the camera's own up otherwise.
+/// Heading is clockwise from the target direction in `0..360`, pitch is positive above it, roll is positive when
+/// rolled clockwise (right wing down).
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct NavBallAttitude {
+    pub heading: f32,
+    pub pitch: f32,
+    pub roll: f32,
+}
+
+impl NavBallAttitude {
+    /// Attitude of `camera_rotation` within the frame given by `reference_rotation`. Both look down their local -Z.
+    pub fn relative_to(camera_rotation: Quat, reference_rotation: Quat) -> Self {
+        let relative_rotation = reference_rotation.inverse() * camera_rotation;
+        let (yaw, pitch, roll) = relative_rotation.to_euler(EulerRot::YXZ);
+        Self {
+            heading: (-yaw.to_degrees()).rem_euclid(360.0),
+            pitch: pitch.to_degrees(),
+            roll: -roll.to_degrees(),
+        }
+    }
+}
+
+/// Gizmos drawn over the navball, such as the prograde and retrograde markers. Rendered on the navball's layer.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct NavBallGizmos {}
+
+/// Radius of the navball meshes.
+pub const NAVBALL_RADIUS: f32 = 1.0;
+
+#[derive(Resource, Clone)]
+pub struct NavBallSettings {
+    /// Layer shared by the navball's orthographic camera, meshes and light, overriding `LayerConfig::foreground`.
+    /// Keep it off the world's layers.
+    pub render_layers: Option<RenderLayers>,
+    pub camera_order: isize,
+    /// Offsets the orthographic camera so the navball sits in a corner of the screen.
+    pub camera_transform: Transform,
+    pub nav_ball_scene: String,
+    pub nav_ring_scene: String,
+    pub prograde_color: Color,
+    pub target_color: Color,
+    pub maneuver_color: Color,
+    /// Size of markers relative to the navball radius.
+    pub marker_size: f32,
+}
+
+impl Default for NavBallSettings {
+    fn default() -> Self {
+        Self {
+            render_layers: None,
+            camera_order: 1,
+            camera_transform: Transform::from_xyz(-7.5, 3.75, 3.0),
+            nav_ball_scene: "experiment_002/nav_ball_orbital.glb#Scene0".to_string(),
+            nav_ring_scene: "experiment_002/nav_ring.glb#Scene0".to_string(),
+            prograde_color: Color::YELLOW,
+            target_color: Color::FUCHSIA,
+            maneuver_color: Color::rgb(0.2, 0.4, 1.0),
+            marker_size: 0.12,
+        }
+    }
+}
+
+impl NavBallSettings {
+    pub fn render_layers(&self, layer_config: &LayerConfig) -> RenderLayers {
+        self.render_layers.unwrap_or(layer_config.foreground)
+    }
+}
+
+pub struct NavBallPlugin;
+impl Plugin for NavBallPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavBallSettings>()
+            .init_resource::<LayerConfig>()
+            .init_resource::<CurrentTarget>()
+            .init_resource::<NavBallAttitude>()
+            .init_gizmo_group::<NavBallGizmos>()
+            .add_systems(Startup, spawn_navball)
+            .add_systems(
+                Update,
+                (
+                    align_navball,
+                    draw_velocity_markers,
+                    draw_target_marker,
+                    draw_maneuver_marker.run_if(resource_exists::<ManeuverNode>),
+                    draw_scheduled_burn_marker,
+                ),
+            );
+    }
+}
+
+pub fn spawn_navball(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<NavBallSettings>,
+    layer_config: Res<LayerConfig>,
+    mut config_store: ResMut<GizmoConfigStore>,
+) {
+    let span = span!(Level::INFO, "spawn_navball()");
+    let _enter = span.enter();
+    debug!("start");
+    let render_layers = settings.render_layers(&layer_config);
+
+    let (navball_gizmo_config, _) = config_store.config_mut::<NavBallGizmos>();
+    navball_gizmo_config.render_layers = render_layers;
+    /* Markers sit on the sphere's surface, keep them from z-fighting with it */
+    navball_gizmo_config.depth_bias = -0.1;
+
+    /* Orthographic Camera */
+    commands.spawn((
+        render_layers,
+        Camera3dBundle {
+            transform: settings.camera_transform,
+            projection: Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(10.0),
+                ..default()
+            }),
+            camera: Camera {
+                order: settings.camera_order,
+                ..default()
+            },
+            ..default()
+        },
+        IgnoreFloatingOrigin,
+    ));
+
+    /* Orthographic NavBall */
+    commands.spawn((
+        render_layers,
+        HookedSceneBundle {
+            hook: SceneHook::new(move |_entity, cmds| {
+                cmds.insert(render_layers);
+            }),
+            scene: SceneBundle {
+                scene: asset_server.load(settings.nav_ball_scene.clone()),
+                ..default()
+            },
+        },
+        NavBall,
+    ));
+
+    /* Orthographic NavRing */
+    commands.spawn((
+        render_layers,
+        HookedSceneBundle {
+            hook: SceneHook::new(move |_entity, cmds| {
+                cmds.insert(render_layers);
+            }),
+            scene: SceneBundle {
+                scene: asset_server.load(settings.nav_ring_scene.clone()),
+                transform: Transform::from_xyz(0.0, 0.0, 2.0),
+                ..default()
+            },
+        },
+    ));
+
+    /* Orthographic Light */
+    commands.spawn((
+        render_layers,
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: 10_000.0,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.0).looking_at(-Vec3::Z, Vec3::Y),
+            ..default()
+        },
+    ));
+    debug!("stop");
+}
+
+/// Rotation of the navball for a camera with `camera_transform` looking along `target_direction`, with `up` as the
+/// navball's reference up. The direction is taken in f64 so that distant targets don't jitter.
+pub fn navball_rotation(camera_transform: &Transform, target_direction: DVec3, up: Vec3) -> Quat {
+    let mut camera_rotation = camera_transform.rotation;
+    let mut camera_looking_at_target_rotation =
+        look_toward(target_direction, up, camera_transform.rotation).inverse();
+    camera_rotation.z = -camera_rotation.z;
+    camera_looking_at_target_rotation.z = -camera_looking_at_target_rotation.z;
+    camera_rotation * camera_looking_at_target_rotation
+}
+
+#[allow(clippy::type_complexity)]
+pub fn align_navball(
+    mut navball_transform_query: Query<&mut Transform, With<NavBall>>,
+    camera_query: Query<(&GridCell<i64>, &Transform), (With<FloatingOrigin>, Without<NavBall>)>,
+    target_query: Query<(&GlobalTransform, Option<&NavBallTargetUp>)>,
+    target_position_query: TargetPositionQuery<(Without<CameraController>, Without<NavBall>)>,
+    space: Res<RootReferenceFrame<i64>>,
+    current_target: Res<CurrentTarget>,
+    mut attitude: ResMut<NavBallAttitude>,
+) {
+    let span = span!(Level::INFO, "align_navball()");
+    let _enter = span.enter();
+
+    let Some(target_entity) = current_target.target else {
+        return;
+    };
+    let Ok((camera_cell, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(target_position) = target_grid_position(&target_position_query, &space, target_entity)
+    else {
+        return;
+    };
+    match target_query.get(target_entity) {
+        Ok((target_transform, target_up)) => {
+            let up = match target_up {
+                Some(_) => target_transform.up().normalize(),
+                None => camera_transform.up().normalize(),
+            };
+            let camera_position = space.grid_position_double(camera_cell, camera_transform);
+            let target_direction = target_position - camera_position;
+            let reference_rotation = look_toward(target_direction, up, camera_transform.rotation);
+            *attitude = NavBallAttitude::relative_to(camera_transform.rotation, reference_rotation);
+            let rotation = navball_rotation(camera_transform, target_direction, up);
+            for mut navball_transform in navball_transform_query.iter_mut() {
+                navball_transform.rotation = rotation;
+            }
+        }
+        Err(e) => {
+            error!("{:?}", e)
+        }
+    }
+}
+
+/// Where a world space direction appears on the navball, relative to its center and scaled to its radius.
+/// The camera's forward maps to the point facing the viewer. Returns `None` for directions on the far side.
+pub fn navball_marker_position(camera_rotation: Quat, world_direction: Vec3) -> Option<Vec3> {
+    let camera_direction = camera_rotation.inverse() * world_direction.try_normalize()?;
+    let display_position = Vec3::new(camera_direction.x, camera_direction.y, -camera_direction.z);
+    match display_position.z >= 0.0 {
+        true => Some(display_position * NAVBALL_RADIUS),
+        false => None,
+    }
+}
+
+/// Draws the prograde (circle with a dot) and retrograde (circle with a cross) markers for the camera's velocity.
+pub fn draw_velocity_markers(
+    mut navball_gizmos: Gizmos<NavBallGizmos>,
+    camera_query: Query<(&Transform, &CameraController), With<FloatingOrigin>>,
+    navball_query: Query<&GlobalTransform, With<NavBall>>,
+    settings: Res<NavBallSettings>,
+) {
+    let (Ok((camera_transform, camera_controller)), Ok(navball_global_transform)) =
+        (camera_query.get_single(), navball_query.get_single())
+    else {
+        return;
+    };
+    let velocity = camera_controller.velocity().0.as_vec3();
+    if velocity.length_squared() < f32::EPSILON {
+        return;
+    }
+    let center = navball_global_transform.translation();
+    let size = settings.marker_size * NAVBALL_RADIUS;
+    let color = settings.prograde_color;
+
+    if let Some(prograde) = navball_marker_position(camera_transform.rotation, velocity) {
+        let position = center + prograde;
+        navball_gizmos.circle(position, Direction3d::Z, size, color);
+        navball_gizmos.circle(position, Direction3d::Z, size * 0.15, color);
+    }
+    if let Some(retrograde) = navball_marker_position(camera_transform.rotation, -velocity) {
+        let position = center + retrograde;
+        navball_gizmos.circle(position, Direction3d::Z, size, color);
+        let diagonal = Vec3::new(size, size, 0.0) * std::f32::consts::FRAC_1_SQRT_2;
+        let anti_diagonal = Vec3::new(size, -size, 0.0) * std::f32::consts::FRAC_1_SQRT_2;
+        navball_gizmos.line(position - diagonal, position + diagonal, color);
+        navball_gizmos.line(position - anti_diagonal, position + anti_diagonal, color);
+    }
+}
+
+/// Draws the target marker (a circle with an inner ring) where the direction to `CurrentTarget` lies on the navball.
+/// Nothing is drawn without a target or when the target is behind the camera.
+pub fn draw_target_marker(
+    mut navball_gizmos: Gizmos<NavBallGizmos>,
+    camera_query: Query<(&GridCell<i64>, &Transform), With<FloatingOrigin>>,
+    navball_query: Query<&GlobalTransform, With<NavBall>>,
+    target_query: TargetPositionQuery,
+    current_target: Res<CurrentTarget>,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<NavBallSettings>,
+) {
+    let Some(target) = current_target.target else {
+        return;
+    };
+    let (Ok((camera_cell, camera_transform)), Ok(navball_global_transform)) =
+        (camera_query.get_single(), navball_query.get_single())
+    else {
+        return;
+    };
+    let Some(target_position) = target_grid_position(&target_query, &space, target) else {
+        return;
+    };
+    let camera_position = space.grid_position_double(camera_cell, camera_transform);
+    let target_direction = (target_position - camera_position)
+        .normalize_or_zero()
+        .as_vec3();
+    let Some(marker) = navball_marker_position(camera_transform.rotation, target_direction) else {
+        return;
+    };
+    let position = navball_global_transform.translation() + marker;
+    let size = settings.marker_size * NAVBALL_RADIUS;
+    navball_gizmos.circle(position, Direction3d::Z, size, settings.target_color);
+    navball_gizmos.circle(position, Direction3d::Z, size * 0.5, settings.target_color);
+}
+
+/// Draws the maneuver marker (a circle with three outward ticks) in the direction of the planned burn.
+pub fn draw_maneuver_marker(
+    mut navball_gizmos: Gizmos<NavBallGizmos>,
+    camera_query: Query<&Transform, With<FloatingOrigin>>,
+    navball_query: Query<&GlobalTransform, With<NavBall>>,
+    maneuver_node: Res<ManeuverNode>,
+    settings: Res<NavBallSettings>,
+) {
+    let (Ok(camera_transform), Ok(navball_global_transform)) =
+        (camera_query.get_single(), navball_query.get_single())
+    else {
+        return;
+    };
+    let Some(marker) =
+        navball_marker_position(camera_transform.rotation, maneuver_node.direction.as_vec3())
+    else {
+        return;
+    };
+    draw_maneuver_glyph(
+        &mut navball_gizmos,
+        navball_global_transform.translation() + marker,
+        settings.marker_size * NAVBALL_RADIUS,
+        settings.maneuver_color,
+    );
+}
+
+/// Draws the maneuver marker in the direction of the next `ScheduledBurn` to come due.
+pub fn draw_scheduled_burn_marker(
+    mut navball_gizmos: Gizmos<NavBallGizmos>,
+    camera_query: Query<&Transform, With<FloatingOrigin>>,
+    navball_query: Query<&GlobalTransform, With<NavBall>>,
+    burn_query: Query<&ScheduledBurn>,
+    settings: Res<NavBallSettings>,
+) {
+    let (Ok(camera_transform), Ok(navball_global_transform)) =
+        (camera_query.get_single(), navball_query.get_single())
+    else {
+        return;
+    };
+    let Some(next_burn) = burn_query.iter().min_by(|a, b| a.at.total_cmp(&b.at)) else {
+        return;
+    };
+    let Some(marker) =
+        navball_marker_position(camera_transform.rotation, next_burn.delta_v.as_vec3())
+    else {
+        return;
+    };
+    draw_maneuver_glyph(
+        &mut navball_gizmos,
+        navball_global_transform.translation() + marker,
+        settings.marker_size * NAVBALL_RADIUS,
+        settings.maneuver_color,
+    );
+}
+
+/// A circle of radius `size` at `position` with three outward ticks.
+fn draw_maneuver_glyph(
+    navball_gizmos: &mut Gizmos<NavBallGizmos>,
+    position: Vec3,
+    size: f32,
+    color: Color,
+) {
+    navball_gizmos.circle(position, Direction3d::Z, size, color);
+    for tick in 0..3 {
+        let angle = std::f32::consts::FRAC_PI_2 + tick as f32 * std::f32::consts::TAU / 3.0;
+        let outward = Vec3::new(angle.cos(), angle.sin(), 0.0);
+        navball_gizmos.line(
+            position + outward * size,
+            position + outward * size * 1.6,
+            color,
+        );
+    }
+}
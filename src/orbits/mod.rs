@@ -0,0 +1,604 @@
+use std::f64::consts::{PI, TAU};
+
+use bevy::{
+    math::{DQuat, DVec3, Vec3A},
+    prelude::*,
+    render::primitives::{self, Frustum},
+};
+use big_space::{
+    reference_frame::{ReferenceFrame, RootReferenceFrame},
+    FloatingOrigin, GridCell,
+};
+
+use crate::{
+    input::{Action, KeyBindings},
+    time::{
+        advance_simulation_time, DeterministicSimulation, SimulationTime, SimulationTimePlugin,
+    },
+};
+
+/// Default number of line segments used to draw an orbit ring.
+pub const ORBIT_GIZMO_SEGMENTS: usize = 64;
+
+/// A circular orbit ring drawn around the entity's position.
+/// The orbital plane starts out as the entity's XZ plane, is tilted by `inclination` about the X axis (the line of nodes),
+/// and is then swung around the Y axis by `longitude_of_ascending_node`. Both angles are in radians.
+/// The ring is drawn with `segments` line segments; large rings need more to look round.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Orbit {
+    pub radius: f32,
+    pub base_color: Color,
+    pub inclination: f32,
+    pub longitude_of_ascending_node: f32,
+    pub segments: usize,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            radius: 0.0,
+            base_color: Color::default(),
+            inclination: 0.0,
+            longitude_of_ascending_node: 0.0,
+            segments: ORBIT_GIZMO_SEGMENTS,
+        }
+    }
+}
+
+impl Orbit {
+    /// Rotation taking the reference (XZ) plane into the orbital plane.
+    pub fn plane_rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.longitude_of_ascending_node)
+            * Quat::from_rotation_x(self.inclination)
+    }
+
+    /// Normal of the orbital plane, relative to the central body.
+    pub fn normal(&self) -> Vec3 {
+        self.plane_rotation() * Vec3::Y
+    }
+
+    /// Orbital period in seconds around a central body with gravitational parameter `mu` (m³/s²).
+    pub fn period(&self, mu: f64) -> Option<f64> {
+        Some(TAU * ((self.radius as f64).powi(3) / mu).sqrt())
+    }
+
+    pub fn apoapsis(&self) -> f64 {
+        self.radius as f64
+    }
+
+    pub fn periapsis(&self) -> f64 {
+        self.radius as f64
+    }
+}
+
+/// Fades orbit rings that would only add clutter. A ring starts fading once the camera is closer to it than
+/// `near_fade_start` times its radius and is hidden at `near_fade_end`, so the ring of a planet being looked at up close
+/// doesn't slice across the view. Likewise it fades once its angular radius (radius over distance to its center) drops
+/// below `far_fade_start` and is hidden at `far_fade_end`. With `cull_offscreen` rings entirely outside the camera's
+/// frustum aren't drawn at all.
+#[derive(Resource, Clone)]
+pub struct OrbitGizmoSettings {
+    pub near_fade_start: f32,
+    pub near_fade_end: f32,
+    pub far_fade_start: f32,
+    pub far_fade_end: f32,
+    pub cull_offscreen: bool,
+}
+
+impl Default for OrbitGizmoSettings {
+    fn default() -> Self {
+        Self {
+            near_fade_start: 0.05,
+            near_fade_end: 0.005,
+            far_fade_start: 0.01,
+            far_fade_end: 0.002,
+            cull_offscreen: true,
+        }
+    }
+}
+
+impl OrbitGizmoSettings {
+    /// Opacity, from 0 to 1, of a ring of `radius` centered on `center` with the given plane `normal`, seen from `eye`.
+    /// All positions are relative to the floating origin.
+    pub fn fade(&self, eye: Vec3, center: Vec3, normal: Vec3, radius: f32) -> f32 {
+        if radius <= 0.0 {
+            return 0.0;
+        }
+        let offset = eye - center;
+        let height = offset.dot(normal);
+        let in_plane = (offset - normal * height).length();
+        let distance_to_ring = (height * height + (in_plane - radius).powi(2)).sqrt();
+        let angular_radius = radius / offset.length().max(f32::EPSILON);
+        fade_between(
+            distance_to_ring / radius,
+            self.near_fade_end,
+            self.near_fade_start,
+        ) * fade_between(angular_radius, self.far_fade_end, self.far_fade_start)
+    }
+}
+
+/// 0 at or below `hidden`, 1 at or above `full`, linear in between.
+fn fade_between(value: f32, hidden: f32, full: f32) -> f32 {
+    if full <= hidden {
+        return if value >= full { 1.0 } else { 0.0 };
+    }
+    ((value - hidden) / (full - hidden)).clamp(0.0, 1.0)
+}
+
+pub struct OrbitGizmoPlugin;
+impl Plugin for OrbitGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OrbitGizmoSettings>()
+            .register_type::<Orbit>()
+            .add_systems(PostUpdate, update_orbit_gizmos);
+    }
+}
+
+pub fn update_orbit_gizmos(
+    global_transform_query: Query<&GlobalTransform>,
+    orbit_entity_query: Query<(Entity, &Orbit)>,
+    camera_query: Query<(&GlobalTransform, &Frustum), (With<Camera3d>, With<FloatingOrigin>)>,
+    settings: Res<OrbitGizmoSettings>,
+    mut default_gizmos: Gizmos,
+) {
+    let camera = camera_query.get_single().ok();
+    for (each_entity, each_orbit) in orbit_entity_query.iter() {
+        let Ok(transform) = global_transform_query.get(each_entity) else {
+            continue;
+        };
+        let (_scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let normal = rotation * each_orbit.normal();
+        let mut alpha = 1.0;
+        if let Some((camera_transform, frustum)) = camera {
+            if settings.cull_offscreen {
+                let bounds = primitives::Sphere {
+                    center: Vec3A::from(translation),
+                    radius: each_orbit.radius,
+                };
+                if !frustum.intersects_sphere(&bounds, false) {
+                    continue;
+                }
+            }
+            alpha = settings.fade(
+                camera_transform.translation(),
+                translation,
+                normal,
+                each_orbit.radius,
+            );
+            if alpha <= 0.0 {
+                continue;
+            }
+        }
+        let color = each_orbit
+            .base_color
+            .with_a(each_orbit.base_color.a() * alpha);
+        match Direction3d::new(normal) {
+            Ok(d) => {
+                default_gizmos
+                    .circle(translation, d, each_orbit.radius, color)
+                    .segments(each_orbit.segments);
+            }
+            Err(e) => error!("{:?}", e),
+        }
+    }
+}
+
+/// One astronomical unit in meters.
+pub const ASTRONOMICAL_UNIT_M: f64 = 1.495_978_707e11;
+
+/// Number of line segments used to draw a reference grid ring. Higher than `ORBIT_GIZMO_SEGMENTS` because the outer
+/// rings are much larger than any orbit.
+pub const REFERENCE_GRID_SEGMENTS: usize = 128;
+
+/// Concentric distance rings on the ecliptic (root XZ) plane. `center` is an absolute grid position, the Sun by
+/// default; a ring is drawn every `spacing` meters out to `extent`, and every `major_every`th ring uses `major_color`.
+#[derive(Resource, Clone)]
+pub struct ReferenceGridSettings {
+    pub visible: bool,
+    pub center: DVec3,
+    pub spacing: f64,
+    pub extent: f64,
+    pub major_every: u32,
+    pub color: Color,
+    pub major_color: Color,
+}
+
+impl Default for ReferenceGridSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            center: DVec3::ZERO,
+            spacing: ASTRONOMICAL_UNIT_M,
+            extent: 50.0 * ASTRONOMICAL_UNIT_M,
+            major_every: 5,
+            color: Color::rgba(0.5, 0.5, 0.5, 0.1),
+            major_color: Color::rgba(0.5, 0.5, 0.5, 0.3),
+        }
+    }
+}
+
+/// Draws the `ReferenceGridSettings` rings with the default gizmos, so they land on the same layer as the orbits.
+pub struct ReferenceGridPlugin;
+impl Plugin for ReferenceGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReferenceGridSettings>()
+            .add_systems(PostUpdate, update_reference_grid_gizmos);
+    }
+}
+
+pub fn update_reference_grid_gizmos(
+    settings: Res<ReferenceGridSettings>,
+    space: Res<RootReferenceFrame<i64>>,
+    floating_origin_query: Query<&GridCell<i64>, With<FloatingOrigin>>,
+    mut default_gizmos: Gizmos,
+) {
+    if !settings.visible || settings.spacing <= 0.0 {
+        return;
+    }
+    let Ok(origin_cell) = floating_origin_query.get_single() else {
+        return;
+    };
+    /* Subtract in f64 before narrowing so the rings stay put however far the origin has moved */
+    let origin_position = space.grid_position_double(origin_cell, &Transform::IDENTITY);
+    let center = (settings.center - origin_position).as_vec3();
+    let ring_count = (settings.extent / settings.spacing).floor() as u32;
+    for ring in 1..=ring_count {
+        let color = if settings.major_every > 0 && ring % settings.major_every == 0 {
+            settings.major_color
+        } else {
+            settings.color
+        };
+        default_gizmos
+            .circle(
+                center,
+                Direction3d::Y,
+                (ring as f64 * settings.spacing) as f32,
+                color,
+            )
+            .segments(REFERENCE_GRID_SEGMENTS);
+    }
+}
+
+/// X/Y/Z axis lines and an ecliptic ring at the root reference frame origin. Their length is `scale` times the
+/// camera's distance from the origin, so they keep roughly the same size on screen however far out the camera is.
+#[derive(Resource, Clone)]
+pub struct ReferenceAxesSettings {
+    pub visible: bool,
+    pub scale: f64,
+    pub ecliptic_color: Color,
+}
+
+impl Default for ReferenceAxesSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            scale: 0.05,
+            ecliptic_color: Color::rgba(1.0, 1.0, 0.0, 0.4),
+        }
+    }
+}
+
+/// Draws the origin axes with the default gizmos and toggles them on `Action::ToggleReferenceAxes`.
+pub struct ReferenceAxesPlugin;
+impl Plugin for ReferenceAxesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReferenceAxesSettings>()
+            .init_resource::<KeyBindings>()
+            .add_systems(Update, toggle_reference_axes)
+            .add_systems(PostUpdate, update_reference_axes_gizmos);
+    }
+}
+
+pub fn toggle_reference_axes(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut settings: ResMut<ReferenceAxesSettings>,
+) {
+    if key_bindings.just_pressed(&key, Action::ToggleReferenceAxes) {
+        settings.visible = !settings.visible;
+    }
+}
+
+pub fn update_reference_axes_gizmos(
+    settings: Res<ReferenceAxesSettings>,
+    space: Res<RootReferenceFrame<i64>>,
+    floating_origin_query: Query<(&GridCell<i64>, &Transform), With<FloatingOrigin>>,
+    mut default_gizmos: Gizmos,
+) {
+    if !settings.visible {
+        return;
+    }
+    let Ok((origin_cell, origin_transform)) = floating_origin_query.get_single() else {
+        return;
+    };
+    let camera_position = space.grid_position_double(origin_cell, origin_transform);
+    let length = (camera_position.length() * settings.scale) as f32;
+    if length <= 0.0 {
+        return;
+    }
+    let origin = (-space.grid_position_double(origin_cell, &Transform::IDENTITY)).as_vec3();
+    default_gizmos.line(origin, origin + Vec3::X * length, Color::RED);
+    default_gizmos.line(origin, origin + Vec3::Y * length, Color::GREEN);
+    default_gizmos.line(origin, origin + Vec3::Z * length, Color::BLUE);
+    default_gizmos
+        .circle(origin, Direction3d::Y, length, settings.ecliptic_color)
+        .segments(ORBIT_GIZMO_SEGMENTS);
+}
+
+/// Classical orbital elements for an elliptical (`eccentricity < 1.0`) orbit around a body at the origin of the
+/// root reference frame.
+/// Uses the same plane convention as `Orbit`: the reference plane is XZ with +Y as its normal and +X as the
+/// reference direction; `raan` swings the line of nodes around Y and `inclination` tilts the plane about it.
+/// Distances are in meters, angles in radians, and `period` in seconds.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct KeplerOrbit {
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub raan: f64,
+    pub arg_periapsis: f64,
+    pub mean_anomaly_at_epoch: f64,
+    pub period: f64,
+}
+
+impl KeplerOrbit {
+    /// Builds the orbit from a position and velocity relative to the central body, taken at the epoch, and the
+    /// central body's gravitational parameter `mu` (m³/s²).
+    /// Returns `None` for states that aren't an ellipse `position_at` can propagate: unbound trajectories
+    /// (`eccentricity >= 1.0`) and radial motion, which has no angular momentum and so no orbital plane.
+    /// Circular orbits have no periapsis, so `arg_periapsis` is 0 and the anomaly is measured from the ascending
+    /// node. Equatorial orbits have no ascending node, so `raan` is 0 and angles are measured from +X.
+    pub fn from_state_vectors(r: DVec3, v: DVec3, mu: f64) -> Option<KeplerOrbit> {
+        /* Work in a conventional frame: reference plane XY, normal +Z, reference direction +X */
+        let r = to_conventional_frame(r);
+        let v = to_conventional_frame(v);
+        let r_length = r.length();
+
+        let h = r.cross(v);
+        let h_length = h.length();
+        if h_length <= DEGENERATE_ORBIT_TOLERANCE * r_length * v.length() {
+            return None;
+        }
+        let h_unit = h / h_length;
+        let n = DVec3::Z.cross(h);
+        let n_length = n.length();
+        let e_vector = ((v.length_squared() - mu / r_length) * r - r.dot(v) * v) / mu;
+        let eccentricity = e_vector.length();
+        if eccentricity >= 1.0 {
+            return None;
+        }
+
+        let specific_energy = (v.length_squared() / 2.0) - (mu / r_length);
+        let semi_major_axis = -mu / (2.0 * specific_energy);
+        let inclination = (h.z / h_length).clamp(-1.0, 1.0).acos();
+
+        let circular = eccentricity < DEGENERATE_ORBIT_TOLERANCE;
+        let equatorial = n_length < DEGENERATE_ORBIT_TOLERANCE * h_length;
+
+        let raan = if equatorial {
+            0.0
+        } else {
+            n.y.atan2(n.x).rem_euclid(TAU)
+        };
+
+        /* Direction from which periapsis and the true anomaly are measured */
+        let node_direction = if equatorial { DVec3::X } else { n };
+        let (arg_periapsis, true_anomaly) = if circular {
+            (0.0, signed_angle(node_direction, r, h_unit))
+        } else {
+            (
+                signed_angle(node_direction, e_vector, h_unit),
+                signed_angle(e_vector, r, h_unit),
+            )
+        };
+
+        let eccentric_anomaly = ((1.0 - eccentricity * eccentricity).sqrt() * true_anomaly.sin())
+            .atan2(eccentricity + true_anomaly.cos());
+        let mean_anomaly_at_epoch =
+            (eccentric_anomaly - eccentricity * eccentric_anomaly.sin()).rem_euclid(TAU);
+        let mut orbit = KeplerOrbit {
+            semi_major_axis,
+            eccentricity,
+            inclination,
+            raan,
+            arg_periapsis,
+            mean_anomaly_at_epoch,
+            period: 0.0,
+        };
+        orbit.period = orbit.orbital_period(mu)?;
+        Some(orbit)
+    }
+
+    /// Period in seconds implied by the semi-major axis and the central body's gravitational parameter `mu` (m³/s²),
+    /// for checking or filling in the `period` field. Parabolic and hyperbolic trajectories never repeat, so they
+    /// have no period.
+    pub fn orbital_period(&self, mu: f64) -> Option<f64> {
+        if self.eccentricity >= 1.0 {
+            None
+        } else {
+            Some(TAU * (self.semi_major_axis.powi(3) / mu).sqrt())
+        }
+    }
+
+    /// Farthest distance from the central body, in meters. Unbound trajectories have no apoapsis.
+    pub fn apoapsis(&self) -> f64 {
+        if self.eccentricity >= 1.0 {
+            f64::INFINITY
+        } else {
+            self.semi_major_axis * (1.0 + self.eccentricity)
+        }
+    }
+
+    /// Closest distance to the central body, in meters.
+    pub fn periapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    /// Sphere-of-influence radius for a body of `body_mass` on this orbit around a parent of `parent_mass`.
+    pub fn sphere_of_influence(&self, body_mass: f64, parent_mass: f64) -> f64 {
+        crate::gravity::sphere_of_influence_radius(self.semi_major_axis, body_mass, parent_mass)
+    }
+
+    pub fn mean_anomaly_at(&self, seconds_since_epoch: f64) -> f64 {
+        (self.mean_anomaly_at_epoch + (TAU * seconds_since_epoch / self.period)).rem_euclid(TAU)
+    }
+
+    /// Position relative to the central body, `seconds_since_epoch` after the epoch.
+    pub fn position_at(&self, seconds_since_epoch: f64) -> DVec3 {
+        let eccentric_anomaly =
+            solve_kepler(self.mean_anomaly_at(seconds_since_epoch), self.eccentricity);
+        let x = self.semi_major_axis * (eccentric_anomaly.cos() - self.eccentricity);
+        let y = self.semi_major_axis
+            * (1.0 - self.eccentricity * self.eccentricity).sqrt()
+            * eccentric_anomaly.sin();
+        self.perifocal_rotation() * DVec3::new(x, 0.0, -y)
+    }
+
+    /// Rotation taking the perifocal frame (periapsis along +X, orbit normal along +Y) into the reference frame.
+    pub fn perifocal_rotation(&self) -> DQuat {
+        DQuat::from_rotation_y(self.raan)
+            * DQuat::from_rotation_x(self.inclination)
+            * DQuat::from_rotation_y(self.arg_periapsis)
+    }
+}
+
+/// Solves Kepler's equation `M = E - e * sin(E)` for the eccentric anomaly `E` using Newton's method.
+pub fn solve_kepler(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let mut eccentric_anomaly = if eccentricity < 0.8 { mean_anomaly } else { PI };
+    for _ in 0..KEPLER_SOLVER_MAX_ITERATIONS {
+        let delta = (eccentric_anomaly - eccentricity * eccentric_anomaly.sin() - mean_anomaly)
+            / (1.0 - eccentricity * eccentric_anomaly.cos());
+        eccentric_anomaly -= delta;
+        if delta.abs() < KEPLER_SOLVER_TOLERANCE {
+            break;
+        }
+    }
+    eccentric_anomaly
+}
+
+/// Eccentricities and node vector lengths (relative to the angular momentum) below this are treated as zero.
+const DEGENERATE_ORBIT_TOLERANCE: f64 = 1e-9;
+
+/// Maps this crate's Y-up reference frame onto the XY-plane/Z-normal frame the textbook formulas are written in.
+fn to_conventional_frame(v: DVec3) -> DVec3 {
+    DVec3::new(v.x, -v.z, v.y)
+}
+
+/// Angle from `from` to `to`, measured counterclockwise around `normal`, in `0..TAU`.
+fn signed_angle(from: DVec3, to: DVec3, normal: DVec3) -> f64 {
+    from.cross(to)
+        .dot(normal)
+        .atan2(from.dot(to))
+        .rem_euclid(TAU)
+}
+
+const KEPLER_SOLVER_MAX_ITERATIONS: usize = 32;
+const KEPLER_SOLVER_TOLERANCE: f64 = 1e-12;
+
+pub struct KeplerOrbitPlugin;
+impl Plugin for KeplerOrbitPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SimulationTimePlugin>() {
+            app.add_plugins(SimulationTimePlugin);
+        }
+        app.add_systems(
+            Update,
+            propagate_kepler_orbits.run_if(not(resource_exists::<DeterministicSimulation>)),
+        )
+        .add_systems(
+            FixedUpdate,
+            propagate_kepler_orbits
+                .after(advance_simulation_time)
+                .run_if(resource_exists::<DeterministicSimulation>),
+        );
+    }
+}
+
+/// Moves every `KeplerOrbit` body to its position at the current `SimulationTime`, so orbits follow the time scale
+/// and stand still while paused.
+/// The position is computed in f64 and only then split into a `GridCell` and a small `Transform` translation, using
+/// the parent's `ReferenceFrame` for satellites and the root frame for everything else.
+pub fn propagate_kepler_orbits(
+    mut orbit_query: Query<(
+        &KeplerOrbit,
+        &mut GridCell<i64>,
+        &mut Transform,
+        Option<&Parent>,
+    )>,
+    frame_query: Query<&ReferenceFrame<i64>>,
+    space: Res<RootReferenceFrame<i64>>,
+    simulation_time: Res<SimulationTime>,
+) {
+    let seconds_since_epoch = simulation_time.elapsed_s;
+    for (each_orbit, mut each_cell, mut each_transform, each_parent) in orbit_query.iter_mut() {
+        let position = each_orbit.position_at(seconds_since_epoch);
+        let (cell, translation) =
+            match each_parent.and_then(|parent| frame_query.get(parent.get()).ok()) {
+                Some(frame) => frame.translation_to_grid(position),
+                None => space.translation_to_grid(position),
+            };
+        *each_cell = cell;
+        each_transform.translation = translation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_returns_to_start_after_one_period() {
+        let orbit = KeplerOrbit {
+            semi_major_axis: 7.0e6,
+            eccentricity: 0.3,
+            inclination: 0.4,
+            raan: 1.1,
+            arg_periapsis: 2.5,
+            mean_anomaly_at_epoch: 0.7,
+            period: 5_800.0,
+        };
+        let start = orbit.position_at(0.0);
+        let halfway = orbit.position_at(orbit.period / 2.0);
+        let end = orbit.position_at(orbit.period);
+        assert!(start.distance(halfway) > 1.0e6);
+        assert!(start.distance(end) < 1.0e-3, "{start} vs {end}");
+    }
+
+    #[test]
+    fn state_vectors_round_trip_through_position_at() {
+        let mu = 3.986e14;
+        let r = DVec3::new(6.0e6, 1.5e6, -2.0e6);
+        let v = DVec3::new(1.0e3, 2.0e3, 7.0e3);
+        let orbit = KeplerOrbit::from_state_vectors(r, v, mu).unwrap();
+        assert!(orbit.position_at(0.0).distance(r) < 1.0e-2);
+    }
+
+    #[test]
+    fn unbound_state_vectors_are_rejected() {
+        assert!(KeplerOrbit::from_state_vectors(DVec3::X, DVec3::Z * 1.5, 1.0).is_none());
+        assert!(KeplerOrbit::from_state_vectors(DVec3::X, DVec3::Z * 3.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn radial_state_vectors_are_rejected() {
+        assert!(KeplerOrbit::from_state_vectors(DVec3::X, DVec3::X * 0.5, 1.0).is_none());
+        assert!(KeplerOrbit::from_state_vectors(DVec3::X, DVec3::ZERO, 1.0).is_none());
+    }
+
+    #[test]
+    fn orbital_period_is_none_when_unbound() {
+        let orbit = |eccentricity| KeplerOrbit {
+            semi_major_axis: 1.0,
+            eccentricity,
+            inclination: 0.0,
+            raan: 0.0,
+            arg_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            period: 0.0,
+        };
+        assert_eq!(orbit(0.5).orbital_period(1.0), Some(TAU));
+        assert_eq!(orbit(1.0).orbital_period(1.0), None);
+        assert_eq!(orbit(2.5).orbital_period(1.0), None);
+    }
+}
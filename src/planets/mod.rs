@@ -0,0 +1,489 @@
+use std::{f64::consts::PI, time::Duration};
+
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        view::RenderLayers,
+    },
+    utils::HashMap,
+};
+use big_space::{
+    reference_frame::{ReferenceFrame, RootReferenceFrame},
+    GridCell,
+};
+
+use crate::{
+    gravity::SphereOfInfluence,
+    grid::spawn_at,
+    orbits::{KeplerOrbit, Orbit, ORBIT_GIZMO_SEGMENTS},
+    targeting::{ComponentInfo, TargetClass, ValidTarget},
+    time::{SimulationTime, SimulationTimePlugin},
+};
+
+pub const SECONDS_PER_DAY: f64 = 86_400.0;
+pub const SUN_MASS_KG: f64 = 1.98847e30;
+
+/// Physical constants for a single body orbiting the Sun.
+#[derive(Clone, Debug)]
+pub struct BodyData {
+    pub name: &'static str,
+    pub radius_m: f32,
+    pub orbit_radius_m: f32,
+    pub orbit_period_s: f64,
+    pub inclination: f32,
+    pub longitude_of_ascending_node: f32,
+    pub base_color: Color,
+    pub mass_kg: f64,
+    /// Sidereal rotation period in seconds.
+    pub rotation_period_s: f64,
+    /// Angle in radians between the spin axis and the orbit normal, see `AxialTilt`. Beyond 90° the body spins
+    /// retrograde, as Venus and Uranus do.
+    pub axial_tilt: f32,
+}
+
+/// Table of the bodies spawned by `spawn_solar_system`. Defaults to the eight planets.
+#[derive(Resource, Clone, Debug)]
+pub struct PlanetData {
+    pub bodies: Vec<BodyData>,
+}
+
+impl Default for PlanetData {
+    fn default() -> Self {
+        Self {
+            bodies: vec![
+                BodyData {
+                    name: "Mercury",
+                    radius_m: 2.4397e6,
+                    orbit_radius_m: 57.91e9,
+                    orbit_period_s: 87.969 * SECONDS_PER_DAY,
+                    inclination: 7.0_f32.to_radians(),
+                    longitude_of_ascending_node: 48.3_f32.to_radians(),
+                    base_color: Color::DARK_GRAY,
+                    mass_kg: 3.3011e23,
+                    rotation_period_s: 58.646 * SECONDS_PER_DAY,
+                    axial_tilt: 0.03_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Venus",
+                    radius_m: 6.0518e6,
+                    orbit_radius_m: 108.21e9,
+                    orbit_period_s: 224.701 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::ORANGE,
+                    mass_kg: 4.8675e24,
+                    rotation_period_s: 243.025 * SECONDS_PER_DAY,
+                    axial_tilt: 177.36_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Earth",
+                    radius_m: 6.371e6,
+                    orbit_radius_m: 149.60e9,
+                    orbit_period_s: 365.256 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::BLUE,
+                    mass_kg: 5.9724e24,
+                    rotation_period_s: 0.99727 * SECONDS_PER_DAY,
+                    axial_tilt: 23.44_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Mars",
+                    radius_m: 3.3962e6,
+                    orbit_radius_m: 228.6e9,
+                    orbit_period_s: 686.98 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::RED,
+                    mass_kg: 6.4171e23,
+                    rotation_period_s: 1.02596 * SECONDS_PER_DAY,
+                    axial_tilt: 25.19_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Jupiter",
+                    radius_m: 71.492e6,
+                    orbit_radius_m: 778.479e9,
+                    orbit_period_s: 4332.59 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::BEIGE,
+                    mass_kg: 1.8982e27,
+                    rotation_period_s: 0.41354 * SECONDS_PER_DAY,
+                    axial_tilt: 3.13_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Saturn",
+                    radius_m: 58.232e6,
+                    orbit_radius_m: 1433.525e9,
+                    orbit_period_s: 10759.22 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::BEIGE,
+                    mass_kg: 5.6834e26,
+                    rotation_period_s: 0.44401 * SECONDS_PER_DAY,
+                    axial_tilt: 26.73_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Uranus",
+                    radius_m: 25.559e6,
+                    orbit_radius_m: 2870.975e9,
+                    orbit_period_s: 30688.5 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::CYAN,
+                    mass_kg: 8.6810e25,
+                    rotation_period_s: 0.71833 * SECONDS_PER_DAY,
+                    axial_tilt: 97.77_f32.to_radians(),
+                },
+                BodyData {
+                    name: "Neptune",
+                    radius_m: 24.764e6,
+                    orbit_radius_m: 4500e9,
+                    orbit_period_s: 60195.0 * SECONDS_PER_DAY,
+                    inclination: 0.0,
+                    longitude_of_ascending_node: 0.0,
+                    base_color: Color::BLUE,
+                    mass_kg: 1.02413e26,
+                    rotation_period_s: 0.67125 * SECONDS_PER_DAY,
+                    axial_tilt: 28.32_f32.to_radians(),
+                },
+            ],
+        }
+    }
+}
+
+impl BodyData {
+    /// Circular orbit starting on the +Z side of the Sun, rotated into the body's orbital plane.
+    pub fn kepler_orbit(&self) -> KeplerOrbit {
+        KeplerOrbit {
+            semi_major_axis: self.orbit_radius_m as f64,
+            eccentricity: 0.0,
+            inclination: self.inclination as f64,
+            raan: self.longitude_of_ascending_node as f64,
+            arg_periapsis: 0.0,
+            mean_anomaly_at_epoch: 1.5 * PI,
+            period: self.orbit_period_s,
+        }
+    }
+
+    pub fn sphere_of_influence(&self) -> SphereOfInfluence {
+        SphereOfInfluence {
+            radius_m: self
+                .kepler_orbit()
+                .sphere_of_influence(self.mass_kg, SUN_MASS_KG),
+        }
+    }
+
+    pub fn axial_spin(&self) -> AxialSpin {
+        AxialSpin {
+            axis: Vec3::Y,
+            period: Duration::from_secs_f64(self.rotation_period_s),
+        }
+    }
+
+    pub fn orbit(&self) -> Orbit {
+        Orbit {
+            radius: self.orbit_radius_m,
+            base_color: self.base_color,
+            inclination: self.inclination,
+            longitude_of_ascending_node: self.longitude_of_ascending_node,
+            segments: ORBIT_GIZMO_SEGMENTS,
+        }
+    }
+}
+
+/// Obliquity in radians: the body's Y axis is tilted this far about X when the component is added. Children such as
+/// rings and moons inherit the tilt through the hierarchy.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AxialTilt(pub f32);
+
+/// Turns the entity once every `period` of `SimulationTime` about `axis`, given in its own (tilted) space, so it
+/// follows the `TimeScale` and stops while physics is paused.
+#[derive(Component, Clone, Debug)]
+pub struct AxialSpin {
+    pub axis: Vec3,
+    pub period: Duration,
+}
+
+pub struct AxialSpinPlugin;
+impl Plugin for AxialSpinPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SimulationTimePlugin>() {
+            app.add_plugins(SimulationTimePlugin);
+        }
+        app.add_systems(Update, (apply_axial_tilt, spin_bodies).chain());
+    }
+}
+
+pub fn apply_axial_tilt(mut tilt_query: Query<(&AxialTilt, &mut Transform), Added<AxialTilt>>) {
+    for (tilt, mut transform) in tilt_query.iter_mut() {
+        transform.rotation = Quat::from_rotation_x(tilt.0) * transform.rotation;
+    }
+}
+
+pub fn spin_bodies(
+    mut spin_query: Query<(&AxialSpin, &mut Transform)>,
+    simulation_time: Res<SimulationTime>,
+    mut last_elapsed_s: Local<f64>,
+) {
+    let elapsed = simulation_time.elapsed_s - *last_elapsed_s;
+    *last_elapsed_s = simulation_time.elapsed_s;
+    for (spin, mut transform) in spin_query.iter_mut() {
+        let (Some(axis), false) = (spin.axis.try_normalize(), spin.period.is_zero()) else {
+            continue;
+        };
+        let angle = (std::f64::consts::TAU * elapsed / spin.period.as_secs_f64()) as f32;
+        /* Renormalize so rounding in the per frame increments can't build up */
+        transform.rotation = (transform.rotation * Quat::from_axis_angle(axis, angle)).normalize();
+    }
+}
+
+/// Spawns every body in `planet_data` along with its orbit ring, returning the body entities keyed by name.
+pub fn spawn_solar_system(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    space: &RootReferenceFrame<i64>,
+    planet_data: &PlanetData,
+    render_layers: RenderLayers,
+) -> HashMap<String, Entity> {
+    let mut spawned = HashMap::new();
+    for each_body in planet_data.bodies.iter() {
+        let material = materials.add(StandardMaterial {
+            base_color: each_body.base_color,
+            perceptual_roughness: 0.8,
+            reflectance: 1.0,
+            ..default()
+        });
+        let mesh = meshes.add(Sphere::new(each_body.radius_m).mesh().ico(16).unwrap());
+        let kepler_orbit = each_body.kepler_orbit();
+        let entity = spawn_at(
+            commands,
+            space,
+            kepler_orbit.position_at(0.0),
+            (
+                ComponentInfo {
+                    name: each_body.name.to_string(),
+                    size: each_body.radius_m,
+                },
+                render_layers,
+                ValidTarget,
+                TargetClass::Planet,
+                SpatialBundle::default(),
+                kepler_orbit,
+                each_body.sphere_of_influence(),
+                AxialTilt(each_body.axial_tilt),
+            ),
+        )
+        .with_children(|parent| {
+            /* Only the surface spins, so rings and satellites parented to the body keep their orientation */
+            parent.spawn((
+                render_layers,
+                PbrBundle {
+                    mesh,
+                    material,
+                    ..default()
+                },
+                each_body.axial_spin(),
+            ));
+        })
+        .id();
+        commands.spawn((
+            render_layers,
+            each_body.orbit(),
+            Transform::IDENTITY,
+            GlobalTransform::IDENTITY,
+            GridCell::<i64>::ZERO,
+        ));
+        spawned.insert(each_body.name.to_string(), entity);
+    }
+    spawned
+}
+
+/// Spawns a body following `orbit` around `parent`, such as a moon around its planet. The satellite lives in a
+/// `ReferenceFrame` nested in the parent, which is added to the parent if it has none, so its position stays precise
+/// however far the parent is from the origin and it follows the parent along its own orbit. The orbit is expressed in
+/// the parent's axes, so an `AxialTilt` on the parent tilts it too.
+/// Returns the satellite's `EntityCommands` so callers can add a mesh, `ComponentInfo` and so on.
+pub fn spawn_satellite<'a>(
+    commands: &'a mut Commands,
+    parent: Entity,
+    orbit: KeplerOrbit,
+) -> EntityCommands<'a> {
+    commands.entity(parent).add(|mut parent: EntityWorldMut| {
+        if !parent.contains::<ReferenceFrame<i64>>() {
+            parent.insert(ReferenceFrame::<i64>::default());
+        }
+    });
+    let mut entity_commands =
+        commands.spawn((orbit, GridCell::<i64>::ZERO, SpatialBundle::default()));
+    entity_commands.set_parent(parent);
+    entity_commands
+}
+
+/// Number of segments around a ring mesh built by `annulus_mesh`.
+pub const RING_MESH_RESOLUTION: usize = 128;
+
+/// A flat ring system around a body, such as Saturn's. The rings lie in the body's XZ (equatorial) plane, tilted a
+/// further `tilt` radians about its X axis; the body's own `AxialTilt` already carries over to them.
+#[derive(Clone, Debug)]
+pub struct RingData {
+    pub inner_radius_m: f32,
+    pub outer_radius_m: f32,
+    pub tilt: f32,
+    pub base_color: Color,
+}
+
+/// A flat ring in the XZ plane facing +Y, from `inner_radius` to `outer_radius`, split into `resolution` segments.
+/// The U texture coordinate runs from the inner edge to the outer one and V once around the ring.
+pub fn annulus_mesh(inner_radius: f32, outer_radius: f32, resolution: usize) -> Mesh {
+    let resolution = resolution.max(3);
+    let mut positions = Vec::with_capacity((resolution + 1) * 2);
+    let mut uvs = Vec::with_capacity((resolution + 1) * 2);
+    let mut indices = Vec::with_capacity(resolution * 6);
+    /* The seam repeats its first pair of vertices so V can wrap from 0 to 1 */
+    for step in 0..=resolution {
+        let fraction = step as f32 / resolution as f32;
+        let (sin, cos) = (fraction * std::f32::consts::TAU).sin_cos();
+        positions.push([cos * inner_radius, 0.0, sin * inner_radius]);
+        positions.push([cos * outer_radius, 0.0, sin * outer_radius]);
+        uvs.push([0.0, fraction]);
+        uvs.push([1.0, fraction]);
+    }
+    for step in 0..resolution as u32 {
+        let inner = step * 2;
+        let (outer, next_inner, next_outer) = (inner + 1, inner + 2, inner + 3);
+        indices.extend_from_slice(&[inner, next_inner, outer, outer, next_inner, next_outer]);
+    }
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Spawns `rings` as a double sided child of `parent`, so they share the body's grid cell and follow it along its
+/// orbit. Returns the ring entity.
+pub fn spawn_planetary_rings(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    parent: Entity,
+    rings: &RingData,
+    render_layers: RenderLayers,
+) -> Entity {
+    let material = materials.add(StandardMaterial {
+        base_color: rings.base_color,
+        perceptual_roughness: 0.8,
+        reflectance: 1.0,
+        cull_mode: None,
+        double_sided: true,
+        ..default()
+    });
+    let mesh = meshes.add(annulus_mesh(
+        rings.inner_radius_m,
+        rings.outer_radius_m,
+        RING_MESH_RESOLUTION,
+    ));
+    let rings_entity = commands
+        .spawn((
+            render_layers,
+            PbrBundle {
+                mesh,
+                material,
+                transform: Transform::from_rotation(Quat::from_rotation_x(rings.tilt)),
+                ..default()
+            },
+        ))
+        .id();
+    commands.entity(parent).add_child(rings_entity);
+    rings_entity
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+    use big_space::{FloatingOrigin, FloatingOriginPlugin};
+
+    use super::*;
+    use crate::orbits::KeplerOrbitPlugin;
+
+    fn circular_orbit(radius: f64, period: f64, inclination: f64) -> KeplerOrbit {
+        KeplerOrbit {
+            semi_major_axis: radius,
+            eccentricity: 0.0,
+            inclination,
+            raan: 0.0,
+            arg_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            period,
+        }
+    }
+
+    #[test]
+    fn satellite_tracks_its_parent() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            FloatingOriginPlugin::<i64>::default(),
+            KeplerOrbitPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(700)));
+        app.world.spawn((
+            FloatingOrigin,
+            GridCell::<i64>::ZERO,
+            SpatialBundle::default(),
+        ));
+        let planet_orbit = circular_orbit(1.0e6, 10_000.0, 0.0);
+        let moon_orbit = circular_orbit(1.0e4, 3_000.0, 0.5);
+        let planet = app
+            .world
+            .spawn((
+                planet_orbit,
+                GridCell::<i64>::ZERO,
+                SpatialBundle::default(),
+            ))
+            .id();
+        let moon = {
+            let mut command_queue = bevy::ecs::system::CommandQueue::default();
+            let mut commands = Commands::new(&mut command_queue, &app.world);
+            let moon = spawn_satellite(&mut commands, planet, moon_orbit).id();
+            command_queue.apply(&mut app.world);
+            moon
+        };
+
+        let mut planet_positions = Vec::new();
+        for _ in 0..4 {
+            app.update();
+            let elapsed_s = app.world.resource::<SimulationTime>().elapsed_s;
+            let planet_position = app
+                .world
+                .get::<GlobalTransform>(planet)
+                .unwrap()
+                .translation();
+            let moon_position = app
+                .world
+                .get::<GlobalTransform>(moon)
+                .unwrap()
+                .translation();
+            let expected_offset = moon_orbit.position_at(elapsed_s).as_vec3();
+            assert!(
+                (moon_position - planet_position).distance(expected_offset) < 1.0,
+                "at {elapsed_s} s the moon is {} from its parent, expected {expected_offset}",
+                moon_position - planet_position
+            );
+            planet_positions.push(planet_position);
+        }
+        /* The parent really did move, so the moon followed it rather than staying put */
+        assert!(planet_positions[0].distance(planet_positions[3]) > 1.0e5);
+    }
+}
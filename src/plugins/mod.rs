@@ -0,0 +1,30 @@
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+use crate::{
+    crosshair::CrosshairPlugin,
+    hud::DiagnosticsOverlayPlugin,
+    navball::NavBallPlugin,
+    orbits::OrbitGizmoPlugin,
+    reset::ResetScenePlugin,
+    targeting::{NearestObjectPlugin, TargetingModePlugin, TargetingTypesPlugin},
+    time::TimeScalePlugin,
+};
+
+/// The crate's core plugins with their default settings. Like Bevy's `DefaultPlugins`, members can be left out with
+/// `SpaceProgramPlugins.build().disable::<NavBallPlugin>()`, and settings resources inserted after it still apply.
+/// `TimeScalePlugin` drives Rapier, so apps without `RapierPhysicsPlugin` should disable it.
+pub struct SpaceProgramPlugins;
+impl PluginGroup for SpaceProgramPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(TargetingTypesPlugin)
+            .add(CrosshairPlugin)
+            .add(NearestObjectPlugin)
+            .add(TargetingModePlugin)
+            .add(NavBallPlugin)
+            .add(OrbitGizmoPlugin)
+            .add(DiagnosticsOverlayPlugin)
+            .add(TimeScalePlugin)
+            .add(ResetScenePlugin)
+    }
+}
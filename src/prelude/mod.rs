@@ -0,0 +1,38 @@
+pub use crate::{
+    camera::{
+        default_space_camera, default_space_projection, AutoFocus, AutopilotMode, AutopilotPlugin,
+        AutopilotSettings, CameraBookmarksPlugin, CameraFocusPlugin, CameraFocusSettings,
+        CameraKeyScheme, CameraKeySchemePlugin, FlyToPose, FlyToTarget, InertialDampingPlugin,
+        InertialDampingSettings, LevelHorizon, OrbitCameraMode, OrbitCameraPlugin,
+    },
+    crosshair::{
+        spawn_crosshair, CrosshairFollow, CrosshairId, CrosshairIds, CrosshairPlugin, CrosshairType,
+    },
+    gravity::{Attracted, GravityAttractor, GravityPlugin, NBody, NBodyPlugin},
+    hud::{ClosestApproachReadoutPlugin, DiagnosticsOverlayPlugin, TargetingModeIndicatorPlugin},
+    input::{
+        Action, CursorLock, CursorLockPlugin, KeyBindings, QuitConfirm, QuitConfirmPlugin,
+        SelectInputPlugin, SelectInputSettings, SelectTargetPressed,
+    },
+    layers::LayerConfig,
+    lighting::{LightingPlugin, LightingSettings, StarLightPlugin},
+    maneuver::{BurnExecuted, ManeuverPlugin, ScheduledBurn},
+    mipmap::{MipmapGeneratorPlugin, MipmapGeneratorSettings},
+    navball::{NavBallPlugin, NavBallSettings},
+    orbits::{KeplerOrbit, KeplerOrbitPlugin, Orbit, OrbitGizmoPlugin, ReferenceAxesPlugin},
+    plugins::SpaceProgramPlugins,
+    projectile::{spawn_projectile, Projectile, ProjectileHit, ProjectilePlugin, ProjectileSpec},
+    reset::{ResetScene, ResetScenePlugin, ResetToInitial, Respawnable},
+    reticle::{ReticlePlugin, ReticleSettings},
+    skybox::{set_skybox, CubemapSkybox, SkyboxPlugin},
+    targeting::{
+        ComponentInfo, CurrentTarget, NearestObject, NearestObjectPlugin, TargetClass,
+        TargetSelected, TargetingMode, TargetingModeChanged, TargetingModePlugin,
+        TargetingSettings, TargetingTypesPlugin, ValidTarget,
+    },
+    time::{
+        DeterministicPlugin, DeterministicSimulation, PhysicsPause, SimulationTime,
+        SimulationTimePlugin, TimeScale, TimeScalePlugin,
+    },
+    trajectory::{ClosestApproach, PredictedTrajectory, TrajectoryPlugin, TrajectorySettings},
+};
@@ -0,0 +1,193 @@
+use bevy::{ecs::system::EntityCommands, math::DVec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+use big_space::{reference_frame::RootReferenceFrame, FloatingOrigin, GridCell};
+
+use crate::{gravity::Attracted, reset::Respawnable};
+
+/// Marks an entity spawned by `spawn_projectile`. Its collisions are reported as `ProjectileHit` events, and it is
+/// `Respawnable`, so `ResetScene` clears it away.
+#[derive(Component, Debug)]
+pub struct Projectile {
+    pub despawn_on_hit: bool,
+}
+
+/// Sent when a projectile starts touching another collider. `contact_point` is an absolute grid position in meters.
+#[derive(Event, Debug)]
+pub struct ProjectileHit {
+    pub projectile: Entity,
+    pub target: Entity,
+    pub contact_point: DVec3,
+}
+
+/// Despawns the entity, and its children, once `timer` finishes.
+#[derive(Component, Debug)]
+pub struct Lifetime {
+    pub timer: Timer,
+}
+
+impl Lifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+}
+
+/// What to fire. `muzzle_velocity` (m/s) and `muzzle_offset` (m) are along the origin's forward direction, `spin` is
+/// the initial angular velocity, and `lifetime` (s) is how long the projectile lives, forever when `None`.
+/// `mass` (kg) overrides the mass Rapier derives from the collider's volume.
+/// With `despawn_on_hit` the projectile is removed by the first collision it reports.
+/// As a resource it is the player's weapon; the default is experiment_002's pellet.
+#[derive(Resource, Clone, Debug)]
+pub struct ProjectileSpec {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    pub collider: Collider,
+    pub mass: Option<f32>,
+    pub muzzle_velocity: f32,
+    pub muzzle_offset: f32,
+    pub spin: Vec3,
+    pub lifetime: Option<f32>,
+    pub despawn_on_hit: bool,
+}
+
+impl FromWorld for ProjectileSpec {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Torus::new(0.01, 0.03));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::PURPLE,
+                perceptual_roughness: 0.8,
+                reflectance: 1.0,
+                ..default()
+            });
+        Self {
+            mesh,
+            material,
+            collider: Collider::capsule(Vec3::new(0.0, 0.1, 0.0), Vec3::new(0.0, -0.1, 0.0), 0.1),
+            mass: None,
+            muzzle_velocity: 20.0,
+            muzzle_offset: 0.125,
+            spin: Vec3::new(2.1, 2.2, 2.3),
+            lifetime: Some(30.0),
+            despawn_on_hit: false,
+        }
+    }
+}
+
+pub struct ProjectilePlugin;
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProjectileSpec>()
+            .add_event::<ProjectileHit>()
+            .add_systems(Update, (despawn_expired, register_projectile_hits));
+    }
+}
+
+/// Spawns a gravity-attracted rigid body just ahead of `origin_transform`, moving at `origin_velocity` plus the
+/// spec's muzzle velocity. Returns the projectile's `EntityCommands` so callers can add their own components.
+pub fn spawn_projectile<'a>(
+    commands: &'a mut Commands,
+    origin_cell: GridCell<i64>,
+    origin_transform: &Transform,
+    origin_velocity: Vec3,
+    spec: &ProjectileSpec,
+) -> EntityCommands<'a> {
+    let forward = origin_transform.forward();
+    let transform = Transform {
+        translation: origin_transform.translation + forward * spec.muzzle_offset,
+        rotation: origin_transform.rotation,
+        ..default()
+    };
+    let velocity = Velocity {
+        linvel: origin_velocity + forward * spec.muzzle_velocity,
+        angvel: spec.spin,
+    };
+    let mut entity_commands = commands.spawn((
+        Projectile {
+            despawn_on_hit: spec.despawn_on_hit,
+        },
+        Respawnable,
+        origin_cell,
+        RigidBody::Dynamic,
+        spec.collider.clone(),
+        ActiveEvents::COLLISION_EVENTS,
+        GravityScale(0.0),
+        Attracted,
+        ExternalForce::default(),
+        ReadMassProperties::default(),
+        velocity,
+        PbrBundle {
+            mesh: spec.mesh.clone(),
+            material: spec.material.clone(),
+            transform,
+            ..default()
+        },
+    ));
+    if let Some(mass) = spec.mass {
+        entity_commands.insert(ColliderMassProperties::Mass(mass));
+    }
+    if let Some(seconds) = spec.lifetime {
+        entity_commands.insert(Lifetime::from_seconds(seconds));
+    }
+    entity_commands
+}
+
+pub fn despawn_expired(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lifetime_query: Query<(Entity, &mut Lifetime)>,
+) {
+    for (entity, mut lifetime) in lifetime_query.iter_mut() {
+        if lifetime.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn register_projectile_hits(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut hit_events: EventWriter<ProjectileHit>,
+    rapier_context: Res<RapierContext>,
+    space: Res<RootReferenceFrame<i64>>,
+    projectile_query: Query<(&Projectile, &GlobalTransform)>,
+    floating_origin_query: Query<&GridCell<i64>, With<FloatingOrigin>>,
+) {
+    let Ok(origin_cell) = floating_origin_query.get_single() else {
+        return;
+    };
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity_1, entity_2, _) = *collision_event else {
+            continue;
+        };
+        for (projectile, target) in [(entity_1, entity_2), (entity_2, entity_1)] {
+            let Ok((projectile_info, global_transform)) = projectile_query.get(projectile) else {
+                continue;
+            };
+            /* Rapier works in floating origin relative coordinates; fall back to the projectile's own position */
+            let point = rapier_context
+                .contact_pair(projectile, target)
+                .and_then(|pair| {
+                    pair.manifolds()
+                        .find_map(|manifold| manifold.solver_contacts().next())
+                        .map(|contact| contact.point())
+                })
+                .unwrap_or_else(|| global_transform.translation());
+            let contact_point =
+                space.grid_position_double(origin_cell, &Transform::from_translation(point));
+            debug!("{:?} hit {:?} at {:?}", projectile, target, contact_point);
+            hit_events.send(ProjectileHit {
+                projectile,
+                target,
+                contact_point,
+            });
+            if projectile_info.despawn_on_hit {
+                commands.entity(projectile).despawn_recursive();
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::{PhysicsSet, Velocity};
+use big_space::GridCell;
+
+use crate::{
+    input::{Action, KeyBindings},
+    targeting::CurrentTarget,
+};
+
+/// Despawned by `ResetScene`. For bodies created while the scene runs, such as projectiles and extra vessels.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Respawnable;
+
+/// Put back where it was spawned by `ResetScene`: its transform, grid cell and velocity are recorded the frame it
+/// appears and restored on reset. Entities with neither this nor `Respawnable`, such as the camera and static bodies,
+/// are left alone.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ResetToInitial;
+
+/// What `ResetToInitial` restores. Recorded by `record_initial_state`.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct InitialState {
+    pub transform: Transform,
+    pub cell: Option<GridCell<i64>>,
+    pub velocity: Option<Velocity>,
+}
+
+/// Returns the scene to how it started: despawns every `Respawnable` and restores every `ResetToInitial`.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct ResetScene;
+
+pub struct ResetScenePlugin;
+impl Plugin for ResetScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .add_event::<ResetScene>()
+            .add_systems(Update, (send_reset_scene, reset_scene).chain())
+            .add_systems(
+                PostUpdate,
+                record_initial_state.before(PhysicsSet::SyncBackend),
+            );
+    }
+}
+
+pub fn send_reset_scene(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut reset_scene: EventWriter<ResetScene>,
+) {
+    if key_bindings.just_pressed(&key, Action::ResetScene) {
+        reset_scene.send(ResetScene);
+    }
+}
+
+/// Runs before the physics step, so a body spawned this frame is recorded before it has moved.
+pub fn record_initial_state(
+    mut commands: Commands,
+    added_query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&GridCell<i64>>,
+            Option<&Velocity>,
+        ),
+        Added<ResetToInitial>,
+    >,
+) {
+    for (entity, transform, cell, velocity) in added_query.iter() {
+        commands.entity(entity).insert(InitialState {
+            transform: *transform,
+            cell: cell.copied(),
+            velocity: velocity.copied(),
+        });
+    }
+}
+
+pub fn reset_scene(
+    mut commands: Commands,
+    mut reset_scene: EventReader<ResetScene>,
+    current_target: Option<ResMut<CurrentTarget>>,
+    respawnable_query: Query<Entity, With<Respawnable>>,
+    mut restore_query: Query<(
+        &InitialState,
+        &mut Transform,
+        Option<&mut GridCell<i64>>,
+        Option<&mut Velocity>,
+    )>,
+) {
+    if reset_scene.read().count() == 0 {
+        return;
+    }
+    if let Some(mut current_target) = current_target {
+        if current_target
+            .target
+            .is_some_and(|target| respawnable_query.contains(target))
+        {
+            current_target.target = None;
+        }
+    }
+    for entity in respawnable_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (initial_state, mut transform, cell, velocity) in restore_query.iter_mut() {
+        *transform = initial_state.transform;
+        if let (Some(mut cell), Some(initial_cell)) = (cell, initial_state.cell) {
+            *cell = initial_cell;
+        }
+        if let Some(mut velocity) = velocity {
+            *velocity = initial_state.velocity.unwrap_or_default();
+        }
+    }
+    debug!(
+        "scene reset: despawned {}, restored {}",
+        respawnable_query.iter().count(),
+        restore_query.iter().count()
+    );
+}
@@ -0,0 +1,755 @@
+use bevy::{
+    prelude::*,
+    render::view::RenderLayers,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use bevy_rapier3d::prelude::{QueryFilter, RapierContext, Velocity};
+use big_space::{camera::CameraController, IgnoreFloatingOrigin};
+
+use crate::{
+    layers::LayerConfig,
+    projectile::ProjectileSpec,
+    targeting::{ComponentInfo, CurrentTarget, TargetClass, TargetSelected, ValidTarget},
+    util::{is_onscreen, overlay_position},
+};
+
+/// Gizmos drawn on the 2D overlay alongside the reticles.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct ReticleGizmos {}
+
+/// An overlay entity showing one target's bracket. Brackets are pooled: each frame they are moved onto whichever
+/// targets are visible, more are spawned when needed, and the surplus is hidden.
+#[derive(Component)]
+pub struct TargetBracket;
+
+/// One arm of a bracket corner, a child of a `TargetBracket`. `corner` points from the bracket's center toward the
+/// corner, e.g. `(1, 1)` for the top right one.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BracketArm {
+    pub corner: Vec2,
+    pub vertical: bool,
+}
+
+impl BracketArm {
+    /// Offset of the arm's center from the bracket's center, for a bracket `size` pixels from center to corner.
+    pub fn translation(&self, size: f32, corner_length: f32) -> Vec3 {
+        let along = match self.vertical {
+            true => Vec2::new(0.0, self.corner.y),
+            false => Vec2::new(self.corner.x, 0.0),
+        };
+        (self.corner * size - along * corner_length * 0.5).extend(0.0)
+    }
+}
+
+/// Pooled overlay text naming a nearby target, managed by `update_target_labels`.
+#[derive(Component)]
+pub struct TargetLabel;
+
+/// Marks an overlay entity, such as the selected target's reticle, that plays the acquire animation whenever a
+/// `TargetSelected` event arrives.
+#[derive(Component)]
+pub struct AnimateOnAcquire;
+
+/// Scales an `AnimateOnAcquire` entity from `start_scale` down to its normal size over `timer`, then removes itself.
+/// Selecting another target while it runs replaces it, restarting the animation from the beginning.
+#[derive(Component, Debug)]
+pub struct AcquireAnimation {
+    pub timer: Timer,
+    pub start_scale: f32,
+}
+
+/// Meshes and per class materials shared by every bracket. Rebuilt whenever `ReticleSettings` changes, at which point
+/// the bracket and label pools are emptied and respawned with the new settings.
+#[derive(Resource)]
+pub struct ReticleAssets {
+    pub render_layers: RenderLayers,
+    pub horizontal_arm: Mesh2dHandle,
+    pub vertical_arm: Mesh2dHandle,
+    pub unclassified: Handle<ColorMaterial>,
+    pub star: Handle<ColorMaterial>,
+    pub planet: Handle<ColorMaterial>,
+    pub vessel: Handle<ColorMaterial>,
+    pub debris: Handle<ColorMaterial>,
+}
+
+impl ReticleAssets {
+    pub fn material_for(&self, class: Option<&TargetClass>) -> &Handle<ColorMaterial> {
+        match class {
+            Some(TargetClass::Star) => &self.star,
+            Some(TargetClass::Planet) => &self.planet,
+            Some(TargetClass::Vessel) => &self.vessel,
+            Some(TargetClass::Debris) => &self.debris,
+            None => &self.unclassified,
+        }
+    }
+}
+
+/// `render_layers` overrides `LayerConfig::overlay` when set. Brackets are sized to enclose the target's projected
+/// radius times `bracket_padding`, clamped to `bracket_min_size..=bracket_max_size` overlay pixels from the target's
+/// center to the bracket's corner. `bracket_corner_length` is the length of each corner's arms and `line_width` their
+/// thickness.
+/// Brackets take their color from the target's `TargetClass`, or `bracket_color` for unclassified targets.
+/// Up to `label_max_count` targets within `label_max_distance` meters get a name label under their bracket; where
+/// labels would overlap, the one for the target that appears larger on screen wins.
+/// On a new target, `AnimateOnAcquire` entities shrink from `acquire_start_scale` to their normal size over
+/// `acquire_duration` seconds.
+/// The flight path marker shows where the camera is heading once it moves faster than `flight_path_min_speed` m/s.
+/// The lead indicator shows where to aim so the `ProjectileSpec` resource's projectile meets a current target that
+/// has a `Velocity`.
+/// With `occlusion_test` a ray is cast to every target each frame and targets hidden behind another collider get no
+/// bracket. Only colliders take part, so occluders need one.
+#[derive(Resource, Clone)]
+pub struct ReticleSettings {
+    pub render_layers: Option<RenderLayers>,
+    pub line_width: f32,
+    pub bracket_min_size: f32,
+    pub bracket_max_size: f32,
+    pub bracket_padding: f32,
+    pub bracket_corner_length: f32,
+    pub bracket_color: Color,
+    pub star_color: Color,
+    pub planet_color: Color,
+    pub vessel_color: Color,
+    pub debris_color: Color,
+    pub label_font_size: f32,
+    pub label_color: Color,
+    pub label_max_count: usize,
+    pub label_max_distance: f32,
+    pub flight_path_color: Color,
+    pub flight_path_size: f32,
+    pub flight_path_min_speed: f64,
+    pub lead_color: Color,
+    pub lead_size: f32,
+    pub acquire_start_scale: f32,
+    pub acquire_duration: f32,
+    pub occlusion_test: bool,
+}
+
+impl Default for ReticleSettings {
+    fn default() -> Self {
+        Self {
+            render_layers: None,
+            line_width: 0.25,
+            bracket_min_size: 15.0,
+            bracket_max_size: 300.0,
+            bracket_padding: 1.2,
+            bracket_corner_length: 5.0,
+            bracket_color: Color::rgb_u8(0xFE, 0x9F, 0x00),
+            star_color: Color::rgb_u8(0xFF, 0xE0, 0x60),
+            planet_color: Color::rgb_u8(0x60, 0xC0, 0xFF),
+            vessel_color: Color::rgb_u8(0x60, 0xFF, 0x80),
+            debris_color: Color::rgb_u8(0xA0, 0xA0, 0xA0),
+            label_font_size: 12.0,
+            label_color: Color::rgb_u8(0xB2, 0xAF, 0xC2),
+            label_max_count: 16,
+            label_max_distance: 1.0e13,
+            flight_path_color: Color::rgb_u8(0x60, 0xFF, 0x80),
+            flight_path_size: 8.0,
+            flight_path_min_speed: 0.01,
+            lead_color: Color::rgb_u8(0xFF, 0x60, 0x60),
+            lead_size: 6.0,
+            acquire_start_scale: 3.0,
+            acquire_duration: 0.2,
+            occlusion_test: false,
+        }
+    }
+}
+
+impl ReticleSettings {
+    /// Bracket size in overlay pixels for a target of `radius_m` at `distance_m`, see `projected_radius`.
+    pub fn bracket_size(
+        &self,
+        radius_m: f32,
+        distance_m: f32,
+        vertical_fov: f32,
+        viewport_height: f32,
+    ) -> f32 {
+        (projected_radius(radius_m, distance_m, vertical_fov, viewport_height)
+            * self.bracket_padding)
+            .clamp(self.bracket_min_size, self.bracket_max_size)
+    }
+
+    pub fn render_layers(&self, layer_config: &LayerConfig) -> RenderLayers {
+        self.render_layers.unwrap_or(layer_config.overlay)
+    }
+
+    pub fn color_for(&self, class: Option<&TargetClass>) -> Color {
+        match class {
+            Some(TargetClass::Star) => self.star_color,
+            Some(TargetClass::Planet) => self.planet_color,
+            Some(TargetClass::Vessel) => self.vessel_color,
+            Some(TargetClass::Debris) => self.debris_color,
+            None => self.bracket_color,
+        }
+    }
+}
+
+/// Draws corner brackets around every onscreen `ValidTarget` on the overlay camera, and labels the nearby ones.
+pub struct ReticlePlugin;
+impl Plugin for ReticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReticleSettings>()
+            .init_resource::<LayerConfig>()
+            .init_gizmo_group::<ReticleGizmos>()
+            .init_resource::<CurrentTarget>()
+            .add_event::<TargetSelected>()
+            .add_systems(
+                Update,
+                (configure_reticle_gizmos, build_reticle_assets).run_if(
+                    resource_changed::<ReticleSettings>.or_else(resource_changed::<LayerConfig>),
+                ),
+            )
+            .add_systems(
+                Update,
+                (start_acquire_animation, animate_target_acquire).chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    update_target_brackets,
+                    update_target_labels,
+                    draw_flight_path_marker,
+                    draw_lead_indicator,
+                ),
+            );
+    }
+}
+
+pub fn configure_reticle_gizmos(
+    settings: Res<ReticleSettings>,
+    layer_config: Res<LayerConfig>,
+    mut gizmo_config_store: ResMut<GizmoConfigStore>,
+) {
+    let (reticle_gizmo_config, _) = gizmo_config_store.config_mut::<ReticleGizmos>();
+    reticle_gizmo_config.render_layers = settings.render_layers(&layer_config);
+    reticle_gizmo_config.line_width = settings.line_width;
+}
+
+pub fn build_reticle_assets(
+    mut commands: Commands,
+    settings: Res<ReticleSettings>,
+    layer_config: Res<LayerConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    pooled_query: Query<Entity, Or<(With<TargetBracket>, With<TargetLabel>)>>,
+) {
+    for pooled in pooled_query.iter() {
+        commands.entity(pooled).despawn_recursive();
+    }
+    let length = settings.bracket_corner_length;
+    let width = settings.line_width;
+    commands.insert_resource(ReticleAssets {
+        render_layers: settings.render_layers(&layer_config),
+        horizontal_arm: Mesh2dHandle(meshes.add(Rectangle::new(length, width))),
+        vertical_arm: Mesh2dHandle(meshes.add(Rectangle::new(width, length))),
+        unclassified: color_materials.add(settings.color_for(None)),
+        star: color_materials.add(settings.color_for(Some(&TargetClass::Star))),
+        planet: color_materials.add(settings.color_for(Some(&TargetClass::Planet))),
+        vessel: color_materials.add(settings.color_for(Some(&TargetClass::Vessel))),
+        debris: color_materials.add(settings.color_for(Some(&TargetClass::Debris))),
+    });
+}
+
+pub fn start_acquire_animation(
+    mut commands: Commands,
+    settings: Res<ReticleSettings>,
+    mut target_selected: EventReader<TargetSelected>,
+    animated_query: Query<Entity, With<AnimateOnAcquire>>,
+) {
+    if target_selected.read().last().is_none() {
+        return;
+    }
+    for entity in animated_query.iter() {
+        commands.entity(entity).insert(AcquireAnimation {
+            timer: Timer::from_seconds(settings.acquire_duration, TimerMode::Once),
+            start_scale: settings.acquire_start_scale,
+        });
+    }
+}
+
+pub fn animate_target_acquire(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut animation_query: Query<(Entity, &mut AcquireAnimation, &mut Transform)>,
+) {
+    for (entity, mut animation, mut transform) in animation_query.iter_mut() {
+        animation.timer.tick(time.delta());
+        /* Ease out so the reticle closes in quickly and settles gently */
+        let remaining = animation.timer.fraction_remaining();
+        let eased = remaining * remaining;
+        transform.scale = Vec3::splat(1.0 + (animation.start_scale - 1.0) * eased);
+        if animation.timer.finished() {
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<AcquireAnimation>();
+        }
+    }
+}
+
+/// Spawns a `TargetBracket` with its eight arms, centered on `position`.
+pub fn spawn_bracket(
+    commands: &mut Commands,
+    assets: &ReticleAssets,
+    settings: &ReticleSettings,
+    position: Vec2,
+    size: f32,
+    material: &Handle<ColorMaterial>,
+) -> Entity {
+    commands
+        .spawn((
+            TargetBracket,
+            IgnoreFloatingOrigin,
+            SpatialBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+        ))
+        .with_children(|parent| {
+            for corner in [
+                Vec2::new(1.0, 1.0),
+                Vec2::new(1.0, -1.0),
+                Vec2::new(-1.0, 1.0),
+                Vec2::new(-1.0, -1.0),
+            ] {
+                for vertical in [false, true] {
+                    let arm = BracketArm { corner, vertical };
+                    parent.spawn((
+                        arm,
+                        assets.render_layers,
+                        MaterialMesh2dBundle {
+                            mesh: match vertical {
+                                true => assets.vertical_arm.clone(),
+                                false => assets.horizontal_arm.clone(),
+                            },
+                            material: material.clone(),
+                            transform: Transform::from_translation(
+                                arm.translation(size, settings.bracket_corner_length),
+                            ),
+                            ..default()
+                        },
+                    ));
+                }
+            }
+        })
+        .id()
+}
+
+/// Whether a collider other than `target`, or one of its descendants, lies on the line from `eye` to `target_position`.
+pub fn is_occluded(
+    rapier_context: &RapierContext,
+    eye: Vec3,
+    target: Entity,
+    target_position: Vec3,
+    parent_query: &Query<&Parent>,
+) -> bool {
+    let offset = target_position - eye;
+    let Some(direction) = offset.try_normalize() else {
+        return false;
+    };
+    match rapier_context.cast_ray(
+        eye,
+        direction,
+        offset.length(),
+        true,
+        QueryFilter::default(),
+    ) {
+        Some((hit, _)) => {
+            hit != target
+                && !parent_query
+                    .iter_ancestors(hit)
+                    .any(|ancestor| ancestor == target)
+        }
+        None => false,
+    }
+}
+
+/// Radius in overlay pixels of a sphere of `radius_m` at `distance_m` from a perspective camera with `vertical_fov`
+/// (radians) and a viewport `viewport_height` pixels tall.
+pub fn projected_radius(
+    radius_m: f32,
+    distance_m: f32,
+    vertical_fov: f32,
+    viewport_height: f32,
+) -> f32 {
+    if distance_m <= radius_m {
+        return f32::INFINITY;
+    }
+    let angular_radius = (radius_m / distance_m).asin();
+    angular_radius.tan() / (vertical_fov * 0.5).tan() * viewport_height * 0.5
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn update_target_brackets(
+    mut commands: Commands,
+    target_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ComponentInfo,
+            Option<&TargetClass>,
+        ),
+        With<ValidTarget>,
+    >,
+    mut bracket_query: Query<(&mut Transform, &mut Visibility, &Children), With<TargetBracket>>,
+    mut arm_query: Query<
+        (&mut Transform, &mut Handle<ColorMaterial>, &BracketArm),
+        Without<TargetBracket>,
+    >,
+    parent_query: Query<&Parent>,
+    rapier_context: Option<Res<RapierContext>>,
+    camera_3d_query: Query<
+        (&Camera, &GlobalTransform, &Projection),
+        (With<Camera3d>, With<CameraController>),
+    >,
+    camera_2d_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    settings: Res<ReticleSettings>,
+    assets: Option<Res<ReticleAssets>>,
+) {
+    let (
+        Some(assets),
+        Ok((camera_3d, camera_3d_global_transform, camera_3d_projection)),
+        Ok((camera_2d, camera_2d_global_transform)),
+    ) = (
+        assets,
+        camera_3d_query.get_single(),
+        camera_2d_query.get_single(),
+    )
+    else {
+        return;
+    };
+    let (Projection::Perspective(perspective), Some(viewport_size)) =
+        (camera_3d_projection, camera_3d.logical_viewport_size())
+    else {
+        return;
+    };
+    let occlusion_context = rapier_context.filter(|_| settings.occlusion_test);
+    let mut brackets = bracket_query.iter_mut();
+    for (target, target_global_transform, target_info, target_class) in target_query.iter() {
+        let Some(position) = overlay_position(
+            camera_3d,
+            camera_3d_global_transform,
+            camera_2d,
+            camera_2d_global_transform,
+            target_global_transform.translation(),
+        ) else {
+            continue;
+        };
+        if let Some(rapier_context) = &occlusion_context {
+            if is_occluded(
+                rapier_context,
+                camera_3d_global_transform.translation(),
+                target,
+                target_global_transform.translation(),
+                &parent_query,
+            ) {
+                continue;
+            }
+        }
+        let distance = camera_3d_global_transform
+            .translation()
+            .distance(target_global_transform.translation());
+        let size =
+            settings.bracket_size(target_info.size, distance, perspective.fov, viewport_size.y);
+        let material = assets.material_for(target_class);
+        match brackets.next() {
+            Some((mut bracket_transform, mut bracket_visibility, arms)) => {
+                bracket_transform.translation = position.extend(0.0);
+                *bracket_visibility = Visibility::Inherited;
+                for arm_entity in arms.iter() {
+                    let Ok((mut arm_transform, mut arm_material, arm)) =
+                        arm_query.get_mut(*arm_entity)
+                    else {
+                        continue;
+                    };
+                    arm_transform.translation =
+                        arm.translation(size, settings.bracket_corner_length);
+                    if *arm_material != *material {
+                        *arm_material = material.clone();
+                    }
+                }
+            }
+            None => {
+                spawn_bracket(&mut commands, &assets, &settings, position, size, material);
+            }
+        }
+    }
+    for (_, mut bracket_visibility, _) in brackets {
+        *bracket_visibility = Visibility::Hidden;
+    }
+}
+
+/// Rough overlay footprint of a label, centered on `position`, assuming a glyph is about 0.6 em wide.
+fn label_rect(position: Vec2, text: &str, font_size: f32) -> Rect {
+    let size = Vec2::new(text.chars().count() as f32 * font_size * 0.6, font_size);
+    Rect::from_center_size(position, size)
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn update_target_labels(
+    mut commands: Commands,
+    target_query: Query<(&GlobalTransform, &ComponentInfo), With<ValidTarget>>,
+    mut label_query: Query<(&mut Text, &mut Transform, &mut Visibility), With<TargetLabel>>,
+    camera_3d_query: Query<
+        (&Camera, &GlobalTransform, &Projection),
+        (With<Camera3d>, With<CameraController>),
+    >,
+    camera_2d_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    settings: Res<ReticleSettings>,
+    layer_config: Res<LayerConfig>,
+) {
+    let (
+        Ok((camera_3d, camera_3d_global_transform, camera_3d_projection)),
+        Ok((camera_2d, camera_2d_global_transform)),
+    ) = (camera_3d_query.get_single(), camera_2d_query.get_single())
+    else {
+        return;
+    };
+    let (Projection::Perspective(perspective), Some(viewport_size)) =
+        (camera_3d_projection, camera_3d.logical_viewport_size())
+    else {
+        return;
+    };
+
+    /* Candidates within range and onscreen, largest on screen first */
+    let mut candidates: Vec<(&str, Vec2, f32)> = target_query
+        .iter()
+        .filter_map(|(target_global_transform, target_info)| {
+            let world_position = target_global_transform.translation();
+            let distance = camera_3d_global_transform
+                .translation()
+                .distance(world_position);
+            if distance > settings.label_max_distance
+                || !is_onscreen(camera_3d, camera_3d_global_transform, world_position)
+            {
+                return None;
+            }
+            let position = overlay_position(
+                camera_3d,
+                camera_3d_global_transform,
+                camera_2d,
+                camera_2d_global_transform,
+                world_position,
+            )?;
+            let size =
+                settings.bracket_size(target_info.size, distance, perspective.fov, viewport_size.y);
+            let label_position = position - Vec2::new(0.0, size + settings.label_font_size);
+            Some((target_info.name.as_str(), label_position, size))
+        })
+        .collect();
+    candidates.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+    /* Drop labels that would overlap one already placed */
+    let mut placed: Vec<(&str, Vec2, Rect)> = Vec::new();
+    for (name, position, _) in candidates {
+        if placed.len() >= settings.label_max_count {
+            break;
+        }
+        let rect = label_rect(position, name, settings.label_font_size);
+        if placed
+            .iter()
+            .all(|(_, _, other)| other.intersect(rect).is_empty())
+        {
+            placed.push((name, position, rect));
+        }
+    }
+
+    let mut labels = label_query.iter_mut();
+    for (name, position, _) in placed {
+        match labels.next() {
+            Some((mut text, mut transform, mut visibility)) => {
+                if text.sections[0].value != name {
+                    text.sections[0].value = name.to_string();
+                }
+                transform.translation = position.extend(0.0);
+                *visibility = Visibility::Inherited;
+            }
+            None => {
+                commands.spawn((
+                    TargetLabel,
+                    IgnoreFloatingOrigin,
+                    settings.render_layers(&layer_config),
+                    Text2dBundle {
+                        text: Text::from_section(
+                            name,
+                            TextStyle {
+                                font_size: settings.label_font_size,
+                                color: settings.label_color,
+                                ..default()
+                            },
+                        ),
+                        transform: Transform::from_translation(position.extend(0.0)),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+    for (_, _, mut visibility) in labels {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Draws the flight path marker, a circle with wings and a tail, centered on `center`.
+pub fn draw_flight_path_symbol(
+    gizmos: &mut Gizmos<ReticleGizmos>,
+    center: Vec2,
+    size: f32,
+    color: Color,
+) {
+    gizmos.circle_2d(center, size, color);
+    gizmos.line_2d(
+        center + Vec2::new(size, 0.0),
+        center + Vec2::new(size * 2.5, 0.0),
+        color,
+    );
+    gizmos.line_2d(
+        center - Vec2::new(size, 0.0),
+        center - Vec2::new(size * 2.5, 0.0),
+        color,
+    );
+    gizmos.line_2d(
+        center + Vec2::new(0.0, size),
+        center + Vec2::new(0.0, size * 2.0),
+        color,
+    );
+}
+
+/// Marks where the camera's velocity points on the overlay, the 2D counterpart of the navball's prograde marker.
+#[allow(clippy::type_complexity)]
+pub fn draw_flight_path_marker(
+    camera_3d_query: Query<(&Camera, &GlobalTransform, &CameraController), With<Camera3d>>,
+    camera_2d_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    settings: Res<ReticleSettings>,
+    time: Res<Time>,
+    mut reticle_gizmos: Gizmos<ReticleGizmos>,
+) {
+    let (
+        Ok((camera_3d, camera_3d_global_transform, controller)),
+        Ok((camera_2d, camera_2d_global_transform)),
+    ) = (camera_3d_query.get_single(), camera_2d_query.get_single())
+    else {
+        return;
+    };
+    let (displacement, _) = controller.velocity();
+    let delta_seconds = time.delta_seconds_f64();
+    if delta_seconds <= 0.0
+        || displacement.length() / delta_seconds < settings.flight_path_min_speed
+    {
+        return;
+    }
+    /* Any point along the velocity works; one meter out stays well inside the far plane */
+    let heading_point =
+        camera_3d_global_transform.translation() + displacement.normalize().as_vec3();
+    let Some(position) = overlay_position(
+        camera_3d,
+        camera_3d_global_transform,
+        camera_2d,
+        camera_2d_global_transform,
+        heading_point,
+    ) else {
+        return;
+    };
+    draw_flight_path_symbol(
+        &mut reticle_gizmos,
+        position,
+        settings.flight_path_size,
+        settings.flight_path_color,
+    );
+}
+
+/// Seconds until a projectile fired now at `speed` can meet a target at `relative_position` moving at
+/// `relative_velocity`, both relative to the shooter. `None` when the target outruns the projectile.
+pub fn intercept_time(relative_position: Vec3, relative_velocity: Vec3, speed: f32) -> Option<f32> {
+    /* |relative_position + relative_velocity * t| = speed * t, a quadratic in t */
+    let a = relative_velocity.length_squared() - speed * speed;
+    let b = 2.0 * relative_position.dot(relative_velocity);
+    let c = relative_position.length_squared();
+    if a.abs() < f32::EPSILON {
+        return (b < 0.0).then_some(-c / b);
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+        .into_iter()
+        .filter(|t| *t > 0.0)
+        .min_by(f32::total_cmp)
+}
+
+pub fn draw_lead_symbol(gizmos: &mut Gizmos<ReticleGizmos>, center: Vec2, size: f32, color: Color) {
+    gizmos.circle_2d(center, size, color);
+    gizmos.line_2d(
+        center - Vec2::new(size * 0.5, 0.0),
+        center + Vec2::new(size * 0.5, 0.0),
+        color,
+    );
+    gizmos.line_2d(
+        center - Vec2::new(0.0, size * 0.5),
+        center + Vec2::new(0.0, size * 0.5),
+        color,
+    );
+}
+
+/// Marks where to aim so a projectile fired from the camera at the `ProjectileSpec`'s muzzle velocity meets the current
+/// target, accounting for both the target's `Velocity` and the camera's own.
+#[allow(clippy::type_complexity)]
+pub fn draw_lead_indicator(
+    current_target: Res<CurrentTarget>,
+    target_query: Query<(&GlobalTransform, &Velocity)>,
+    camera_3d_query: Query<(&Camera, &GlobalTransform, &CameraController), With<Camera3d>>,
+    camera_2d_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    settings: Res<ReticleSettings>,
+    projectile_spec: Option<Res<ProjectileSpec>>,
+    time: Res<Time>,
+    mut reticle_gizmos: Gizmos<ReticleGizmos>,
+) {
+    let (
+        Some(projectile_spec),
+        Some((target_global_transform, target_velocity)),
+        Ok((camera_3d, camera_3d_global_transform, controller)),
+        Ok((camera_2d, camera_2d_global_transform)),
+    ) = (
+        projectile_spec,
+        current_target
+            .target
+            .and_then(|target| target_query.get(target).ok()),
+        camera_3d_query.get_single(),
+        camera_2d_query.get_single(),
+    )
+    else {
+        return;
+    };
+    let delta_seconds = time.delta_seconds();
+    if delta_seconds <= 0.0 {
+        return;
+    }
+    let (displacement, _) = controller.velocity();
+    let camera_velocity = displacement.as_vec3() / delta_seconds;
+    let target_position = target_global_transform.translation();
+    let relative_position = target_position - camera_3d_global_transform.translation();
+    let relative_velocity = target_velocity.linvel - camera_velocity;
+    let Some(time_s) = intercept_time(
+        relative_position,
+        relative_velocity,
+        projectile_spec.muzzle_velocity,
+    ) else {
+        return;
+    };
+    let lead_point = target_position + relative_velocity * time_s;
+    if !is_onscreen(camera_3d, camera_3d_global_transform, lead_point) {
+        return;
+    }
+    let Some(position) = overlay_position(
+        camera_3d,
+        camera_3d_global_transform,
+        camera_2d,
+        camera_2d_global_transform,
+        lead_point,
+    ) else {
+        return;
+    };
+    draw_lead_symbol(
+        &mut reticle_gizmos,
+        position,
+        settings.lead_size,
+        settings.lead_color,
+    );
+}
@@ -0,0 +1,204 @@
+use std::{fs, path::Path};
+
+use bevy::{math::DVec3, prelude::*};
+use bevy_rapier3d::prelude::Velocity;
+use big_space::{reference_frame::RootReferenceFrame, GridCell};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    orbits::{Orbit, ORBIT_GIZMO_SEGMENTS},
+    targeting::ComponentInfo,
+};
+
+/// Everything `save_world` records about one body. Positions are absolute f64 grid positions so that bodies far from
+/// the origin keep their precision through the round trip.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedBody {
+    pub name: String,
+    pub size: f32,
+    pub position: DVec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub velocity: Option<SavedVelocity>,
+    pub orbit: Option<SavedOrbit>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SavedVelocity {
+    pub linvel: Vec3,
+    pub angvel: Vec3,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SavedOrbit {
+    pub radius: f32,
+    pub base_color: Color,
+    pub inclination: f32,
+    pub longitude_of_ascending_node: f32,
+    /// Missing from scenes saved before rings had a segment count.
+    #[serde(default = "default_orbit_segments")]
+    pub segments: usize,
+}
+
+fn default_orbit_segments() -> usize {
+    ORBIT_GIZMO_SEGMENTS
+}
+
+/// The contents of a scene file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SavedScene {
+    pub bodies: Vec<SavedBody>,
+}
+
+/// Writes every entity with a `ComponentInfo`, `GridCell` and `Transform` to `path` as RON, along with its `Velocity`
+/// and `Orbit` when present.
+pub fn save_world(world: &mut World, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let mut query = world.query::<(
+        &ComponentInfo,
+        &GridCell<i64>,
+        &Transform,
+        Option<&Velocity>,
+        Option<&Orbit>,
+    )>();
+    let space = world.resource::<RootReferenceFrame<i64>>();
+    let bodies = query
+        .iter(world)
+        .map(|(info, cell, transform, velocity, orbit)| SavedBody {
+            name: info.name.clone(),
+            size: info.size,
+            position: space.grid_position_double(cell, transform),
+            rotation: transform.rotation,
+            scale: transform.scale,
+            velocity: velocity.map(|velocity| SavedVelocity {
+                linvel: velocity.linvel,
+                angvel: velocity.angvel,
+            }),
+            orbit: orbit.map(|orbit| SavedOrbit {
+                radius: orbit.radius,
+                base_color: orbit.base_color,
+                inclination: orbit.inclination,
+                longitude_of_ascending_node: orbit.longitude_of_ascending_node,
+                segments: orbit.segments,
+            }),
+        })
+        .collect();
+    let scene = SavedScene { bodies };
+    let text = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default())?;
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Spawns the bodies stored in `path` by `save_world` and returns their entities, in file order, so the caller can
+/// attach meshes, colliders and anything else that isn't saved.
+pub fn load_world(
+    commands: &mut Commands,
+    space: &RootReferenceFrame<i64>,
+    path: impl AsRef<Path>,
+) -> anyhow::Result<Vec<Entity>> {
+    let text = fs::read_to_string(path)?;
+    let scene: SavedScene = ron::from_str(&text)?;
+    let entities = scene
+        .bodies
+        .into_iter()
+        .map(|body| {
+            let (cell, translation) = space.translation_to_grid(body.position);
+            let transform = Transform {
+                translation,
+                rotation: body.rotation,
+                scale: body.scale,
+            };
+            let mut entity_commands = commands.spawn((
+                SpatialBundle::from_transform(transform),
+                cell,
+                ComponentInfo {
+                    name: body.name,
+                    size: body.size,
+                },
+            ));
+            if let Some(velocity) = body.velocity {
+                entity_commands.insert(Velocity {
+                    linvel: velocity.linvel,
+                    angvel: velocity.angvel,
+                });
+            }
+            if let Some(orbit) = body.orbit {
+                entity_commands.insert(Orbit {
+                    radius: orbit.radius,
+                    base_color: orbit.base_color,
+                    inclination: orbit.inclination,
+                    longitude_of_ascending_node: orbit.longitude_of_ascending_node,
+                    segments: orbit.segments,
+                });
+            }
+            entity_commands.id()
+        })
+        .collect();
+    Ok(entities)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+    use big_space::FloatingOriginPlugin;
+
+    use super::*;
+
+    #[test]
+    fn save_clear_load_keeps_positions() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FloatingOriginPlugin::<i64>::default()));
+        let positions = [
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(12.5, -3.0, 7.25),
+            DVec3::new(1.496e11, 2.0e3, -4.2e9),
+        ];
+        let space = app.world.resource::<RootReferenceFrame<i64>>().clone();
+        for (index, position) in positions.iter().enumerate() {
+            let (cell, translation) = space.translation_to_grid(*position);
+            app.world.spawn((
+                ComponentInfo {
+                    name: format!("Body {index}"),
+                    size: 1.0,
+                },
+                cell,
+                Transform::from_translation(translation),
+            ));
+        }
+        let path = std::env::temp_dir().join("bevy_space_program_scene_io_round_trip.ron");
+        save_world(&mut app.world, &path).unwrap();
+
+        let saved: Vec<Entity> = app
+            .world
+            .query_filtered::<Entity, With<ComponentInfo>>()
+            .iter(&app.world)
+            .collect();
+        for entity in saved {
+            app.world.despawn(entity);
+        }
+
+        let mut command_queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut command_queue, &app.world);
+        let loaded = load_world(&mut commands, &space, &path).unwrap();
+        command_queue.apply(&mut app.world);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), positions.len());
+        for entity in loaded {
+            let entity_ref = app.world.entity(entity);
+            let name = &entity_ref.get::<ComponentInfo>().unwrap().name;
+            let index: usize = name.trim_start_matches("Body ").parse().unwrap();
+            let position = space.grid_position_double(
+                entity_ref.get::<GridCell<i64>>().unwrap(),
+                entity_ref.get::<Transform>().unwrap(),
+            );
+            assert!(
+                position.distance(positions[index]) < 1.0e-3,
+                "{name}: {position} vs {}",
+                positions[index]
+            );
+        }
+    }
+}
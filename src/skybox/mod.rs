@@ -0,0 +1,180 @@
+use bevy::{
+    core_pipeline::Skybox,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+        },
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Requests a `Skybox` on this camera from a vertically stacked cubemap image (six square faces, top to bottom).
+/// The image is reinterpreted as a cube once it has loaded and the `Skybox` is attached then.
+#[derive(Component, Clone, Debug)]
+pub struct CubemapSkybox {
+    pub image: Handle<Image>,
+    pub brightness: f32,
+}
+
+pub struct SkyboxPlugin;
+impl Plugin for SkyboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_cubemap_skyboxes);
+    }
+}
+
+/// Switches `camera` to another skybox image. The current skybox stays visible until the new image has loaded.
+pub fn set_skybox(commands: &mut Commands, camera: Entity, image: Handle<Image>, brightness: f32) {
+    commands
+        .entity(camera)
+        .insert(CubemapSkybox { image, brightness });
+}
+
+/// Reinterprets a vertically stacked cubemap image as a cube texture. Does nothing if it already is one.
+pub fn prepare_cubemap_skybox(image: &mut Image) {
+    let already_cube = matches!(
+        &image.texture_view_descriptor,
+        Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..
+        })
+    );
+    if already_cube {
+        return;
+    }
+    if image.texture_descriptor.size.depth_or_array_layers == 1 {
+        image.reinterpret_stacked_2d_as_array(image.height() / image.width());
+    }
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+}
+
+/// Attaches or updates `Skybox` on cameras whose `CubemapSkybox` image has loaded and isn't applied yet.
+pub fn apply_cubemap_skyboxes(
+    mut commands: Commands,
+    camera_query: Query<(Entity, &CubemapSkybox, Option<&Skybox>)>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (camera_entity, cubemap_skybox, skybox) in camera_query.iter() {
+        if let Some(skybox) = skybox {
+            if skybox.image == cubemap_skybox.image
+                && skybox.brightness == cubemap_skybox.brightness
+            {
+                continue;
+            }
+        }
+        let Some(image) = images.get_mut(&cubemap_skybox.image) else {
+            continue;
+        };
+        prepare_cubemap_skybox(image);
+        debug!("skybox applied to {:?}", camera_entity);
+        commands.entity(camera_entity).insert(Skybox {
+            image: cubemap_skybox.image.clone(),
+            brightness: cubemap_skybox.brightness,
+        });
+    }
+}
+
+/// Renders `star_count` randomly placed stars onto the six faces of a `resolution` square cubemap.
+/// Most stars are faint with a few bright ones, roughly like the naked eye sky. The same `seed` always produces the
+/// same sky. The returned image is already a cube texture and can be used directly as a `Skybox`.
+pub fn generate_starfield_cubemap(seed: u64, star_count: usize, resolution: u32) -> Image {
+    let face_size = (resolution * resolution) as usize;
+    let mut data = [0, 0, 0, 255].repeat(face_size * 6);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _ in 0..star_count {
+        /* Uniform direction on the sphere */
+        let z: f32 = rng.gen_range(-1.0..=1.0);
+        let azimuth: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+        let ring_radius = (1.0 - z * z).sqrt();
+        let direction = Vec3::new(ring_radius * azimuth.cos(), ring_radius * azimuth.sin(), z);
+
+        /* Skewed toward faint stars */
+        let brightness = rng.gen::<f32>().powi(6);
+        let tint = rng.gen_range(-0.15..=0.15);
+        let color = [
+            brightness * (1.0 + tint),
+            brightness,
+            brightness * (1.0 - tint),
+        ];
+
+        let (face, x, y) = cubemap_texel(direction, resolution);
+        add_star_texel(&mut data, resolution, face, x, y, color);
+        if brightness > 0.5 {
+            let halo = color.map(|channel| channel * 0.3);
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (halo_x, halo_y) = (x as i64 + dx, y as i64 + dy);
+                if (0..resolution as i64).contains(&halo_x)
+                    && (0..resolution as i64).contains(&halo_y)
+                {
+                    add_star_texel(
+                        &mut data,
+                        resolution,
+                        face,
+                        halo_x as u32,
+                        halo_y as u32,
+                        halo,
+                    );
+                }
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: resolution,
+            height: resolution,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    image
+}
+
+/// Face index (+X, -X, +Y, -Y, +Z, -Z) and texel of `direction` on a cubemap.
+fn cubemap_texel(direction: Vec3, resolution: u32) -> (usize, u32, u32) {
+    let abs = direction.abs();
+    let (face, major, s, t) = if abs.x >= abs.y && abs.x >= abs.z {
+        match direction.x > 0.0 {
+            true => (0, abs.x, -direction.z, -direction.y),
+            false => (1, abs.x, direction.z, -direction.y),
+        }
+    } else if abs.y >= abs.z {
+        match direction.y > 0.0 {
+            true => (2, abs.y, direction.x, direction.z),
+            false => (3, abs.y, direction.x, -direction.z),
+        }
+    } else {
+        match direction.z > 0.0 {
+            true => (4, abs.z, direction.x, -direction.y),
+            false => (5, abs.z, -direction.x, -direction.y),
+        }
+    };
+    let to_texel =
+        |coordinate: f32| (((coordinate / major + 1.0) / 2.0) * resolution as f32) as u32;
+    (
+        face,
+        to_texel(s).min(resolution - 1),
+        to_texel(t).min(resolution - 1),
+    )
+}
+
+fn add_star_texel(data: &mut [u8], resolution: u32, face: usize, x: u32, y: u32, color: [f32; 3]) {
+    let texel = face * (resolution * resolution) as usize + (y * resolution + x) as usize;
+    let rgba = &mut data[texel * 4..texel * 4 + 4];
+    for (channel, value) in rgba.iter_mut().zip(color) {
+        *channel = channel.saturating_add((value.clamp(0.0, 1.0) * 255.0) as u8);
+    }
+}
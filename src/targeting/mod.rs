@@ -0,0 +1,387 @@
+use bevy::{ecs::entity::Entities, prelude::*};
+use bevy_rapier3d::prelude::{QueryFilter, RapierContext};
+use big_space::{camera::CameraController, FloatingOrigin};
+
+use crate::{
+    input::{Action, CommandEntered, CommandSequence, CommandSequencePlugin},
+    util::{is_onscreen, overlay_position},
+};
+
+/// Marks an entity as something the player can select as a navigation target.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct ValidTarget;
+
+/// What kind of object a target is, used to color its overlay markers. Targets without one are unclassified and get
+/// the neutral bracket color. `Default` is only here because `#[reflect(Component)]` needs it, and reflected inserts
+/// overwrite it with the stored variant; `Planet` is used because natural bodies make up most of the scenery targets.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[reflect(Component)]
+pub enum TargetClass {
+    Star,
+    #[default]
+    Planet,
+    Vessel,
+    Debris,
+}
+
+/// Display name and radius (in meters) of a targetable object.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct ComponentInfo {
+    pub name: String,
+    pub size: f32,
+}
+
+/// The navigation target instruments such as the navball follow.
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct CurrentTarget {
+    pub target: Option<Entity>,
+}
+
+/// Sent when the player picks a new navigation target.
+#[derive(Event, Debug)]
+pub struct TargetSelected {
+    pub target: Entity,
+}
+
+/// The floating origin camera's `CameraController::nearest_object()`, as the entity and its distance in meters.
+/// `None` when the controller found nothing or the entity it found has since been despawned.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Resource)]
+pub struct NearestObject(pub Option<(Entity, f64)>);
+
+impl NearestObject {
+    pub fn entity(&self) -> Option<Entity> {
+        self.0.map(|(entity, _)| entity)
+    }
+
+    pub fn distance(&self) -> Option<f64> {
+        self.0.map(|(_, distance)| distance)
+    }
+}
+
+/// Registers the targeting components and resources for reflection, so they show up in inspectors and scenes
+/// whichever targeting systems an app runs. Part of `SpaceProgramPlugins`.
+pub struct TargetingTypesPlugin;
+impl Plugin for TargetingTypesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ValidTarget>()
+            .register_type::<TargetClass>()
+            .register_type::<ComponentInfo>()
+            .register_type::<CurrentTarget>();
+    }
+}
+
+/// Keeps `NearestObject` up to date. Runs in `PreUpdate`, so every `Update` system sees the same value.
+pub struct NearestObjectPlugin;
+impl Plugin for NearestObjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NearestObject>()
+            .register_type::<NearestObject>()
+            .add_systems(PreUpdate, update_nearest_object);
+    }
+}
+
+pub fn update_nearest_object(
+    mut nearest_object: ResMut<NearestObject>,
+    camera_query: Query<&CameraController, With<FloatingOrigin>>,
+    entities: &Entities,
+) {
+    /* The controller's result is from the last frame, so the entity may be gone by now */
+    let nearest = camera_query
+        .get_single()
+        .ok()
+        .and_then(|controller| controller.nearest_object())
+        .filter(|(entity, _)| entities.contains(*entity));
+    nearest_object.set_if_neq(NearestObject(nearest));
+}
+
+/// How `Action::SelectTarget` picks a target: the floating origin camera's `NearestObject`, the onscreen target
+/// nearest the crosshair, or the target whose collider is under the crosshair.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum TargetingMode {
+    Nearest,
+    #[default]
+    Cursor,
+    RayCast,
+}
+
+impl TargetingMode {
+    /// The target this mode selects, given the nearest object, the target nearest the cursor and the target hit by a
+    /// ray through the crosshair.
+    pub fn pick(
+        self,
+        nearest: Option<Entity>,
+        cursor_nearest: Option<Entity>,
+        under_crosshair: Option<Entity>,
+    ) -> Option<Entity> {
+        match self {
+            TargetingMode::Nearest => nearest,
+            TargetingMode::Cursor => cursor_nearest,
+            TargetingMode::RayCast => under_crosshair,
+        }
+    }
+}
+
+/// `selection_radius` is how far, in overlay units (pixels with the default 2D camera), a target may be from the
+/// crosshair and still be picked by `TargetingMode::Cursor`.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct TargetingSettings {
+    pub selection_radius: f32,
+}
+
+impl Default for TargetingSettings {
+    fn default() -> Self {
+        Self {
+            selection_radius: 100.0,
+        }
+    }
+}
+
+/// Sent when `TargetingMode` changes.
+#[derive(Event, Debug)]
+pub struct TargetingModeChanged {
+    pub mode: TargetingMode,
+}
+
+/// Adds `TargetingMode` and `TargetingSettings`. The mode is switched with the `NavModeCommand` then `NavModeCursor`,
+/// `NavModeNearest` or `NavModeRayCast` key sequences.
+pub struct TargetingModePlugin;
+impl Plugin for TargetingModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(CommandSequencePlugin::<TargetingMode>::default())
+            .init_resource::<TargetingMode>()
+            .register_type::<TargetingMode>()
+            .init_resource::<TargetingSettings>()
+            .register_type::<TargetingSettings>()
+            .add_event::<TargetingModeChanged>()
+            .add_systems(
+                Update,
+                (apply_targeting_mode_commands, send_targeting_mode_changed).chain(),
+            );
+        let mut command_sequence = app.world.resource_mut::<CommandSequence<TargetingMode>>();
+        command_sequence.register(
+            [Action::NavModeCommand, Action::NavModeCursor],
+            TargetingMode::Cursor,
+        );
+        command_sequence.register(
+            [Action::NavModeCommand, Action::NavModeNearest],
+            TargetingMode::Nearest,
+        );
+        command_sequence.register(
+            [Action::NavModeCommand, Action::NavModeRayCast],
+            TargetingMode::RayCast,
+        );
+    }
+}
+
+pub fn apply_targeting_mode_commands(
+    mut command_events: EventReader<CommandEntered<TargetingMode>>,
+    mut targeting_mode: ResMut<TargetingMode>,
+) {
+    for CommandEntered(mode) in command_events.read() {
+        targeting_mode.set_if_neq(*mode);
+    }
+}
+
+pub fn send_targeting_mode_changed(
+    targeting_mode: Res<TargetingMode>,
+    mut mode_changed: EventWriter<TargetingModeChanged>,
+) {
+    if targeting_mode.is_changed() && !targeting_mode.is_added() {
+        debug!("targeting mode: {:?}", *targeting_mode);
+        mode_changed.send(TargetingModeChanged {
+            mode: *targeting_mode,
+        });
+    }
+}
+
+/// Where a world position lands on a 2D overlay camera, and whether it is inside the 3D camera's viewport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OverlayProjection {
+    pub position: Vec2,
+    pub onscreen: bool,
+}
+
+/// Projects `world_position` as seen by `camera_3d` into the world space of the 2D overlay `camera_2d`.
+/// Returns `None` when the position is behind the 3D camera or can't be projected.
+pub fn project_to_overlay(
+    camera_3d: &Camera,
+    camera_3d_global_transform: &GlobalTransform,
+    camera_2d: &Camera,
+    camera_2d_global_transform: &GlobalTransform,
+    world_position: Vec3,
+) -> Option<OverlayProjection> {
+    let position = overlay_position(
+        camera_3d,
+        camera_3d_global_transform,
+        camera_2d,
+        camera_2d_global_transform,
+        world_position,
+    )?;
+    let onscreen = is_onscreen(camera_3d, camera_3d_global_transform, world_position);
+    Some(OverlayProjection { position, onscreen })
+}
+
+/// The onscreen target whose overlay position is closest to `point`, e.g. the crosshair at the overlay origin,
+/// ignoring targets more than `max_distance` from it. Ties go to the lowest `Entity`, so the pick doesn't depend on
+/// query iteration order.
+pub fn pick_nearest_onscreen(
+    targets: impl IntoIterator<Item = (Entity, OverlayProjection)>,
+    point: Vec2,
+    max_distance: f32,
+) -> Option<(Entity, Vec2)> {
+    targets
+        .into_iter()
+        .filter(|(_, projection)| projection.onscreen)
+        .map(|(entity, projection)| (entity, projection.position))
+        .filter(|(_, position)| position.distance_squared(point) <= max_distance * max_distance)
+        .min_by(|(a_entity, a), (b_entity, b)| {
+            a.distance_squared(point)
+                .total_cmp(&b.distance_squared(point))
+                .then_with(|| a_entity.cmp(b_entity))
+        })
+}
+
+/// The target hit first by a ray from `origin` along `direction`, in floating origin relative coordinates.
+/// A collider counts as hitting the target when it is on an entity `is_target` accepts or on one of its descendants,
+/// so colliders of non targets, such as projectiles, don't block the ray.
+pub fn pick_under_crosshair(
+    rapier_context: &RapierContext,
+    origin: Vec3,
+    direction: Vec3,
+    is_target: impl Fn(Entity) -> bool,
+    parent_query: &Query<&Parent>,
+) -> Option<Entity> {
+    let owning_target = |entity: Entity| {
+        std::iter::once(entity)
+            .chain(parent_query.iter_ancestors(entity))
+            .find(|each| is_target(*each))
+    };
+    let predicate = |entity: Entity| owning_target(entity).is_some();
+    let direction = direction.try_normalize()?;
+    let (hit, _) = rapier_context.cast_ray(
+        origin,
+        direction,
+        f32::MAX,
+        true,
+        QueryFilter::default().predicate(&predicate),
+    )?;
+    owning_target(hit)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::camera::{camera_system, ManualTextureViews};
+
+    use super::*;
+
+    /// A 3D camera at the origin looking down -Z and a default 2D overlay camera, both on a 1280x720 primary window,
+    /// with their viewports and projections computed the way `CameraPlugin` would.
+    fn overlay_cameras() -> ((Camera, GlobalTransform), (Camera, GlobalTransform)) {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            WindowPlugin::default(),
+            AssetPlugin::default(),
+        ))
+        .init_asset::<Image>()
+        .init_resource::<ManualTextureViews>()
+        .add_systems(
+            PostUpdate,
+            (
+                camera_system::<Projection>,
+                camera_system::<OrthographicProjection>,
+            ),
+        );
+        let camera_3d = app.world.spawn(Camera3dBundle::default()).id();
+        let camera_2d = app
+            .world
+            .spawn(Camera2dBundle {
+                camera: Camera {
+                    order: 1,
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+        app.update();
+        let camera_and_transform = |entity| {
+            let entity = app.world.entity(entity);
+            (
+                entity.get::<Camera>().unwrap().clone(),
+                GlobalTransform::from(*entity.get::<Transform>().unwrap()),
+            )
+        };
+        (
+            camera_and_transform(camera_3d),
+            camera_and_transform(camera_2d),
+        )
+    }
+
+    #[test]
+    fn targets_behind_the_camera_are_not_projected() {
+        let ((camera_3d, camera_3d_gt), (camera_2d, camera_2d_gt)) = overlay_cameras();
+        let project = |position| {
+            project_to_overlay(
+                &camera_3d,
+                &camera_3d_gt,
+                &camera_2d,
+                &camera_2d_gt,
+                position,
+            )
+        };
+        assert!(project(Vec3::new(0.0, 0.0, -10.0)).is_some());
+        assert_eq!(project(Vec3::new(0.0, 0.0, 10.0)), None);
+        assert_eq!(project(Vec3::new(100.0, 0.0, 10.0)), None);
+    }
+
+    #[test]
+    fn targets_off_the_edge_are_projected_offscreen() {
+        let ((camera_3d, camera_3d_gt), (camera_2d, camera_2d_gt)) = overlay_cameras();
+        let project = |position| {
+            project_to_overlay(
+                &camera_3d,
+                &camera_3d_gt,
+                &camera_2d,
+                &camera_2d_gt,
+                position,
+            )
+        };
+
+        let ahead = project(Vec3::new(0.0, 0.0, -10.0)).unwrap();
+        assert!(ahead.onscreen);
+        assert!(ahead.position.abs_diff_eq(Vec2::ZERO, 1.0e-3));
+
+        let right = project(Vec3::new(1.0, 0.0, -10.0)).unwrap();
+        assert!(right.onscreen);
+        assert!(right.position.x > 0.0 && right.position.x < 640.0);
+
+        /* Ten times farther to the side than ahead is well outside any sane field of view */
+        let far_right = project(Vec3::new(100.0, 0.0, -10.0)).unwrap();
+        assert!(!far_right.onscreen);
+        assert!(far_right.position.x > 640.0);
+    }
+
+    #[test]
+    fn equidistant_targets_pick_the_lowest_entity() {
+        let onscreen_at = |x: f32| OverlayProjection {
+            position: Vec2::new(x, 0.0),
+            onscreen: true,
+        };
+        let left = (Entity::from_raw(7), onscreen_at(-10.0));
+        let right = (Entity::from_raw(3), onscreen_at(10.0));
+        assert_eq!(
+            pick_nearest_onscreen([left, right], Vec2::ZERO, 100.0),
+            Some((Entity::from_raw(3), Vec2::new(10.0, 0.0)))
+        );
+        assert_eq!(
+            pick_nearest_onscreen([right, left], Vec2::ZERO, 100.0),
+            Some((Entity::from_raw(3), Vec2::new(10.0, 0.0)))
+        );
+    }
+}
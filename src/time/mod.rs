@@ -0,0 +1,433 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::{RapierConfiguration, TimestepMode};
+
+use crate::input::{Action, KeyBindings};
+
+/// Simulation speed multiplier applied to Rapier's timestep, kept within `min..=max`.
+#[derive(Resource, Clone, Debug)]
+pub struct TimeScale {
+    scale: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            min: 0.001953125,
+            max: 512.0,
+        }
+    }
+}
+
+impl TimeScale {
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(self.min, self.max);
+        debug!("time_scale: {:?}", self.scale);
+    }
+
+    pub fn multiply_time_scale(&mut self, factor: f32) {
+        self.set_time_scale(self.scale * factor);
+    }
+}
+
+/// Sent whenever the effective `TimeScale` changes.
+#[derive(Event, Debug)]
+pub struct TimeScaleChanged {
+    pub new_scale: f32,
+}
+
+/// Seconds of simulated time since startup: real time multiplied by the `TimeScale`, standing still while the
+/// `PhysicsPause` holds the physics pipeline.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct SimulationTime {
+    pub elapsed_s: f64,
+}
+
+/// Freezes the physics pipeline, optionally letting it run for single frames while frozen.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PhysicsPause {
+    paused: bool,
+    step_requested: bool,
+}
+
+impl PhysicsPause {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step_requested = false;
+    }
+
+    pub fn toggle(&mut self) {
+        match self.paused {
+            true => self.resume(),
+            false => self.pause(),
+        }
+    }
+
+    /// Runs the physics pipeline for exactly one frame and pauses again. Pauses first if running.
+    pub fn step_once(&mut self) {
+        self.paused = true;
+        self.step_requested = true;
+    }
+}
+
+pub struct TimeScalePlugin;
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<SimulationTimePlugin>() {
+            app.add_plugins(SimulationTimePlugin);
+        }
+        app.init_resource::<TimeScale>()
+            .init_resource::<PhysicsPause>()
+            .init_resource::<KeyBindings>()
+            .add_event::<TimeScaleChanged>()
+            .add_systems(
+                PreUpdate,
+                (
+                    apply_time_scale.run_if(resource_changed::<TimeScale>),
+                    apply_physics_pause.before(advance_simulation_time),
+                ),
+            )
+            .add_systems(
+                Update,
+                cycle_timestep_mode.run_if(not(resource_exists::<DeterministicSimulation>)),
+            );
+    }
+}
+
+/// Enables the physics pipeline while running, or for the single frame after `PhysicsPause::step_once`.
+pub fn apply_physics_pause(
+    mut physics_pause: ResMut<PhysicsPause>,
+    mut rapier_configuration: ResMut<RapierConfiguration>,
+) {
+    let step = std::mem::take(&mut physics_pause.bypass_change_detection().step_requested);
+    let active = !physics_pause.paused || step;
+    if rapier_configuration.physics_pipeline_active != active {
+        rapier_configuration.physics_pipeline_active = active;
+    }
+}
+
+/// Keeps `SimulationTime` running. Added by `TimeScalePlugin` and `KeplerOrbitPlugin`; without the former the clock
+/// simply follows real time.
+pub struct SimulationTimePlugin;
+impl Plugin for SimulationTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationTime>()
+            .add_systems(
+                PreUpdate,
+                advance_simulation_time.run_if(not(resource_exists::<DeterministicSimulation>)),
+            )
+            .add_systems(
+                FixedUpdate,
+                advance_simulation_time.run_if(resource_exists::<DeterministicSimulation>),
+            );
+    }
+}
+
+/// Runs after `apply_physics_pause`, so a single stepped frame advances the clock too. Under `DeterministicSimulation`
+/// the clock runs at 1x, like Rapier's fixed timestep, whatever the `TimeScale`.
+pub fn advance_simulation_time(
+    time: Res<Time>,
+    time_scale: Option<Res<TimeScale>>,
+    deterministic: Option<Res<DeterministicSimulation>>,
+    rapier_configuration: Option<Res<RapierConfiguration>>,
+    mut simulation_time: ResMut<SimulationTime>,
+) {
+    if rapier_configuration.map_or(true, |configuration| configuration.physics_pipeline_active) {
+        let scale = match (deterministic, time_scale) {
+            (None, Some(time_scale)) => time_scale.scale(),
+            _ => 1.0,
+        };
+        simulation_time.elapsed_s += time.delta_seconds_f64() * scale as f64;
+    }
+}
+
+/// Copies the current `TimeScale` into Rapier's timestep mode and announces it with `TimeScaleChanged`.
+/// Fixed timesteps have no scale and are left alone.
+pub fn apply_time_scale(
+    time_scale: Res<TimeScale>,
+    mut rapier_configuration: ResMut<RapierConfiguration>,
+    mut last_scale: Local<Option<f32>>,
+    mut time_scale_changed: EventWriter<TimeScaleChanged>,
+) {
+    if *last_scale != Some(time_scale.scale()) {
+        *last_scale = Some(time_scale.scale());
+        time_scale_changed.send(TimeScaleChanged {
+            new_scale: time_scale.scale(),
+        });
+    }
+
+    match &mut rapier_configuration.timestep_mode {
+        TimestepMode::Interpolated {
+            time_scale: rapier_time_scale,
+            ..
+        }
+        | TimestepMode::Variable {
+            time_scale: rapier_time_scale,
+            ..
+        } => *rapier_time_scale = time_scale.scale(),
+        TimestepMode::Fixed { .. } => {}
+    }
+}
+
+/// The mode after `mode` in the cycle Interpolated, Fixed, Variable, keeping its step size and substeps. Modes that
+/// scale time take `time_scale`.
+pub fn next_timestep_mode(mode: &TimestepMode, time_scale: f32) -> TimestepMode {
+    match *mode {
+        TimestepMode::Interpolated { dt, substeps, .. } => TimestepMode::Fixed { dt, substeps },
+        TimestepMode::Fixed { dt, substeps } => TimestepMode::Variable {
+            max_dt: dt,
+            time_scale,
+            substeps,
+        },
+        TimestepMode::Variable {
+            max_dt, substeps, ..
+        } => TimestepMode::Interpolated {
+            dt: max_dt,
+            time_scale,
+            substeps,
+        },
+    }
+}
+
+/// Switches Rapier to the next timestep mode on `Action::CycleTimestepMode`. Interpolated is smoothest, Fixed is
+/// deterministic but can't be fast-forwarded, Variable keeps up with high time scales at the cost of both.
+pub fn cycle_timestep_mode(
+    key: Res<ButtonInput<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    time_scale: Res<TimeScale>,
+    mut rapier_configuration: ResMut<RapierConfiguration>,
+) {
+    if !key_bindings.just_pressed(&key, Action::CycleTimestepMode) {
+        return;
+    }
+    let mode = next_timestep_mode(&rapier_configuration.timestep_mode, time_scale.scale());
+    if matches!(mode, TimestepMode::Fixed { .. }) && time_scale.scale() != 1.0 {
+        warn!(
+            "fixed timestep can't scale time, running at 1x instead of {}x",
+            time_scale.scale()
+        );
+    }
+    debug!("timestep mode: {:?}", mode);
+    rapier_configuration.timestep_mode = mode;
+}
+
+/// Steps the simulation on a fixed clock so the same inputs always produce the same trajectories: Rapier gets a
+/// `TimestepMode::Fixed` of `dt` seconds split into `substeps`, `Time<Fixed>` ticks at the same rate, and the crate's
+/// own integrators (gravity, Kepler orbits, `SimulationTime`) move to `FixedUpdate`. Add Rapier with
+/// `RapierPhysicsPlugin::in_fixed_schedule()` so its steps line up with theirs.
+#[derive(Resource, Clone, Debug)]
+pub struct DeterministicSimulation {
+    pub dt: f32,
+    pub substeps: usize,
+}
+
+impl Default for DeterministicSimulation {
+    fn default() -> Self {
+        Self {
+            dt: 1.0 / 64.0,
+            substeps: 1,
+        }
+    }
+}
+
+/// Adds `DeterministicSimulation`; plugins that integrate anything check for it to pick their schedule.
+pub struct DeterministicPlugin;
+impl Plugin for DeterministicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeterministicSimulation>()
+            .add_systems(PreStartup, configure_deterministic_timestep);
+    }
+}
+
+pub fn configure_deterministic_timestep(
+    settings: Res<DeterministicSimulation>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut rapier_configuration: ResMut<RapierConfiguration>,
+) {
+    fixed_time.set_timestep_seconds(settings.dt as f64);
+    rapier_configuration.timestep_mode = TimestepMode::Fixed {
+        dt: settings.dt,
+        substeps: settings.substeps,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{scene::ScenePlugin, time::TimeUpdateStrategy};
+    use bevy_rapier3d::prelude::*;
+    use big_space::{FloatingOrigin, FloatingOriginPlugin, GridCell};
+
+    use super::*;
+    use crate::{
+        gravity::{Attracted, GravityAttractor, GravityPlugin},
+        orbits::{KeplerOrbit, KeplerOrbitPlugin},
+    };
+
+    fn kepler_orbit() -> KeplerOrbit {
+        let mut orbit = KeplerOrbit {
+            semi_major_axis: 10.0,
+            eccentricity: 0.3,
+            inclination: 0.2,
+            raan: 0.0,
+            arg_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            period: 0.0,
+        };
+        orbit.period = orbit.orbital_period(50.0).unwrap();
+        orbit
+    }
+
+    /// Drops a spinning ball onto a box resting on the ground, swings a body around a point mass under `apply_gravity`
+    /// and moves another along a `KeplerOrbit`, all with `scale` as the `TimeScale`, and returns where everything
+    /// ends up.
+    fn run_deterministic_scenario(scale: f32) -> Vec<(GridCell<i64>, Transform)> {
+        let mut time_scale = TimeScale::default();
+        time_scale.set_time_scale(scale);
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            FloatingOriginPlugin::<i64>::default(),
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+            RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule(),
+            DeterministicPlugin,
+            GravityPlugin,
+            KeplerOrbitPlugin,
+        ))
+        .init_asset::<Mesh>()
+        .insert_resource(time_scale)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            DeterministicSimulation::default().dt,
+        )));
+        app.world.spawn((
+            FloatingOrigin,
+            GridCell::<i64>::ZERO,
+            SpatialBundle::default(),
+        ));
+        app.world.spawn((
+            RigidBody::Fixed,
+            Collider::cuboid(10.0, 0.5, 10.0),
+            GridCell::<i64>::ZERO,
+            TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)),
+        ));
+        app.world.spawn((
+            RigidBody::Dynamic,
+            Collider::cuboid(0.5, 0.5, 0.5),
+            GridCell::<i64>::ZERO,
+            TransformBundle::from(Transform::from_xyz(0.2, 0.5, 0.0)),
+        ));
+        app.world.spawn((
+            RigidBody::Dynamic,
+            Collider::ball(0.3),
+            Velocity {
+                linvel: Vec3::new(-0.5, 0.0, 0.25),
+                angvel: Vec3::new(1.0, 2.0, 3.0),
+            },
+            GridCell::<i64>::ZERO,
+            TransformBundle::from(Transform::from_xyz(0.0, 4.0, 0.1)),
+        ));
+        /* Well clear of the ground, with Rapier's own gravity off so only the point mass pulls on it */
+        app.world.spawn((
+            GravityAttractor { mu: 50.0 },
+            GridCell::<i64>::ZERO,
+            TransformBundle::from(Transform::from_xyz(40.0, 0.0, 0.0)),
+        ));
+        app.world.spawn((
+            RigidBody::Dynamic,
+            Collider::ball(0.2),
+            Attracted,
+            GravityScale(0.0),
+            ReadMassProperties::default(),
+            ExternalForce::default(),
+            Velocity::linear(Vec3::new(3.0, 0.0, 0.0)),
+            GridCell::<i64>::ZERO,
+            TransformBundle::from(Transform::from_xyz(40.0, 0.0, 5.0)),
+        ));
+        app.world.spawn((
+            kepler_orbit(),
+            GridCell::<i64>::ZERO,
+            SpatialBundle::default(),
+        ));
+        for _ in 0..256 {
+            app.update();
+        }
+        let mut body_query = app
+            .world
+            .query_filtered::<(Entity, &GridCell<i64>, &Transform), Or<(
+                With<RigidBody>,
+                With<KeplerOrbit>,
+            )>>();
+        let mut bodies: Vec<_> = body_query.iter(&app.world).collect();
+        bodies.sort_by_key(|(entity, _, _)| *entity);
+        bodies
+            .into_iter()
+            .map(|(_, cell, transform)| (*cell, *transform))
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_runs_end_in_identical_positions() {
+        let first = run_deterministic_scenario(1.0);
+        let second = run_deterministic_scenario(1.0);
+        assert_eq!(first.len(), 5);
+        /* Something has to have moved for the comparison to mean anything */
+        assert_ne!(first[2].1.translation, Vec3::new(0.0, 4.0, 0.1));
+        assert_ne!(first[3].1.translation, Vec3::new(40.0, 0.0, 5.0));
+        assert_ne!(
+            first[4].1.translation,
+            kepler_orbit().position_at(0.0).as_vec3()
+        );
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn deterministic_runs_ignore_time_scale() {
+        assert_eq!(
+            run_deterministic_scenario(1.0),
+            run_deterministic_scenario(8.0)
+        );
+    }
+
+    #[test]
+    fn set_time_scale_clamps_to_bounds() {
+        let mut time_scale = TimeScale::default();
+        time_scale.set_time_scale(4.0);
+        assert_eq!(time_scale.scale(), 4.0);
+        time_scale.set_time_scale(1.0e6);
+        assert_eq!(time_scale.scale(), time_scale.max);
+        time_scale.set_time_scale(0.0);
+        assert_eq!(time_scale.scale(), time_scale.min);
+    }
+
+    #[test]
+    fn multiply_time_scale_stops_at_bounds() {
+        let mut time_scale = TimeScale::default();
+        for _ in 0..20 {
+            time_scale.multiply_time_scale(2.0);
+        }
+        assert_eq!(time_scale.scale(), time_scale.max);
+        time_scale.multiply_time_scale(0.5);
+        assert_eq!(time_scale.scale(), time_scale.max / 2.0);
+        for _ in 0..40 {
+            time_scale.multiply_time_scale(0.5);
+        }
+        assert_eq!(time_scale.scale(), time_scale.min);
+    }
+}
@@ -0,0 +1,372 @@
+use bevy::{math::DVec3, prelude::*};
+use bevy_rapier3d::prelude::*;
+use big_space::{
+    camera::CameraController, reference_frame::RootReferenceFrame, FloatingOrigin, GridCell,
+};
+
+use crate::{
+    camera::{target_grid_position, TargetPositionQuery},
+    gravity::{gravitational_acceleration, Attracted, GravityAttractor},
+    targeting::CurrentTarget,
+};
+
+/// Path an `Attracted` body will follow under gravity alone, as absolute grid positions in meters spaced
+/// `TrajectorySettings::dt` seconds apart, starting at the body's current position.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PredictedTrajectory {
+    pub points: Vec<DVec3>,
+}
+
+#[derive(Resource, Clone)]
+pub struct TrajectorySettings {
+    pub visible: bool,
+    /// Number of integration steps predicted ahead of each body.
+    pub steps: usize,
+    /// Length of each integration step, in seconds.
+    pub dt: f64,
+    pub color: Color,
+    pub closest_approach_color: Color,
+    /// Radius of the closest approach marker as a fraction of its distance from the camera.
+    pub closest_approach_marker_scale: f32,
+}
+
+impl Default for TrajectorySettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            steps: 256,
+            dt: 0.25,
+            color: Color::rgba(1.0, 0.6, 0.2, 0.8),
+            closest_approach_color: Color::rgba(1.0, 0.2, 0.2, 0.9),
+            closest_approach_marker_scale: 0.01,
+        }
+    }
+}
+
+/// Where the floating origin camera's predicted path passes nearest the `CurrentTarget`'s.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Approach {
+    pub target: Entity,
+    /// Seconds from now.
+    pub time_s: f64,
+    pub distance_m: f64,
+    /// Absolute grid positions of the camera and the target at that moment.
+    pub vessel_position: DVec3,
+    pub target_position: DVec3,
+}
+
+/// The predicted closest approach to the current target, `None` without a target or a camera trajectory.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ClosestApproach(pub Option<Approach>);
+
+/// Predicts and draws the trajectory of every dynamic `Attracted` body with a `Velocity` and of the floating origin
+/// camera, using the default gizmos, and marks where the camera's path passes closest to the `CurrentTarget`.
+pub struct TrajectoryPlugin;
+impl Plugin for TrajectoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrajectorySettings>()
+            .init_resource::<CurrentTarget>()
+            .init_resource::<ClosestApproach>()
+            .add_systems(
+                PostUpdate,
+                (
+                    (predict_trajectories, predict_vessel_trajectory),
+                    update_closest_approach,
+                    (draw_trajectories, draw_closest_approach),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Time in seconds, from the moment `relative_position` and `relative_velocity` are taken, at which two bodies moving
+/// in straight lines are nearest each other. Negative when they are already separating.
+pub fn time_to_closest_approach(relative_position: DVec3, relative_velocity: DVec3) -> f64 {
+    let speed_squared = relative_velocity.length_squared();
+    if speed_squared == 0.0 {
+        return 0.0;
+    }
+    -relative_position.dot(relative_velocity) / speed_squared
+}
+
+/// Closest approach between two trajectories sampled `dt` seconds apart from the same moment. The nearest pair of
+/// samples is found first, then the segments either side of it are refined treating each as a straight line.
+/// A shorter `target` path holds its last point. Returns the time, distance and both positions.
+pub fn closest_approach(
+    vessel: &[DVec3],
+    target: &[DVec3],
+    dt: f64,
+) -> Option<(f64, f64, DVec3, DVec3)> {
+    let target_at = |index: usize| target.get(index).or(target.last()).copied();
+    let nearest = (0..vessel.len())
+        .filter_map(|index| Some((index, vessel[index].distance(target_at(index)?))))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?
+        .0;
+    let mut best = (
+        nearest as f64 * dt,
+        vessel[nearest].distance(target_at(nearest)?),
+        vessel[nearest],
+        target_at(nearest)?,
+    );
+    for start in [nearest.saturating_sub(1), nearest] {
+        let end = start + 1;
+        if end >= vessel.len() || dt <= 0.0 {
+            continue;
+        }
+        let (vessel_start, target_start) = (vessel[start], target_at(start)?);
+        let vessel_velocity = (vessel[end] - vessel_start) / dt;
+        let target_velocity = (target_at(end)? - target_start) / dt;
+        let t = time_to_closest_approach(
+            vessel_start - target_start,
+            vessel_velocity - target_velocity,
+        )
+        .clamp(0.0, dt);
+        let vessel_position = vessel_start + vessel_velocity * t;
+        let target_position = target_start + target_velocity * t;
+        let distance = vessel_position.distance(target_position);
+        if distance < best.1 {
+            best = (
+                start as f64 * dt + t,
+                distance,
+                vessel_position,
+                target_position,
+            );
+        }
+    }
+    Some(best)
+}
+
+/// Integrates a body starting at `position` with `velocity` forward `steps` steps of `dt` seconds with velocity
+/// Verlet, against point masses given as `(position, mu)`. The attractors are held where they are. The returned
+/// points start with `position`.
+pub fn predict_trajectory(
+    position: DVec3,
+    velocity: DVec3,
+    attractors: &[(DVec3, f64)],
+    steps: usize,
+    dt: f64,
+) -> Vec<DVec3> {
+    let acceleration_at = |position: DVec3| -> DVec3 {
+        attractors
+            .iter()
+            .map(|(attractor_position, mu)| {
+                gravitational_acceleration(position, *attractor_position, *mu)
+            })
+            .sum()
+    };
+    let mut points = Vec::with_capacity(steps + 1);
+    let (mut position, mut velocity) = (position, velocity);
+    let mut acceleration = acceleration_at(position);
+    points.push(position);
+    for _ in 0..steps {
+        velocity += acceleration * (dt / 2.0);
+        position += velocity * dt;
+        acceleration = acceleration_at(position);
+        velocity += acceleration * (dt / 2.0);
+        points.push(position);
+    }
+    points
+}
+
+#[allow(clippy::type_complexity)]
+pub fn predict_trajectories(
+    mut commands: Commands,
+    attractor_query: Query<(Entity, &GravityAttractor, &GridCell<i64>, &Transform)>,
+    mut body_query: Query<
+        (
+            Entity,
+            &RigidBody,
+            &Velocity,
+            &GridCell<i64>,
+            &Transform,
+            Option<&mut PredictedTrajectory>,
+        ),
+        With<Attracted>,
+    >,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<TrajectorySettings>,
+) {
+    if !settings.visible {
+        return;
+    }
+    for (each_entity, each_rigid_body, each_velocity, each_cell, each_transform, each_trajectory) in
+        body_query.iter_mut()
+    {
+        if *each_rigid_body != RigidBody::Dynamic {
+            continue;
+        }
+        let attractors: Vec<(DVec3, f64)> = attractor_query
+            .iter()
+            .filter(|(attractor_entity, _, _, _)| *attractor_entity != each_entity)
+            .map(|(_, attractor, cell, transform)| {
+                (space.grid_position_double(cell, transform), attractor.mu)
+            })
+            .collect();
+        let points = predict_trajectory(
+            space.grid_position_double(each_cell, each_transform),
+            each_velocity.linvel.as_dvec3(),
+            &attractors,
+            settings.steps,
+            settings.dt,
+        );
+        match each_trajectory {
+            Some(mut trajectory) => trajectory.points = points,
+            None => {
+                commands
+                    .entity(each_entity)
+                    .insert(PredictedTrajectory { points });
+            }
+        }
+    }
+}
+
+/// Predicts the floating origin camera's path from its current velocity, as if it were coasting under gravity.
+pub fn predict_vessel_trajectory(
+    mut commands: Commands,
+    attractor_query: Query<(&GravityAttractor, &GridCell<i64>, &Transform)>,
+    mut camera_query: Query<
+        (
+            Entity,
+            &CameraController,
+            &GridCell<i64>,
+            &Transform,
+            Option<&mut PredictedTrajectory>,
+        ),
+        With<FloatingOrigin>,
+    >,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<TrajectorySettings>,
+    time: Res<Time>,
+) {
+    if !settings.visible || time.delta_seconds_f64() <= 0.0 {
+        return;
+    }
+    let Ok((camera_entity, camera_controller, camera_cell, camera_transform, camera_trajectory)) =
+        camera_query.get_single_mut()
+    else {
+        return;
+    };
+    let attractors: Vec<(DVec3, f64)> = attractor_query
+        .iter()
+        .map(|(attractor, cell, transform)| {
+            (space.grid_position_double(cell, transform), attractor.mu)
+        })
+        .collect();
+    /* The controller reports the displacement for the frame */
+    let velocity = camera_controller.velocity().0 / time.delta_seconds_f64();
+    let points = predict_trajectory(
+        space.grid_position_double(camera_cell, camera_transform),
+        velocity,
+        &attractors,
+        settings.steps,
+        settings.dt,
+    );
+    match camera_trajectory {
+        Some(mut trajectory) => trajectory.points = points,
+        None => {
+            commands
+                .entity(camera_entity)
+                .insert(PredictedTrajectory { points });
+        }
+    }
+}
+
+/// Finds the closest approach between the camera's predicted path and the current target's. A target without a
+/// `PredictedTrajectory` is treated as staying where it is.
+pub fn update_closest_approach(
+    mut closest: ResMut<ClosestApproach>,
+    current_target: Res<CurrentTarget>,
+    camera_query: Query<&PredictedTrajectory, With<FloatingOrigin>>,
+    target_trajectory_query: Query<&PredictedTrajectory, Without<FloatingOrigin>>,
+    target_position_query: TargetPositionQuery,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<TrajectorySettings>,
+) {
+    let vessel = camera_query.get_single().ok();
+    let approach = current_target
+        .target
+        .zip(vessel)
+        .and_then(|(target, vessel)| {
+            let target_points = match target_trajectory_query.get(target) {
+                Ok(trajectory) => trajectory.points.clone(),
+                Err(_) => vec![target_grid_position(
+                    &target_position_query,
+                    &space,
+                    target,
+                )?],
+            };
+            let (time_s, distance_m, vessel_position, target_position) =
+                closest_approach(&vessel.points, &target_points, settings.dt)?;
+            Some(Approach {
+                target,
+                time_s,
+                distance_m,
+                vessel_position,
+                target_position,
+            })
+        });
+    closest.set_if_neq(ClosestApproach(approach));
+}
+
+pub fn draw_trajectories(
+    trajectory_query: Query<&PredictedTrajectory>,
+    floating_origin_query: Query<&GridCell<i64>, With<FloatingOrigin>>,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<TrajectorySettings>,
+    mut default_gizmos: Gizmos,
+) {
+    if !settings.visible {
+        return;
+    }
+    let Ok(origin_cell) = floating_origin_query.get_single() else {
+        return;
+    };
+    /* Subtract in f64 before narrowing so long paths don't jitter far from the origin */
+    let origin_position = space.grid_position_double(origin_cell, &Transform::IDENTITY);
+    for each_trajectory in trajectory_query.iter() {
+        default_gizmos.linestrip(
+            each_trajectory
+                .points
+                .iter()
+                .map(|point| (*point - origin_position).as_vec3()),
+            settings.color,
+        );
+    }
+}
+
+/// Marks the closest approach with a circle facing the camera at the vessel's predicted position and a line to where
+/// the target will be.
+pub fn draw_closest_approach(
+    closest: Res<ClosestApproach>,
+    floating_origin_query: Query<(&GridCell<i64>, &Transform), With<FloatingOrigin>>,
+    space: Res<RootReferenceFrame<i64>>,
+    settings: Res<TrajectorySettings>,
+    mut default_gizmos: Gizmos,
+) {
+    if !settings.visible {
+        return;
+    }
+    let Some(approach) = closest.0 else {
+        return;
+    };
+    let Ok((origin_cell, origin_transform)) = floating_origin_query.get_single() else {
+        return;
+    };
+    let origin_position = space.grid_position_double(origin_cell, &Transform::IDENTITY);
+    let vessel_position = (approach.vessel_position - origin_position).as_vec3();
+    let target_position = (approach.target_position - origin_position).as_vec3();
+    let to_camera = origin_transform.translation - vessel_position;
+    let radius = to_camera.length() * settings.closest_approach_marker_scale;
+    let normal = Direction3d::new(to_camera).unwrap_or(Direction3d::Y);
+    default_gizmos.circle(
+        vessel_position,
+        normal,
+        radius,
+        settings.closest_approach_color,
+    );
+    default_gizmos.line(
+        vessel_position,
+        target_position,
+        settings.closest_approach_color,
+    );
+}
@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+/// Position on the 2D overlay camera's world plane of `world_position` as seen by `camera_3d`, for placing reticles
+/// and other overlay markers. Returns `None` when the position is behind `camera_3d` or can't be projected, so a
+/// `None` should always hide the marker. Positions off the edge of the screen are still returned, use `is_onscreen`
+/// to tell them apart.
+pub fn overlay_position(
+    camera_3d: &Camera,
+    camera_3d_global_transform: &GlobalTransform,
+    camera_2d: &Camera,
+    camera_2d_global_transform: &GlobalTransform,
+    world_position: Vec3,
+) -> Option<Vec2> {
+    let viewport_position =
+        camera_3d.world_to_viewport(camera_3d_global_transform, world_position)?;
+    camera_2d.viewport_to_world_2d(camera_2d_global_transform, viewport_position)
+}
+
+/// Whether `world_position` is in front of `camera_3d` and inside its viewport.
+pub fn is_onscreen(
+    camera_3d: &Camera,
+    camera_3d_global_transform: &GlobalTransform,
+    world_position: Vec3,
+) -> bool {
+    match (
+        camera_3d.world_to_viewport(camera_3d_global_transform, world_position),
+        camera_3d.logical_viewport_rect(),
+    ) {
+        (Some(viewport_position), Some(viewport_rect)) => viewport_rect.contains(viewport_position),
+        _ => false,
+    }
+}